@@ -0,0 +1,105 @@
+//! Helpers shared by the parsers to read the fields of binary formats.
+//!
+//! The `read_*` functions panic if the field is out of bounds, so they must only be used on data whose length was checked
+//! (e.g. a header read in full, or a slice returned by [`get()`]).
+
+use std::io::{self, Read};
+
+/// Returns the `len` bytes at `offset`, or an error of kind [`io::ErrorKind::InvalidData`] if they are out of bounds.
+pub(crate) fn get(data: &[u8], offset: usize, len: usize) -> io::Result<&[u8]> {
+    data.get(offset..)
+        .and_then(|data| data.get(..len))
+        .ok_or_else(|| invalid_data("unexpected end of data"))
+}
+
+/// Reads exactly `len` bytes from `reader`.
+///
+/// Unlike reading into a buffer of `len` bytes, the buffer only grows as data is read,
+/// so a corrupted length can't make the parser allocate more memory than the size of the stream.
+pub(crate) fn read_vec<R: Read>(reader: R, len: u64) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.take(len).read_to_end(&mut data)?;
+
+    if data.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(data)
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+pub(crate) fn read_u16_be(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+pub(crate) fn read_u32_be(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+pub(crate) fn read_u64_be(data: &[u8], offset: usize) -> u64 {
+    u64::from_be_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Reads a NUL-padded string, replacing invalid UTF-8 sequences.
+pub(crate) fn read_string(data: &[u8]) -> String {
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+
+    String::from_utf8_lossy(&data[..len]).into_owned()
+}
+
+pub(crate) fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_bounds() {
+        let data = [1, 2, 3, 4];
+
+        assert_eq!(get(&data, 1, 3).unwrap(), &[2, 3, 4]);
+        assert_eq!(get(&data, 4, 0).unwrap(), &[]);
+        assert!(get(&data, 2, 3).is_err());
+        assert!(get(&data, 5, 0).is_err());
+        assert!(get(&data, usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn read_exact_len() {
+        let data = [1, 2, 3, 4];
+
+        assert_eq!(read_vec(&data[..], 3).unwrap(), [1, 2, 3]);
+        assert_eq!(
+            read_vec(&data[..], u64::MAX).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn fields() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+        assert_eq!(read_u16(&data, 1), 0x0302);
+        assert_eq!(read_u32(&data, 0), 0x0403_0201);
+        assert_eq!(read_u64(&data, 0), 0x0807_0605_0403_0201);
+        assert_eq!(read_u16_be(&data, 1), 0x0203);
+        assert_eq!(read_u32_be(&data, 0), 0x0102_0304);
+        assert_eq!(read_u64_be(&data, 0), 0x0102_0304_0506_0708);
+
+        assert_eq!(read_string(b"abc\0\0\0"), "abc");
+        assert_eq!(read_string(b"abc"), "abc");
+    }
+}
@@ -0,0 +1,107 @@
+//! Values of the console's system configuration.
+//!
+//! These are also stored in some files, such as the per-language titles and the region lock of [SMDH](crate::smdh) files.
+//! The raw values match the `CFG_*` constants of `libctru`.
+
+/// Console region.
+#[doc(alias = "CFG_Region")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Region {
+    /// Japan.
+    Japan = 0,
+    /// USA.
+    USA = 1,
+    /// Europe.
+    Europe = 2,
+    /// Australia.
+    Australia = 3,
+    /// China.
+    China = 4,
+    /// Korea.
+    Korea = 5,
+    /// Taiwan.
+    Taiwan = 6,
+}
+
+/// Language set for the console's OS.
+#[doc(alias = "CFG_Language")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum Language {
+    /// Japanese.
+    Japanese = 0,
+    /// English.
+    English = 1,
+    /// French.
+    French = 2,
+    /// German.
+    German = 3,
+    /// Italian.
+    Italian = 4,
+    /// Spanish.
+    Spanish = 5,
+    /// Korean.
+    Korean = 7,
+    /// Dutch.
+    Dutch = 8,
+    /// Portuguese.
+    Portuguese = 9,
+    /// Russian.
+    Russian = 10,
+    /// Simplified Chinese.
+    SimplifiedChinese = 6,
+    /// Traditional Chinese.
+    TraditionalChinese = 11,
+}
+
+impl From<Region> for u8 {
+    fn from(v: Region) -> Self {
+        v as u8
+    }
+}
+
+impl From<Language> for u8 {
+    fn from(v: Language) -> Self {
+        v as u8
+    }
+}
+
+impl TryFrom<u8> for Region {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Region::Japan),
+            1 => Ok(Region::USA),
+            2 => Ok(Region::Europe),
+            3 => Ok(Region::Australia),
+            4 => Ok(Region::China),
+            5 => Ok(Region::Korea),
+            6 => Ok(Region::Taiwan),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<u8> for Language {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Language::Japanese),
+            1 => Ok(Language::English),
+            2 => Ok(Language::French),
+            3 => Ok(Language::German),
+            4 => Ok(Language::Italian),
+            5 => Ok(Language::Spanish),
+            6 => Ok(Language::SimplifiedChinese),
+            7 => Ok(Language::Korean),
+            8 => Ok(Language::Dutch),
+            9 => Ok(Language::Portuguese),
+            10 => Ok(Language::Russian),
+            11 => Ok(Language::TraditionalChinese),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,778 @@
+//! CFNT fonts and text rendering.
+//!
+//! CFNT is the format of the console's shared system font, which covers the characters of the console's region
+//! (`ctru-rs` maps it with `Font::system()`), and of the `.bcfnt` files read by [`Font::parse()`].
+//!
+//! A CFNT font is made of:
+//! - Glyph sheets (TGLP), holding the images of the glyphs as tiled GPU textures.
+//! - Width tables (CWDH), giving the horizontal metrics of each glyph.
+//! - Character maps (CMAP), giving the index of the glyph of each character.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::cfnt::Font;
+//! use ctru_formats::gfx::pixel::Rgba8;
+//! use ctru_formats::gfx::surface::Surface;
+//! use ctru_formats::gfx::FramebufferFormat;
+//!
+//! let font = Font::parse(std::fs::read("font.bcfnt")?)?;
+//!
+//! let mut framebuffer = vec![0; Surface::buffer_len(320, 240, FramebufferFormat::Bgr8)];
+//! let mut surface = Surface::new(&mut framebuffer, 320, 240, FramebufferFormat::Bgr8);
+//!
+//! font.draw_text(&mut surface, "こんにちは、世界！", 10, 10, Rgba8::opaque(255, 255, 255));
+//! #
+//! # Ok(())
+//! # }
+//! ```
+#![doc(alias = "CFNT")]
+#![doc(alias = "bcfnt")]
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::io;
+
+use crate::bytes::{get, invalid_data, read_u16, read_u32};
+use crate::gfx::draw;
+use crate::gfx::pixel::Rgba8;
+use crate::gfx::surface::Surface;
+use crate::gfx::tiling;
+
+// Size of the CFNT header, which is followed by the FINF block.
+const HEADER_LEN: usize = 0x14;
+
+// Glyph index of the characters without a glyph in a character map.
+const NO_GLYPH: u16 = 0xFFFF;
+
+// Formats of the glyph sheets (GPU texture formats).
+const SHEET_L8: u16 = 0x7;
+const SHEET_A8: u16 = 0x8;
+const SHEET_L4: u16 = 0xA;
+const SHEET_A4: u16 = 0xB;
+
+/// Horizontal metrics of a glyph.
+#[doc(alias = "charWidthInfo_s")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CharWidth {
+    /// Horizontal offset of the glyph image from the pen position.
+    pub left: i8,
+    /// Width of the glyph image.
+    pub glyph_width: u8,
+    /// Distance to advance the pen by after drawing the glyph.
+    pub char_width: u8,
+}
+
+impl CharWidth {
+    fn parse(data: &[u8]) -> Self {
+        Self {
+            left: data[0] as i8,
+            glyph_width: data[1],
+            char_width: data[2],
+        }
+    }
+}
+
+// A range of glyph widths (CWDH block).
+#[derive(Clone, Debug)]
+struct WidthTable {
+    start: u16,
+    widths: Vec<CharWidth>,
+}
+
+// A range of mapped characters (CMAP block).
+#[derive(Clone, Debug)]
+enum CharMap {
+    // Consecutive characters mapped to consecutive glyphs.
+    Direct { begin: u16, end: u16, offset: u16 },
+    // Consecutive characters mapped to the glyphs in the table.
+    Table { begin: u16, indexes: Vec<u16> },
+    // Sparse characters, sorted by code.
+    Scan(Vec<(u16, u16)>),
+}
+
+impl CharMap {
+    fn glyph_index(&self, code: u16) -> Option<u16> {
+        let index = match self {
+            Self::Direct { begin, end, offset } => (*begin..=*end)
+                .contains(&code)
+                .then(|| (code - begin).wrapping_add(*offset))?,
+            Self::Table { begin, indexes } => *indexes.get(code.checked_sub(*begin)? as usize)?,
+            Self::Scan(entries) => {
+                let position = entries
+                    .binary_search_by_key(&code, |&(code, _)| code)
+                    .ok()?;
+                entries[position].1
+            }
+        };
+
+        (index != NO_GLYPH).then_some(index)
+    }
+}
+
+// Layout of the glyph sheets (TGLP block).
+#[derive(Clone, Debug)]
+struct Sheets {
+    cell_width: u8,
+    cell_height: u8,
+    baseline: u8,
+    format: u16,
+    size: usize,
+    count: u16,
+    glyphs_per_row: u16,
+    rows: u16,
+    width: u16,
+    height: u16,
+    offset: usize,
+}
+
+/// A CFNT font.
+pub struct Font {
+    data: Cow<'static, [u8]>,
+    line_feed: u8,
+    height: u8,
+    ascent: u8,
+    replacement_index: u16,
+    default_width: CharWidth,
+    sheets: Sheets,
+    widths: Vec<WidthTable>,
+    maps: Vec<CharMap>,
+}
+
+impl Font {
+    /// Parse a CFNT font (e.g. a `.bcfnt` file).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if `data` isn't a valid CFNT font,
+    /// and of kind [`io::ErrorKind::Unsupported`] if its glyph sheets are compressed or their format isn't supported.
+    pub fn parse(data: Vec<u8>) -> io::Result<Self> {
+        Self::from_data(Cow::Owned(data), 0)
+    }
+
+    /// Parse a CFNT font that was relocated in memory, so that its internal pointers are offsets from `base`
+    /// instead of from the start of the font.
+    ///
+    /// This is the case of the system font once it's mapped into a process, where `base` is the address of the font.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::parse()`].
+    pub fn parse_relocated(data: &'static [u8], base: usize) -> io::Result<Self> {
+        Self::from_data(Cow::Borrowed(data), base)
+    }
+
+    // Parse a font whose internal pointers are offsets from `base`.
+    fn from_data(data: Cow<'static, [u8]>, base: usize) -> io::Result<Self> {
+        let bytes = &*data;
+
+        if bytes.get(..4) != Some(b"CFNT") {
+            return Err(invalid_data("not a CFNT font"));
+        }
+        if bytes.get(HEADER_LEN..HEADER_LEN + 4) != Some(b"FINF") {
+            return Err(invalid_data("missing FINF block"));
+        }
+
+        let finf = HEADER_LEN + 8;
+        let info = bytes
+            .get(finf..finf + 0x18)
+            .ok_or_else(|| invalid_data("truncated FINF block"))?;
+
+        let line_feed = info[0x01];
+        let replacement_index = read_u16(info, 0x02);
+        let default_width = CharWidth::parse(&info[0x04..0x07]);
+        let tglp = pointer(bytes, read_u32(info, 0x08), base)?;
+        let mut cwdh = read_u32(info, 0x0C);
+        let mut cmap = read_u32(info, 0x10);
+        let height = info[0x14];
+        let ascent = info[0x16];
+
+        let sheets = parse_sheets(bytes, tglp, base)?;
+
+        // The CWDH and CMAP blocks are linked lists, which must not loop back to a block that was already read.
+        let mut visited = HashSet::new();
+
+        let mut widths = Vec::new();
+        while cwdh != 0 {
+            let offset = pointer(bytes, cwdh, base)?;
+            if !visited.insert(offset) {
+                return Err(invalid_data("loop in the CWDH blocks"));
+            }
+
+            let header = get(bytes, offset, 8)?;
+            let (start, end) = (read_u16(header, 0), read_u16(header, 2));
+            let count = (end as usize + 1)
+                .checked_sub(start as usize)
+                .ok_or_else(|| invalid_data("invalid CWDH block"))?;
+
+            widths.push(WidthTable {
+                start,
+                widths: get(bytes, offset + 8, count * 3)?
+                    .chunks_exact(3)
+                    .map(CharWidth::parse)
+                    .collect(),
+            });
+
+            cwdh = read_u32(header, 4);
+        }
+
+        let mut maps = Vec::new();
+        while cmap != 0 {
+            let offset = pointer(bytes, cmap, base)?;
+            if !visited.insert(offset) {
+                return Err(invalid_data("loop in the CMAP blocks"));
+            }
+
+            let header = get(bytes, offset, 12)?;
+            let (begin, end) = (read_u16(header, 0), read_u16(header, 2));
+            let method = read_u16(header, 4);
+            let entries = offset + 12;
+
+            maps.push(match method {
+                0 => CharMap::Direct {
+                    begin,
+                    end,
+                    offset: read_u16(get(bytes, entries, 2)?, 0),
+                },
+                1 => {
+                    let count = (end as usize + 1)
+                        .checked_sub(begin as usize)
+                        .ok_or_else(|| invalid_data("invalid CMAP block"))?;
+
+                    CharMap::Table {
+                        begin,
+                        indexes: get(bytes, entries, count * 2)?
+                            .chunks_exact(2)
+                            .map(|index| read_u16(index, 0))
+                            .collect(),
+                    }
+                }
+                2 => {
+                    let count = read_u16(get(bytes, entries, 2)?, 0) as usize;
+                    let mut entries: Vec<(u16, u16)> = get(bytes, entries + 2, count * 4)?
+                        .chunks_exact(4)
+                        .map(|entry| (read_u16(entry, 0), read_u16(entry, 2)))
+                        .collect();
+                    entries.sort_unstable();
+
+                    CharMap::Scan(entries)
+                }
+                _ => return Err(invalid_data("unknown CMAP mapping method")),
+            });
+
+            cmap = read_u32(header, 8);
+        }
+
+        Ok(Self {
+            data,
+            line_feed,
+            height,
+            ascent,
+            replacement_index,
+            default_width,
+            sheets,
+            widths,
+            maps,
+        })
+    }
+
+    /// Returns the distance between two lines of text, in pixels.
+    pub fn line_feed(&self) -> u8 {
+        self.line_feed
+    }
+
+    /// Returns the height of the font, in pixels.
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Returns the distance from the top of a line to the baseline, in pixels.
+    pub fn ascent(&self) -> u8 {
+        self.ascent
+    }
+
+    /// Returns the size of the glyph cells, in pixels.
+    pub fn cell_size(&self) -> (u8, u8) {
+        (self.sheets.cell_width, self.sheets.cell_height)
+    }
+
+    /// Returns the distance from the top of a glyph cell to the baseline, in pixels.
+    pub fn baseline(&self) -> u8 {
+        self.sheets.baseline
+    }
+
+    /// Returns the index of the glyph of a character, or `None` if the font doesn't have it.
+    #[doc(alias = "fontGlyphIndexFromCodePoint")]
+    pub fn glyph_index(&self, c: char) -> Option<u16> {
+        let code = u16::try_from(u32::from(c)).ok()?;
+
+        self.maps.iter().find_map(|map| map.glyph_index(code))
+    }
+
+    /// Returns the glyph of a character.
+    ///
+    /// If the font doesn't have the character, the replacement glyph of the font (usually `?`) is returned.
+    pub fn glyph(&self, c: char) -> Glyph<'_> {
+        let index = self.glyph_index(c).unwrap_or(self.replacement_index);
+
+        Glyph {
+            font: self,
+            index,
+            width: self.char_width(index),
+        }
+    }
+
+    // Metrics of the glyph with the given index.
+    fn char_width(&self, index: u16) -> CharWidth {
+        self.widths
+            .iter()
+            .find_map(|table| table.widths.get(index.checked_sub(table.start)? as usize))
+            .copied()
+            .unwrap_or(self.default_width)
+    }
+
+    /// Returns the size (in pixels) of the text, once drawn with [`Font::draw_text()`].
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let width = text
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| u32::from(self.glyph(c).width().char_width))
+                    .sum()
+            })
+            .max()
+            .unwrap_or(0);
+        let lines = text.split('\n').count() as u32;
+
+        (width, lines * u32::from(self.line_feed))
+    }
+
+    /// Draw text with its top-left corner at (`x`, `y`).
+    ///
+    /// Each glyph is drawn with the given color, blended with the surface according to the glyph's coverage of each pixel.
+    /// Line feeds (`'\n'`) move the pen to the start of the next line.
+    pub fn draw_text(&self, surface: &mut Surface, text: &str, x: i32, y: i32, color: Rgba8) {
+        let (mut pen_x, mut pen_y) = (x, y);
+
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y += i32::from(self.line_feed);
+                continue;
+            }
+
+            let glyph = self.glyph(c);
+            glyph.draw(surface, pen_x, pen_y, color);
+
+            pen_x += i32::from(glyph.width().char_width);
+        }
+    }
+}
+
+/// A glyph of a [`Font`].
+#[derive(Copy, Clone)]
+pub struct Glyph<'a> {
+    font: &'a Font,
+    index: u16,
+    width: CharWidth,
+}
+
+impl Glyph<'_> {
+    /// Returns the index of the glyph within the font.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Returns the horizontal metrics of the glyph.
+    pub fn width(&self) -> CharWidth {
+        self.width
+    }
+
+    /// Returns the coverage (from 0 to 255) of the pixel at (`x`, `y`) of the glyph's cell,
+    /// or `None` if it's outside of the cell.
+    pub fn coverage(&self, x: usize, y: usize) -> Option<u8> {
+        let sheets = &self.font.sheets;
+
+        if x >= sheets.cell_width.into() || y >= sheets.cell_height.into() {
+            return None;
+        }
+
+        let per_sheet = usize::from(sheets.glyphs_per_row) * usize::from(sheets.rows);
+        let (sheet, position) = (
+            usize::from(self.index) / per_sheet,
+            usize::from(self.index) % per_sheet,
+        );
+        if sheet >= sheets.count.into() {
+            return None;
+        }
+
+        // Cells are separated by 1 pixel wide borders.
+        let column = position % usize::from(sheets.glyphs_per_row);
+        let row = position / usize::from(sheets.glyphs_per_row);
+        let sheet_x = column * (usize::from(sheets.cell_width) + 1) + 1 + x;
+        let sheet_y = row * (usize::from(sheets.cell_height) + 1) + 1 + y;
+
+        // Like every GPU texture, the sheets are stored upside down.
+        let texel = tiling::tiled_index(
+            sheet_x,
+            usize::from(sheets.height) - 1 - sheet_y,
+            sheets.width.into(),
+        );
+        let data = &self.font.data[sheets.offset + sheet * sheets.size..][..sheets.size];
+
+        Some(match sheets.format {
+            SHEET_A4 | SHEET_L4 => ((data[texel / 2] >> (4 * (texel % 2))) & 0xF) * 17,
+            _ => data[texel],
+        })
+    }
+
+    /// Draw the glyph with the pen at (`x`, `y`), which is the top-left corner of the line.
+    pub fn draw(&self, surface: &mut Surface, x: i32, y: i32, color: Rgba8) {
+        let left = x + i32::from(self.width.left);
+
+        for glyph_y in 0..usize::from(self.font.sheets.cell_height) {
+            for glyph_x in 0..usize::from(self.width.glyph_width) {
+                let Some(coverage) = self.coverage(glyph_x, glyph_y) else {
+                    continue;
+                };
+
+                let alpha = (u32::from(color.a) * u32::from(coverage) + 127) / 255;
+                draw::pixel(
+                    surface,
+                    left + glyph_x as i32,
+                    y + glyph_y as i32,
+                    Rgba8 {
+                        a: alpha as u8,
+                        ..color
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn parse_sheets(data: &[u8], offset: usize, base: usize) -> io::Result<Sheets> {
+    let tglp = get(data, offset, 0x18)?;
+
+    // The highest bit of the format flags compressed sheets. The system font's sheets are decompressed when it's
+    // loaded into memory, but those of a font read from a file are still compressed.
+    let format = read_u16(tglp, 0x0A);
+    if format & 0x8000 != 0 && base == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "compressed glyph sheets aren't supported",
+        ));
+    }
+
+    let sheets = Sheets {
+        cell_width: tglp[0x00],
+        cell_height: tglp[0x01],
+        baseline: tglp[0x02],
+        size: read_u32(tglp, 0x04) as usize,
+        count: read_u16(tglp, 0x08),
+        format: format & 0x7FFF,
+        glyphs_per_row: read_u16(tglp, 0x0C),
+        rows: read_u16(tglp, 0x0E),
+        width: read_u16(tglp, 0x10),
+        height: read_u16(tglp, 0x12),
+        offset: pointer(data, read_u32(tglp, 0x14), base)?,
+    };
+
+    let bits = match sheets.format {
+        SHEET_A4 | SHEET_L4 => 4,
+        SHEET_A8 | SHEET_L8 => 8,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unsupported glyph sheet format",
+            ))
+        }
+    };
+
+    let cells_fit = (usize::from(sheets.cell_width) + 1) * usize::from(sheets.glyphs_per_row)
+        < usize::from(sheets.width)
+        && (usize::from(sheets.cell_height) + 1) * usize::from(sheets.rows)
+            < usize::from(sheets.height);
+    let tiled = sheets.width % 8 == 0 && sheets.height % 8 == 0;
+
+    if !cells_fit
+        || !tiled
+        || sheets.glyphs_per_row == 0
+        || sheets.rows == 0
+        || sheets.size < usize::from(sheets.width) * usize::from(sheets.height) * bits / 8
+    {
+        return Err(invalid_data("invalid TGLP block"));
+    }
+
+    let sheets_len = sheets
+        .size
+        .checked_mul(usize::from(sheets.count))
+        .ok_or_else(|| invalid_data("invalid TGLP block"))?;
+    get(data, sheets.offset, sheets_len)?;
+
+    Ok(sheets)
+}
+
+// Convert an internal pointer of the font into an offset.
+fn pointer(data: &[u8], pointer: u32, base: usize) -> io::Result<usize> {
+    (pointer as usize)
+        .checked_sub(base)
+        .filter(|&offset| offset < data.len())
+        .ok_or_else(|| invalid_data("invalid block offset"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::tiling::PixelSize;
+    use crate::gfx::FramebufferFormat;
+
+    const SHEET_WIDTH: usize = 32;
+    const SHEET_HEIGHT: usize = 16;
+    const CELL_WIDTH: usize = 5;
+    const CELL_HEIGHT: usize = 6;
+    const LINE_FEED: u8 = 8;
+
+    // Coverage (from 0 to 15) of the pixels of the test glyphs.
+    fn glyph_pixel(index: usize, x: usize, y: usize) -> u8 {
+        match index {
+            1 => 15,
+            2 => ((x + y) & 0xF) as u8,
+            _ => 0,
+        }
+    }
+
+    // Append a block, returning the offset of its content.
+    fn block(font: &mut Vec<u8>, magic: &[u8; 4], content: &[u8]) -> usize {
+        font.resize(font.len().next_multiple_of(4), 0);
+        font.extend_from_slice(magic);
+        font.extend_from_slice(&(content.len() as u32 + 8).to_le_bytes());
+        font.extend_from_slice(content);
+
+        font.len() - content.len()
+    }
+
+    fn patch_u32(font: &mut [u8], offset: usize, value: usize) {
+        font[offset..offset + 4].copy_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    // Build a font with 4 glyphs: '?' (replacement), 'A' (and 'a'), 'B' and 'あ'.
+    fn build() -> Vec<u8> {
+        let mut font = vec![0; HEADER_LEN];
+        font[..4].copy_from_slice(b"CFNT");
+
+        let mut finf = vec![0; 0x18];
+        finf[0x01] = LINE_FEED;
+        finf[0x04..0x07].copy_from_slice(&[0, 5, 6]);
+        finf[0x14] = CELL_HEIGHT as u8;
+        finf[0x16] = 5;
+        let finf = block(&mut font, b"FINF", &finf);
+
+        // Glyph sheet, drawn top-down then stored upside down and tiled.
+        let mut linear = vec![0; SHEET_WIDTH * SHEET_HEIGHT / 2];
+        for index in 0..4 {
+            let (column, row) = (index % 4, index / 4);
+
+            for y in 0..CELL_HEIGHT {
+                for x in 0..CELL_WIDTH {
+                    let sheet_x = column * (CELL_WIDTH + 1) + 1 + x;
+                    let sheet_y = SHEET_HEIGHT - 1 - (row * (CELL_HEIGHT + 1) + 1 + y);
+                    let texel = sheet_y * SHEET_WIDTH + sheet_x;
+
+                    linear[texel / 2] |= glyph_pixel(index, x, y) << (4 * (texel % 2));
+                }
+            }
+        }
+        let mut sheet = vec![0; linear.len()];
+        tiling::linear_to_tiled(
+            &linear,
+            &mut sheet,
+            SHEET_WIDTH,
+            SHEET_HEIGHT,
+            PixelSize::Bits4,
+        );
+
+        let mut tglp = vec![CELL_WIDTH as u8, CELL_HEIGHT as u8, 5, 7];
+        tglp.extend_from_slice(&(sheet.len() as u32).to_le_bytes());
+        for value in [1, SHEET_A4, 4, 2, SHEET_WIDTH as u16, SHEET_HEIGHT as u16] {
+            tglp.extend_from_slice(&value.to_le_bytes());
+        }
+        tglp.extend_from_slice(&[0; 4]);
+        let tglp = block(&mut font, b"TGLP", &tglp);
+        let sheet = block(&mut font, b"SHT ", &sheet);
+        patch_u32(&mut font, tglp + 0x14, sheet);
+
+        let mut cwdh = vec![0, 0, 3, 0, 0, 0, 0, 0];
+        for width in [[0, 5, 6], [1, 4, 6], [0, 5, 5], [0xFF, 5, 7]] {
+            cwdh.extend_from_slice(&width);
+        }
+        let cwdh = block(&mut font, b"CWDH", &cwdh);
+
+        let maps: [&[u16]; 3] = [
+            // 'A' and 'B', directly mapped to glyphs 1 and 2.
+            &[0x41, 0x42, 0, 0, 0, 0, 1],
+            // 'a' mapped to glyph 1 through a table, and 'b' without a glyph.
+            &[0x61, 0x62, 1, 0, 0, 0, 1, NO_GLYPH],
+            // '?' and 'あ', through a scan list.
+            &[0x3F, 0x3042, 2, 0, 0, 0, 2, 0x3042, 3, 0x3F, 0],
+        ];
+        let mut cmaps = Vec::new();
+        for map in maps {
+            let content: Vec<u8> = map.iter().flat_map(|value| value.to_le_bytes()).collect();
+            cmaps.push(block(&mut font, b"CMAP", &content));
+        }
+        patch_u32(&mut font, cmaps[0] + 8, cmaps[1]);
+        patch_u32(&mut font, cmaps[1] + 8, cmaps[2]);
+
+        patch_u32(&mut font, finf + 0x08, tglp);
+        patch_u32(&mut font, finf + 0x0C, cwdh);
+        patch_u32(&mut font, finf + 0x10, cmaps[0]);
+
+        let len = font.len();
+        patch_u32(&mut font, 0x0C, len);
+
+        font
+    }
+
+    #[test]
+    fn char_maps() {
+        let font = Font::parse(build()).unwrap();
+
+        assert_eq!(font.glyph_index('?'), Some(0));
+        assert_eq!(font.glyph_index('A'), Some(1));
+        assert_eq!(font.glyph_index('B'), Some(2));
+        assert_eq!(font.glyph_index('あ'), Some(3));
+        assert_eq!(font.glyph_index('a'), Some(1));
+        assert_eq!(font.glyph_index('b'), None);
+        assert_eq!(font.glyph_index('Z'), None);
+        assert_eq!(font.glyph_index('🦀'), None);
+
+        assert_eq!(font.glyph('Z').index(), 0);
+        assert_eq!(
+            font.glyph('あ').width(),
+            CharWidth {
+                left: -1,
+                glyph_width: 5,
+                char_width: 7
+            }
+        );
+    }
+
+    #[test]
+    fn glyph_images() {
+        let font = Font::parse(build()).unwrap();
+        assert_eq!(font.cell_size(), (CELL_WIDTH as u8, CELL_HEIGHT as u8));
+
+        for (c, index) in [('?', 0), ('A', 1), ('B', 2)] {
+            let glyph = font.glyph(c);
+
+            for y in 0..CELL_HEIGHT {
+                for x in 0..CELL_WIDTH {
+                    assert_eq!(
+                        glyph.coverage(x, y),
+                        Some(glyph_pixel(index, x, y) * 17),
+                        "{c:?} at ({x}, {y})"
+                    );
+                }
+            }
+
+            assert_eq!(glyph.coverage(CELL_WIDTH, 0), None);
+        }
+    }
+
+    #[test]
+    fn text() {
+        let font = Font::parse(build()).unwrap();
+        assert_eq!(font.measure("AB\nA"), (11, 2 * u32::from(LINE_FEED)));
+
+        let format = FramebufferFormat::Bgr8;
+        let mut buffer = vec![0; Surface::buffer_len(16, 16, format)];
+        let mut surface = Surface::new(&mut buffer, 16, 16, format);
+
+        let white = Rgba8::opaque(255, 255, 255);
+        font.draw_text(&mut surface, "A\nAA", 0, 0, white);
+
+        let lit: Vec<Vec<usize>> = surface
+            .rows()
+            .map(|row| {
+                row.enumerate()
+                    .filter(|&(_, pixel)| pixel == white)
+                    .map(|(x, _)| x)
+                    .collect()
+            })
+            .collect();
+
+        // 'A' is 4 pixels wide, 1 pixel to the right of the pen, which moves by 6 pixels.
+        assert_eq!(lit[0], [1, 2, 3, 4]);
+        assert_eq!(lit[5], [1, 2, 3, 4]);
+        assert!(lit[6].is_empty());
+        assert_eq!(lit[8], [1, 2, 3, 4, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn invalid_fonts() {
+        let font = build();
+
+        assert!(Font::parse(font[..0x40].to_vec()).is_err());
+
+        let mut corrupted = font.clone();
+        corrupted[0] = b'X';
+        assert!(Font::parse(corrupted).is_err());
+
+        let mut unsupported = font.clone();
+        let tglp = read_u32(&unsupported, HEADER_LEN + 8 + 0x08) as usize;
+        unsupported[tglp + 0x0A] = 0x0C;
+        assert_eq!(
+            Font::parse(unsupported).err().map(|err| err.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+
+        // Compressed sheets.
+        let mut compressed = font.clone();
+        compressed[tglp + 0x0B] |= 0x80;
+        assert_eq!(
+            Font::parse(compressed).err().map(|err| err.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+
+        // Sheets whose total size overflows (on 32 bit targets) or exceeds the font.
+        let mut oversized = font.clone();
+        patch_u32(&mut oversized, tglp + 0x04, u32::MAX as usize);
+        oversized[tglp + 0x08..tglp + 0x0A].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(
+            Font::parse(oversized).err().map(|err| err.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn looping_blocks() {
+        let font = build();
+        let finf = HEADER_LEN + 8;
+        let cwdh = read_u32(&font, finf + 0x0C) as usize;
+        let cmaps = read_u32(&font, finf + 0x10) as usize;
+
+        // A CMAP block pointing to itself.
+        let mut self_referencing = font.clone();
+        patch_u32(&mut self_referencing, cmaps + 8, cmaps);
+
+        // The last CMAP block pointing back to the first one.
+        let mut looping = font.clone();
+        let last = read_u32(&font, read_u32(&font, cmaps + 8) as usize + 8) as usize;
+        patch_u32(&mut looping, last + 8, cmaps);
+
+        // A CWDH block pointing to itself.
+        let mut widths = font;
+        patch_u32(&mut widths, cwdh + 4, cwdh);
+
+        for font in [self_referencing, looping, widths] {
+            assert_eq!(
+                Font::parse(font).err().map(|err| err.kind()),
+                Some(io::ErrorKind::InvalidData)
+            );
+        }
+    }
+}
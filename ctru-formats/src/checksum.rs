@@ -0,0 +1,56 @@
+//! Checksums used to detect corrupted data.
+//!
+//! These are *not* cryptographic hashes: they detect accidental corruption (e.g. a torn write or a damaged file),
+//! not deliberate tampering. Content hashes of the console's formats use SHA-256 instead.
+
+/// Compute the CRC32 (IEEE 802.3) checksum of `data`.
+///
+/// This is the checksum used by PNG images, zlib and ZIP archives.
+///
+/// # Example
+///
+/// ```
+/// use ctru_formats::checksum::crc32;
+///
+/// assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+/// ```
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &byte| {
+        CRC32_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+static CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,601 @@
+//! CIA (CTR Importable Archive) parsing.
+//!
+//! CIA files are the packages installed through the `Am` service of `ctru-rs`. They hold the certificate chain,
+//! the [`Ticket`] and the [`Tmd`] of a title, followed by its contents (usually [NCCH](crate::ncch) containers)
+//! and optional metadata including the title's [SMDH](crate::smdh).
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::cia::Cia;
+//!
+//! let mut cia = Cia::new(std::fs::File::open("app.cia")?)?;
+//!
+//! println!("Title {} v{}", cia.tmd().title_id(), cia.tmd().title_version());
+//!
+//! for content in cia.contents() {
+//!     println!("Content {} ({} bytes)", content.index, content.size);
+//! }
+//!
+//! assert!(cia.verify()?);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use self::signature::SIGNATURE_TYPE_LEN;
+use super::sha256::Sha256;
+use super::smdh::{Smdh, SMDH_LEN};
+use crate::bytes::{invalid_data, read_u32, read_vec};
+
+mod signature;
+mod ticket;
+mod tmd;
+
+pub use self::signature::{Certificate, KeyType, SignatureType};
+pub use self::ticket::Ticket;
+pub use self::tmd::{ContentChunk, ContentType, Tmd};
+
+// Size of the fixed CIA header, including the content index.
+const HEADER_LEN: usize = 0x2020;
+
+// Size of the content index bitmap.
+const CONTENT_INDEX_LEN: usize = 0x2000;
+
+// Sections of a CIA file are aligned to this boundary.
+const ALIGNMENT: u64 = 0x40;
+
+// Offset of the SMDH within the meta section.
+const META_SMDH_OFFSET: usize = 0x400;
+
+/// A CIA file.
+pub struct Cia<R> {
+    reader: R,
+    start: u64,
+    content_index: Vec<u8>,
+    certificates: Vec<Certificate>,
+    ticket: Ticket,
+    tmd: Tmd,
+    content_offset: u64,
+    content_len: u64,
+    meta_len: u32,
+}
+
+impl<R: Read + Seek> Cia<R> {
+    /// Parse the CIA file starting at the current position of `reader`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if the file isn't a valid CIA file.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut header = vec![0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let header_len = read_u32(&header, 0x00);
+        if header_len as usize != HEADER_LEN {
+            return Err(invalid_data("not a CIA file"));
+        }
+
+        let certificates_len = read_u32(&header, 0x08);
+        let ticket_len = read_u32(&header, 0x0C);
+        let tmd_len = read_u32(&header, 0x10);
+        let meta_len = read_u32(&header, 0x14);
+        let content_len = u64::from_le_bytes(header[0x18..0x20].try_into().unwrap());
+
+        let mut offset = align(u64::from(header_len));
+        let mut read_section = |len: u32| -> io::Result<Vec<u8>> {
+            reader.seek(SeekFrom::Start(start + offset))?;
+            let section = read_vec(&mut reader, u64::from(len))?;
+            offset = align(offset + u64::from(len));
+
+            Ok(section)
+        };
+
+        let certificate_chain = read_section(certificates_len)?;
+        let ticket = Ticket::parse(&read_section(ticket_len)?)?;
+        let tmd = Tmd::parse(&read_section(tmd_len)?)?;
+
+        let mut certificates = Vec::new();
+        let mut remaining = &certificate_chain[..];
+        // The chain may be followed by zero padding.
+        while remaining.len() >= SIGNATURE_TYPE_LEN && remaining.iter().any(|&b| b != 0) {
+            let (certificate, len) = Certificate::parse(remaining)?;
+            certificates.push(certificate);
+            remaining = &remaining[len..];
+        }
+
+        Ok(Self {
+            reader,
+            start,
+            content_index: header[0x20..0x20 + CONTENT_INDEX_LEN].to_vec(),
+            certificates,
+            ticket,
+            tmd,
+            content_offset: offset,
+            content_len,
+            meta_len,
+        })
+    }
+
+    /// Open a content included in the CIA file.
+    ///
+    /// Encrypted contents (see [`ContentType::ENCRYPTED`]) are returned as they are stored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::NotFound`] if the content isn't included in the CIA file.
+    pub fn open_content(&mut self, index: u16) -> io::Result<ContentReader<'_, R>> {
+        let out_of_bounds = || invalid_data("CIA content out of bounds");
+        let mut offset = self
+            .start
+            .checked_add(self.content_offset)
+            .ok_or_else(out_of_bounds)?;
+
+        for content in self.tmd.contents() {
+            if !self.contains_content(content.index) {
+                continue;
+            }
+
+            let end = offset.checked_add(content.size).ok_or_else(out_of_bounds)?;
+
+            if content.index == index {
+                self.reader.seek(SeekFrom::Start(offset))?;
+
+                return Ok(ContentReader {
+                    reader: &mut self.reader,
+                    start: offset,
+                    len: content.size,
+                    pos: 0,
+                });
+            }
+
+            offset = end;
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("content {index} isn't included in the CIA file"),
+        ))
+    }
+
+    /// Read a content included in the CIA file.
+    pub fn read_content(&mut self, index: u16) -> io::Result<Vec<u8>> {
+        let content = self.open_content(index)?;
+        let len = content.len();
+
+        read_vec(content, len)
+    }
+
+    /// Check the hash of a content against its record in the TMD.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::Unsupported`] if the content is encrypted,
+    /// since its hash covers the decrypted data.
+    pub fn verify_content(&mut self, index: u16) -> io::Result<bool> {
+        let record = *self
+            .tmd
+            .content(index)
+            .ok_or_else(|| invalid_data("content missing from the TMD"))?;
+
+        if record.content_type.contains(ContentType::ENCRYPTED) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "can't verify encrypted contents",
+            ));
+        }
+
+        let mut content = self.open_content(index)?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; 0x10000];
+
+        loop {
+            let read = content.read(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buf[..read]);
+        }
+
+        Ok(hasher.finalize() == record.hash)
+    }
+
+    /// Check the TMD and the hashes of every unencrypted content included in the CIA file.
+    pub fn verify(&mut self) -> io::Result<bool> {
+        if !self.tmd.verify() {
+            return Ok(false);
+        }
+
+        let indexes: Vec<u16> = self
+            .contents()
+            .filter(|content| !content.content_type.contains(ContentType::ENCRYPTED))
+            .map(|content| content.index)
+            .collect();
+
+        for index in indexes {
+            if !self.verify_content(index)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Read the SMDH stored in the metadata section, if the CIA file has one.
+    pub fn smdh(&mut self) -> io::Result<Option<Smdh>> {
+        if (self.meta_len as usize) < META_SMDH_OFFSET + SMDH_LEN {
+            return Ok(None);
+        }
+
+        let smdh_offset = self
+            .content_offset
+            .checked_add(self.content_len)
+            .and_then(|end| end.checked_next_multiple_of(ALIGNMENT))
+            .and_then(|meta_offset| meta_offset.checked_add(self.start))
+            .and_then(|meta_offset| meta_offset.checked_add(META_SMDH_OFFSET as u64))
+            .ok_or_else(|| invalid_data("CIA metadata out of bounds"))?;
+        let mut data = vec![0; SMDH_LEN];
+        self.reader.seek(SeekFrom::Start(smdh_offset))?;
+        self.reader.read_exact(&mut data)?;
+
+        Smdh::parse(&data).map(Some)
+    }
+}
+
+impl<R> Cia<R> {
+    /// Returns the certificate chain validating the ticket and TMD.
+    pub fn certificates(&self) -> &[Certificate] {
+        &self.certificates
+    }
+
+    /// Returns the ticket of the title.
+    pub fn ticket(&self) -> &Ticket {
+        &self.ticket
+    }
+
+    /// Returns the TMD of the title.
+    pub fn tmd(&self) -> &Tmd {
+        &self.tmd
+    }
+
+    /// Returns `true` if the content with the given index is included in the CIA file.
+    pub fn contains_content(&self, index: u16) -> bool {
+        let index = index as usize;
+
+        self.content_index
+            .get(index / 8)
+            .is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    /// Returns the records of the contents included in the CIA file, in the order they are stored.
+    pub fn contents(&self) -> impl Iterator<Item = &ContentChunk> {
+        self.tmd
+            .contents()
+            .iter()
+            .filter(|content| self.contains_content(content.index))
+    }
+
+    /// Consume the CIA file, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// A content of a [`Cia`] file, opened with [`Cia::open_content()`].
+pub struct ContentReader<'a, R> {
+    reader: &'a mut R,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl<R> ContentReader<'_, R> {
+    /// Returns the size of the content in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the content is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<R: Read + Seek> Read for ContentReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let len = (buf.len() as u64).min(remaining) as usize;
+
+        if len == 0 {
+            return Ok(0);
+        }
+
+        self.reader.seek(SeekFrom::Start(self.start + self.pos))?;
+        let read = self.reader.read(&mut buf[..len])?;
+        self.pos += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for ContentReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => {
+                self.pos = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(delta) => (self.len, delta),
+            SeekFrom::Current(delta) => (self.pos, delta),
+        };
+
+        match base.checked_add_signed(delta) {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn align(offset: u64) -> u64 {
+    offset.next_multiple_of(ALIGNMENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::cfg::Language;
+    use crate::sha256::sha256;
+    use crate::title_id::TitleId;
+
+    const TITLE_ID: u64 = 0x0004_0000_0ABC_DE00;
+
+    // Signature block of type RSA-2048 with SHA-256, with an empty signature.
+    fn signature_block() -> Vec<u8> {
+        let mut block = 0x010004u32.to_be_bytes().to_vec();
+        block.resize(4 + 0x100 + 0x3C, 0);
+        block
+    }
+
+    fn put_string(data: &mut [u8], string: &str) {
+        data[..string.len()].copy_from_slice(string.as_bytes());
+    }
+
+    fn certificate(name: &str) -> Vec<u8> {
+        let mut body = vec![0; 0x88 + 0x138];
+        put_string(&mut body[..0x40], "Root-CA00000003");
+        body[0x40..0x44].copy_from_slice(&1u32.to_be_bytes());
+        put_string(&mut body[0x44..0x84], name);
+        body[0x88] = 0xAB;
+
+        [signature_block(), body].concat()
+    }
+
+    fn ticket() -> Vec<u8> {
+        let mut body = vec![0; 0x164 + 0xAC];
+        put_string(&mut body[..0x40], "Root-CA00000003-XS0000000c");
+        body[0x7C] = 1;
+        body[0x7F..0x8F].copy_from_slice(&[0x55; 16]);
+        body[0x9C..0xA4].copy_from_slice(&TITLE_ID.to_be_bytes());
+        body[0xA6..0xA8].copy_from_slice(&0x0410u16.to_be_bytes());
+
+        [signature_block(), body].concat()
+    }
+
+    fn tmd(contents: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut chunks = Vec::new();
+        for (index, data) in contents {
+            chunks.extend_from_slice(&(u32::from(*index) + 0x10).to_be_bytes());
+            chunks.extend_from_slice(&index.to_be_bytes());
+            chunks.extend_from_slice(&0u16.to_be_bytes());
+            chunks.extend_from_slice(&(data.len() as u64).to_be_bytes());
+            chunks.extend_from_slice(&sha256(data));
+        }
+
+        let mut infos = vec![0; 64 * 0x24];
+        infos[2..4].copy_from_slice(&(contents.len() as u16).to_be_bytes());
+        infos[4..0x24].copy_from_slice(&sha256(&chunks));
+
+        let mut header = vec![0; 0xC4];
+        put_string(&mut header[..0x40], "Root-CA00000003-CP0000000b");
+        header[0x4C..0x54].copy_from_slice(&TITLE_ID.to_be_bytes());
+        header[0x5A..0x5E].copy_from_slice(&0x8000u32.to_le_bytes());
+        header[0x9C..0x9E].copy_from_slice(&0x0410u16.to_be_bytes());
+        header[0x9E..0xA0].copy_from_slice(&(contents.len() as u16).to_be_bytes());
+        header[0xA4..0xC4].copy_from_slice(&sha256(&infos));
+
+        [signature_block(), header, infos, chunks].concat()
+    }
+
+    fn pad(data: &mut Vec<u8>) {
+        data.resize(data.len().next_multiple_of(0x40), 0);
+    }
+
+    // Build a CIA with contents 0 and 2, where content 1 is listed in the TMD but not included.
+    fn sample() -> Vec<u8> {
+        build([certificate("CA00000003"), certificate("XS0000000c")].concat())
+    }
+
+    fn build(certificates: Vec<u8>) -> Vec<u8> {
+        let content0 = b"main content".repeat(10);
+        let content1 = b"manual".to_vec();
+        let content2 = b"download play child".to_vec();
+
+        let ticket = ticket();
+        let tmd = tmd(&[(0, &content0), (1, &content1), (2, &content2)]);
+
+        let mut smdh = Smdh::default();
+        smdh.title_mut(Language::English).short_description = "Packaged".to_owned();
+        let mut meta = vec![0; META_SMDH_OFFSET];
+        meta.extend_from_slice(&smdh.to_bytes().unwrap());
+
+        let mut data = vec![0; HEADER_LEN];
+        data[0x00..0x04].copy_from_slice(&(HEADER_LEN as u32).to_le_bytes());
+        data[0x08..0x0C].copy_from_slice(&(certificates.len() as u32).to_le_bytes());
+        data[0x0C..0x10].copy_from_slice(&(ticket.len() as u32).to_le_bytes());
+        data[0x10..0x14].copy_from_slice(&(tmd.len() as u32).to_le_bytes());
+        data[0x14..0x18].copy_from_slice(&(meta.len() as u32).to_le_bytes());
+        data[0x18..0x20].copy_from_slice(&((content0.len() + content2.len()) as u64).to_le_bytes());
+        data[0x20] = 0b1010_0000;
+
+        for section in [certificates, ticket, tmd] {
+            pad(&mut data);
+            data.extend_from_slice(&section);
+        }
+        pad(&mut data);
+        data.extend_from_slice(&content0);
+        data.extend_from_slice(&content2);
+        pad(&mut data);
+        data.extend_from_slice(&meta);
+
+        data
+    }
+
+    #[test]
+    fn parse_sections() {
+        let mut cia = Cia::new(Cursor::new(sample())).unwrap();
+
+        let names: Vec<&str> = cia.certificates().iter().map(|c| c.name()).collect();
+        assert_eq!(names, ["CA00000003", "XS0000000c"]);
+        assert_eq!(cia.certificates()[0].key_type(), KeyType::Rsa2048);
+        assert_eq!(cia.certificates()[0].public_key()[0], 0xAB);
+
+        assert_eq!(cia.ticket().title_id(), TitleId::new(TITLE_ID));
+        assert_eq!(cia.ticket().encrypted_title_key(), [0x55; 16]);
+        assert_eq!(cia.ticket().title_version(), 0x0410);
+
+        assert_eq!(cia.tmd().title_id(), TitleId::new(TITLE_ID));
+        assert_eq!(cia.tmd().title_version(), 0x0410);
+        assert_eq!(cia.tmd().save_data_size(), 0x8000);
+        assert_eq!(cia.tmd().contents().len(), 3);
+
+        let included: Vec<u16> = cia.contents().map(|content| content.index).collect();
+        assert_eq!(included, [0, 2]);
+        assert!(!cia.contains_content(1));
+
+        assert_eq!(cia.read_content(2).unwrap(), b"download play child");
+        assert_eq!(
+            cia.open_content(1).err().map(|e| e.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+
+        let smdh = cia.smdh().unwrap().unwrap();
+        assert_eq!(smdh.title(Language::English).short_description, "Packaged");
+    }
+
+    #[test]
+    fn content_reader() {
+        let mut cia = Cia::new(Cursor::new(sample())).unwrap();
+        let mut content = cia.open_content(0).unwrap();
+
+        assert_eq!(content.len(), 120);
+
+        let mut buf = [0; 4];
+        content.seek(SeekFrom::End(-4)).unwrap();
+        content.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"tent");
+        assert_eq!(content.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn verification() {
+        let data = sample();
+
+        let mut cia = Cia::new(Cursor::new(data.clone())).unwrap();
+        assert!(cia.tmd().verify());
+        assert!(cia.verify().unwrap());
+
+        // Corrupt the last byte of content 2.
+        let content_end = data.len() - (0x400 + SMDH_LEN);
+        let content_end = (0..content_end).rev().find(|&i| data[i] != 0).unwrap();
+        let mut corrupted = data;
+        corrupted[content_end] ^= 0xFF;
+
+        let mut cia = Cia::new(Cursor::new(corrupted)).unwrap();
+        assert!(cia.verify_content(0).unwrap());
+        assert!(!cia.verify_content(2).unwrap());
+        assert!(!cia.verify().unwrap());
+    }
+
+    #[test]
+    fn padded_certificate_chain() {
+        for padding in [2, 0x10] {
+            let certificates = [
+                certificate("CA00000003"),
+                certificate("XS0000000c"),
+                vec![0; padding],
+            ]
+            .concat();
+
+            let cia = Cia::new(Cursor::new(build(certificates))).unwrap();
+            assert_eq!(cia.certificates().len(), 2);
+        }
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(Cia::new(Cursor::new(vec![0; HEADER_LEN])).is_err());
+
+        let mut data = sample();
+        data.truncate(0x2100);
+        assert!(Cia::new(Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn oversized_contents() {
+        let data = sample();
+
+        // Make the TMD record of content 0 claim the largest possible size.
+        let mut record = vec![0, 0, 0, 0x10, 0, 0, 0, 0];
+        record.extend_from_slice(&120u64.to_be_bytes());
+        let size = data
+            .windows(record.len())
+            .position(|window| window == record)
+            .unwrap()
+            + 8;
+        let patched = |content_size: u64| {
+            let mut data = data.clone();
+            data[size..size + 8].copy_from_slice(&content_size.to_be_bytes());
+            Cia::new(Cursor::new(data)).unwrap()
+        };
+
+        // Read without preallocating the whole content.
+        let mut cia = patched(1 << 62);
+        assert_eq!(
+            cia.read_content(0).err().map(|e| e.kind()),
+            Some(io::ErrorKind::UnexpectedEof)
+        );
+
+        let mut cia = patched(u64::MAX);
+        for index in [0, 2] {
+            assert_eq!(
+                cia.open_content(index).err().map(|e| e.kind()),
+                Some(io::ErrorKind::InvalidData)
+            );
+        }
+
+        // The metadata section follows the contents.
+        let mut oversized = data;
+        oversized[0x18..0x20].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let mut cia = Cia::new(Cursor::new(oversized)).unwrap();
+        assert_eq!(
+            cia.smdh().err().map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Signed blobs and certificates.
+
+use std::io;
+
+use crate::bytes::{invalid_data, read_string, read_u32_be};
+
+/// Algorithm used to sign a ticket, TMD or certificate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SignatureType {
+    /// RSA-4096 with SHA-1.
+    Rsa4096Sha1,
+    /// RSA-2048 with SHA-1.
+    Rsa2048Sha1,
+    /// ECDSA with SHA-1.
+    EcdsaSha1,
+    /// RSA-4096 with SHA-256.
+    Rsa4096Sha256,
+    /// RSA-2048 with SHA-256.
+    Rsa2048Sha256,
+    /// ECDSA with SHA-256.
+    EcdsaSha256,
+}
+
+impl SignatureType {
+    fn from_raw(raw: u32) -> Option<Self> {
+        Some(match raw {
+            0x010000 => Self::Rsa4096Sha1,
+            0x010001 => Self::Rsa2048Sha1,
+            0x010002 => Self::EcdsaSha1,
+            0x010003 => Self::Rsa4096Sha256,
+            0x010004 => Self::Rsa2048Sha256,
+            0x010005 => Self::EcdsaSha256,
+            _ => return None,
+        })
+    }
+
+    /// Returns the size of the signature in bytes.
+    pub fn signature_len(self) -> usize {
+        match self {
+            Self::Rsa4096Sha1 | Self::Rsa4096Sha256 => 0x200,
+            Self::Rsa2048Sha1 | Self::Rsa2048Sha256 => 0x100,
+            Self::EcdsaSha1 | Self::EcdsaSha256 => 0x3C,
+        }
+    }
+
+    // Size of the signature block: type, signature and alignment padding.
+    fn block_len(self) -> usize {
+        let padding = match self {
+            Self::EcdsaSha1 | Self::EcdsaSha256 => 0x40,
+            _ => 0x3C,
+        };
+
+        4 + self.signature_len() + padding
+    }
+}
+
+// Size of the signature type, at the start of every signed blob.
+pub(super) const SIGNATURE_TYPE_LEN: usize = 4;
+
+// Split a signed blob into its signature type, signature and signed data.
+pub(super) fn split_signed(data: &[u8]) -> io::Result<(SignatureType, &[u8], &[u8])> {
+    if data.len() < SIGNATURE_TYPE_LEN {
+        return Err(invalid_data("truncated signature"));
+    }
+
+    let signature_type = SignatureType::from_raw(read_u32_be(data, 0))
+        .ok_or_else(|| invalid_data("unknown signature type"))?;
+
+    if data.len() < signature_type.block_len() {
+        return Err(invalid_data("truncated signature"));
+    }
+
+    let signature = &data[4..4 + signature_type.signature_len()];
+    let body = &data[signature_type.block_len()..];
+
+    Ok((signature_type, signature, body))
+}
+
+/// Type of the public key held by a [`Certificate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyType {
+    /// RSA-4096 public key.
+    Rsa4096,
+    /// RSA-2048 public key.
+    Rsa2048,
+    /// ECC (sect233r1) public key.
+    Ecc,
+}
+
+impl KeyType {
+    // Size of the public key, including padding.
+    fn block_len(self) -> usize {
+        match self {
+            Self::Rsa4096 => 0x238,
+            Self::Rsa2048 => 0x138,
+            Self::Ecc => 0x78,
+        }
+    }
+
+    // Size of the public key itself.
+    fn key_len(self) -> usize {
+        match self {
+            Self::Rsa4096 => 0x204,
+            Self::Rsa2048 => 0x104,
+            Self::Ecc => 0x3C,
+        }
+    }
+}
+
+/// A certificate of the chain validating tickets and TMDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Certificate {
+    signature_type: SignatureType,
+    signature: Vec<u8>,
+    issuer: String,
+    key_type: KeyType,
+    name: String,
+    public_key: Vec<u8>,
+}
+
+// Size of the certificate fields preceding the public key.
+const CERTIFICATE_HEADER_LEN: usize = 0x88;
+
+impl Certificate {
+    // Parse the certificate at the start of `data`, returning it along with its size.
+    pub(super) fn parse(data: &[u8]) -> io::Result<(Self, usize)> {
+        let (signature_type, signature, body) = split_signed(data)?;
+
+        if body.len() < CERTIFICATE_HEADER_LEN {
+            return Err(invalid_data("truncated certificate"));
+        }
+
+        let key_type = match read_u32_be(body, 0x40) {
+            0 => KeyType::Rsa4096,
+            1 => KeyType::Rsa2048,
+            2 => KeyType::Ecc,
+            _ => return Err(invalid_data("unknown certificate key type")),
+        };
+
+        if body.len() < CERTIFICATE_HEADER_LEN + key_type.block_len() {
+            return Err(invalid_data("truncated certificate"));
+        }
+
+        let certificate = Self {
+            signature_type,
+            signature: signature.to_vec(),
+            issuer: read_string(&body[..0x40]),
+            key_type,
+            name: read_string(&body[0x44..0x84]),
+            public_key: body[CERTIFICATE_HEADER_LEN..][..key_type.key_len()].to_vec(),
+        };
+        let len = signature_type.block_len() + CERTIFICATE_HEADER_LEN + key_type.block_len();
+
+        Ok((certificate, len))
+    }
+
+    /// Returns the algorithm used to sign this certificate.
+    pub fn signature_type(&self) -> SignatureType {
+        self.signature_type
+    }
+
+    /// Returns the signature of this certificate.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Returns the issuer of this certificate (e.g. `"Root-CA00000003"`).
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the name of this certificate (e.g. `"CP0000000b"`).
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type of the public key held by this certificate.
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// Returns the public key held by this certificate.
+    ///
+    /// RSA keys are made of the modulus followed by the 4 byte exponent.
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
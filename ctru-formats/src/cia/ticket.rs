@@ -0,0 +1,103 @@
+//! Tickets.
+
+use std::io;
+
+use super::signature::{split_signed, SignatureType};
+use crate::bytes::{invalid_data, read_string, read_u16_be, read_u32_be, read_u64_be};
+use crate::title_id::TitleId;
+
+// Size of the ticket fields preceding the content index.
+const TICKET_BODY_LEN: usize = 0x164;
+
+/// The license allowing a title to be used, holding its encrypted title key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ticket {
+    signature_type: SignatureType,
+    issuer: String,
+    version: u8,
+    title_key: [u8; 16],
+    ticket_id: u64,
+    console_id: u32,
+    title_id: TitleId,
+    title_version: u16,
+    license_type: u8,
+    common_key_index: u8,
+}
+
+impl Ticket {
+    /// Parse a ticket.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if `data` isn't a valid ticket.
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        let (signature_type, _, body) = split_signed(data)?;
+
+        if body.len() < TICKET_BODY_LEN {
+            return Err(invalid_data("truncated ticket"));
+        }
+
+        Ok(Self {
+            signature_type,
+            issuer: read_string(&body[..0x40]),
+            version: body[0x7C],
+            title_key: body[0x7F..0x8F].try_into().unwrap(),
+            ticket_id: read_u64_be(body, 0x90),
+            console_id: read_u32_be(body, 0x98),
+            title_id: TitleId::new(read_u64_be(body, 0x9C)),
+            title_version: read_u16_be(body, 0xA6),
+            license_type: body[0xB0],
+            common_key_index: body[0xB1],
+        })
+    }
+
+    /// Returns the algorithm used to sign this ticket.
+    pub fn signature_type(&self) -> SignatureType {
+        self.signature_type
+    }
+
+    /// Returns the issuer of this ticket (e.g. `"Root-CA00000003-XS0000000c"`).
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the version of the ticket format.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the title key, encrypted with the common key selected by [`Ticket::common_key_index()`].
+    pub fn encrypted_title_key(&self) -> [u8; 16] {
+        self.title_key
+    }
+
+    /// Returns the ID of this ticket.
+    pub fn ticket_id(&self) -> u64 {
+        self.ticket_id
+    }
+
+    /// Returns the ID of the console this ticket is bound to, or 0 for tickets valid on every console.
+    pub fn console_id(&self) -> u32 {
+        self.console_id
+    }
+
+    /// Returns the ID of the title this ticket is for.
+    pub fn title_id(&self) -> TitleId {
+        self.title_id
+    }
+
+    /// Returns the version of the title this ticket was issued for.
+    pub fn title_version(&self) -> u16 {
+        self.title_version
+    }
+
+    /// Returns the type of license granted by this ticket.
+    pub fn license_type(&self) -> u8 {
+        self.license_type
+    }
+
+    /// Returns the index of the common key used to encrypt the title key.
+    pub fn common_key_index(&self) -> u8 {
+        self.common_key_index
+    }
+}
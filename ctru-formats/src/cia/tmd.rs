@@ -0,0 +1,208 @@
+//! Title metadata (TMD).
+
+use std::io;
+
+use super::signature::{split_signed, SignatureType};
+use crate::bytes::{invalid_data, read_string, read_u16_be, read_u32_be, read_u64_be};
+use crate::sha256::sha256;
+use crate::title_id::TitleId;
+
+// Size of the TMD header, up to the content info records.
+const HEADER_LEN: usize = 0xC4;
+
+const CONTENT_INFO_COUNT: usize = 64;
+const CONTENT_INFO_LEN: usize = 0x24;
+const CONTENT_CHUNK_LEN: usize = 0x30;
+
+bitflags::bitflags! {
+    /// Properties of a content.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ContentType: u16 {
+        /// The content is encrypted with the title key.
+        const ENCRYPTED = 0x0001;
+        /// The content is stored on a game card.
+        const DISC = 0x0002;
+        /// The content is a CFM.
+        const CFM = 0x0004;
+        /// The content is optional (e.g. DLC).
+        const OPTIONAL = 0x4000;
+        /// The content is shared between titles.
+        const SHARED = 0x8000;
+    }
+}
+
+/// Description of a content of a title.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ContentChunk {
+    /// ID of the content, which names its file on the console.
+    pub id: u32,
+    /// Index of the content within the title.
+    pub index: u16,
+    /// Properties of the content.
+    pub content_type: ContentType,
+    /// Size of the content in bytes.
+    pub size: u64,
+    /// SHA-256 hash of the decrypted content.
+    pub hash: [u8; 32],
+}
+
+/// Title metadata, describing the contents of a title.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tmd {
+    signature_type: SignatureType,
+    issuer: String,
+    version: u8,
+    system_version: u64,
+    title_id: TitleId,
+    title_type: u32,
+    group_id: u16,
+    save_data_size: u32,
+    access_rights: u32,
+    title_version: u16,
+    boot_content: u16,
+    content_info_hash: [u8; 32],
+    content_infos: Vec<u8>,
+    content_chunks: Vec<u8>,
+    contents: Vec<ContentChunk>,
+}
+
+impl Tmd {
+    /// Parse a TMD.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if `data` isn't a valid TMD.
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        let (signature_type, _, body) = split_signed(data)?;
+
+        let infos_len = CONTENT_INFO_COUNT * CONTENT_INFO_LEN;
+        if body.len() < HEADER_LEN + infos_len {
+            return Err(invalid_data("truncated TMD"));
+        }
+
+        let content_count = read_u16_be(body, 0x9E) as usize;
+        let content_chunks = body[HEADER_LEN + infos_len..]
+            .get(..content_count * CONTENT_CHUNK_LEN)
+            .ok_or_else(|| invalid_data("truncated TMD"))?;
+
+        let contents = content_chunks
+            .chunks_exact(CONTENT_CHUNK_LEN)
+            .map(|chunk| ContentChunk {
+                id: read_u32_be(chunk, 0x00),
+                index: read_u16_be(chunk, 0x04),
+                content_type: ContentType::from_bits_retain(read_u16_be(chunk, 0x06)),
+                size: read_u64_be(chunk, 0x08),
+                hash: chunk[0x10..0x30].try_into().unwrap(),
+            })
+            .collect();
+
+        Ok(Self {
+            signature_type,
+            issuer: read_string(&body[..0x40]),
+            version: body[0x40],
+            system_version: read_u64_be(body, 0x44),
+            title_id: TitleId::new(read_u64_be(body, 0x4C)),
+            title_type: read_u32_be(body, 0x54),
+            group_id: read_u16_be(body, 0x58),
+            // Unlike the rest of the TMD, the save data size is little endian.
+            save_data_size: u32::from_le_bytes(body[0x5A..0x5E].try_into().unwrap()),
+            access_rights: read_u32_be(body, 0x98),
+            title_version: read_u16_be(body, 0x9C),
+            boot_content: read_u16_be(body, 0xA0),
+            content_info_hash: body[0xA4..0xC4].try_into().unwrap(),
+            content_infos: body[HEADER_LEN..HEADER_LEN + infos_len].to_vec(),
+            content_chunks: content_chunks.to_vec(),
+            contents,
+        })
+    }
+
+    /// Check the hashes protecting the content records.
+    ///
+    /// This doesn't check the signature of the TMD, nor the contents themselves.
+    pub fn verify(&self) -> bool {
+        if sha256(&self.content_infos) != self.content_info_hash {
+            return false;
+        }
+
+        // Each content info record covers a range of content chunk records.
+        self.content_infos
+            .chunks_exact(CONTENT_INFO_LEN)
+            .all(|info| {
+                let offset = read_u16_be(info, 0) as usize;
+                let count = read_u16_be(info, 2) as usize;
+
+                if count == 0 {
+                    return true;
+                }
+
+                self.content_chunks
+                    .get(offset * CONTENT_CHUNK_LEN..(offset + count) * CONTENT_CHUNK_LEN)
+                    .is_some_and(|chunks| sha256(chunks) == info[4..])
+            })
+    }
+
+    /// Returns the algorithm used to sign this TMD.
+    pub fn signature_type(&self) -> SignatureType {
+        self.signature_type
+    }
+
+    /// Returns the issuer of this TMD (e.g. `"Root-CA00000003-CP0000000b"`).
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the version of the TMD format.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Returns the ID of the system title this title requires, if any.
+    pub fn system_version(&self) -> u64 {
+        self.system_version
+    }
+
+    /// Returns the ID of the title.
+    pub fn title_id(&self) -> TitleId {
+        self.title_id
+    }
+
+    /// Returns the type of the title.
+    pub fn title_type(&self) -> u32 {
+        self.title_type
+    }
+
+    /// Returns the group ID of the title.
+    pub fn group_id(&self) -> u16 {
+        self.group_id
+    }
+
+    /// Returns the size of the title's save data in bytes.
+    pub fn save_data_size(&self) -> u32 {
+        self.save_data_size
+    }
+
+    /// Returns the access rights of the title.
+    pub fn access_rights(&self) -> u32 {
+        self.access_rights
+    }
+
+    /// Returns the version of the title.
+    pub fn title_version(&self) -> u16 {
+        self.title_version
+    }
+
+    /// Returns the index of the content booted when launching the title.
+    pub fn boot_content(&self) -> u16 {
+        self.boot_content
+    }
+
+    /// Returns the records of the contents making up the title.
+    pub fn contents(&self) -> &[ContentChunk] {
+        &self.contents
+    }
+
+    /// Returns the record of the content with the given index.
+    pub fn content(&self, index: u16) -> Option<&ContentChunk> {
+        self.contents.iter().find(|content| content.index == index)
+    }
+}
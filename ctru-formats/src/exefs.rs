@@ -0,0 +1,206 @@
+//! ExeFS parsing.
+//!
+//! The ExeFS is the small file system of an [NCCH](crate::ncch) container holding the application's code,
+//! its banner and its icon ([SMDH](crate::smdh)). Each of its (up to 10) files is protected by a SHA-256 hash.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::sha256::sha256;
+use crate::bytes::{invalid_data, read_vec};
+
+/// Size of the ExeFS header, which precedes the file data.
+pub const HEADER_LEN: usize = 0x200;
+
+// Maximum number of files in an ExeFS.
+const MAX_FILES: usize = 10;
+
+// Offset of the hash of the first file. Hashes are stored in reverse order.
+const LAST_HASH_OFFSET: usize = 0x1E0;
+
+/// A file of an [`ExeFs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExeFsFile {
+    /// Name of the file (e.g. `".code"`, `"icon"` or `"banner"`).
+    pub name: String,
+    /// Offset of the file data from the end of the ExeFS header.
+    pub offset: u32,
+    /// Size of the file in bytes.
+    pub size: u32,
+    /// SHA-256 hash of the file.
+    pub hash: [u8; 32],
+}
+
+/// An ExeFS image.
+pub struct ExeFs<R> {
+    reader: R,
+    start: u64,
+    files: Vec<ExeFsFile>,
+}
+
+impl<R: Read + Seek> ExeFs<R> {
+    /// Parse the ExeFS starting at the current position of `reader`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if the file table is invalid.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let mut files = Vec::new();
+
+        for index in 0..MAX_FILES {
+            let entry = &header[index * 0x10..][..0x10];
+            if entry[0] == 0 {
+                continue;
+            }
+
+            let name_len = entry[..8].iter().position(|&b| b == 0).unwrap_or(8);
+            let name = std::str::from_utf8(&entry[..name_len])
+                .map_err(|_| invalid_data("invalid ExeFS file name"))?;
+
+            files.push(ExeFsFile {
+                name: name.to_owned(),
+                offset: u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+                size: u32::from_le_bytes(entry[12..16].try_into().unwrap()),
+                hash: header[LAST_HASH_OFFSET - index * 0x20..][..0x20]
+                    .try_into()
+                    .unwrap(),
+            });
+        }
+
+        Ok(Self {
+            reader,
+            start,
+            files,
+        })
+    }
+
+    /// Read a file of the ExeFS.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::NotFound`] if the file doesn't exist.
+    pub fn read(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        let file = self.file(name).ok_or_else(|| not_found(name))?;
+        let (offset, size) = (file.offset, file.size);
+
+        self.reader.seek(SeekFrom::Start(
+            self.start + HEADER_LEN as u64 + u64::from(offset),
+        ))?;
+
+        read_vec(&mut self.reader, u64::from(size))
+    }
+
+    /// Check the hash of a file of the ExeFS.
+    pub fn verify(&mut self, name: &str) -> io::Result<bool> {
+        let data = self.read(name)?;
+        let file = self.file(name).ok_or_else(|| not_found(name))?;
+
+        Ok(sha256(&data) == file.hash)
+    }
+
+    /// Check the hashes of every file of the ExeFS.
+    pub fn verify_all(&mut self) -> io::Result<bool> {
+        for index in 0..self.files.len() {
+            let name = self.files[index].name.clone();
+
+            if !self.verify(&name)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R> ExeFs<R> {
+    /// Returns the files of the ExeFS.
+    pub fn files(&self) -> &[ExeFsFile] {
+        &self.files
+    }
+
+    /// Returns the file with the given name.
+    pub fn file(&self, name: &str) -> Option<&ExeFsFile> {
+        self.files.iter().find(|file| file.name == name)
+    }
+
+    /// Consume the ExeFS, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn not_found(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("ExeFS file {name:?} not found"),
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    // Build an ExeFS image from a list of files.
+    pub(crate) fn build(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut header = vec![0; HEADER_LEN];
+        let mut data = Vec::new();
+
+        for (index, (name, contents)) in files.iter().enumerate() {
+            let entry = &mut header[index * 0x10..][..0x10];
+            entry[..name.len()].copy_from_slice(name.as_bytes());
+            entry[8..12].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            entry[12..16].copy_from_slice(&(contents.len() as u32).to_le_bytes());
+            header[LAST_HASH_OFFSET - index * 0x20..][..0x20].copy_from_slice(&sha256(contents));
+
+            data.extend_from_slice(contents);
+            data.resize(data.len().next_multiple_of(0x200), 0);
+        }
+
+        [header, data].concat()
+    }
+
+    #[test]
+    fn read_and_verify() {
+        let image = build(&[(".code", b"code"), ("icon", b"smdh")]);
+        let mut exefs = ExeFs::new(Cursor::new(image.clone())).unwrap();
+
+        let names: Vec<&str> = exefs
+            .files()
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect();
+        assert_eq!(names, [".code", "icon"]);
+        assert_eq!(exefs.file("icon").unwrap().offset, 0x200);
+
+        assert_eq!(exefs.read("icon").unwrap(), b"smdh");
+        assert!(exefs.verify_all().unwrap());
+        assert_eq!(
+            exefs.read("banner").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let mut corrupted = image;
+        corrupted[HEADER_LEN] ^= 0xFF;
+        let mut exefs = ExeFs::new(Cursor::new(corrupted)).unwrap();
+        assert!(!exefs.verify(".code").unwrap());
+        assert!(exefs.verify("icon").unwrap());
+    }
+
+    #[test]
+    fn oversized_file() {
+        let mut image = build(&[("icon", b"smdh")]);
+        image[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let mut exefs = ExeFs::new(Cursor::new(image)).unwrap();
+        assert_eq!(
+            exefs.read("icon").unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Encodings of the paths used by the file-system service.
+//!
+//! The file-system service identifies archives and the entries within them using NUL-terminated text paths
+//! (UTF-16LE or ASCII) or binary paths, whose meaning depends on the archive. These functions produce the encoded data
+//! wrapped by `ctru::services::fs::path::FsPath`.
+
+/// Encode a path as NUL-terminated UTF-16LE.
+///
+/// # Example
+///
+/// ```
+/// use ctru_formats::fs_path::encode_utf16;
+///
+/// assert_eq!(encode_utf16("/a"), [b'/', 0, b'a', 0, 0, 0]);
+/// ```
+pub fn encode_utf16(path: &str) -> Vec<u8> {
+    path.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// Encode a path as NUL-terminated ASCII.
+///
+/// Returns `None` if `path` contains non-ASCII or NUL characters.
+pub fn encode_ascii(path: &str) -> Option<Vec<u8>> {
+    if !path.is_ascii() || path.contains('\0') {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(path.len() + 1);
+    data.extend_from_slice(path.as_bytes());
+    data.push(0);
+
+    Some(data)
+}
+
+/// Encode the binary path made of a media type, followed by the low and high words of a 64 bit ID.
+///
+/// This is the path identifying the save data of a title (with its title ID) or an ExtData archive (with its ExtData ID).
+pub fn encode_media_id(media_type: u32, id: u64) -> [u8; 12] {
+    let mut path = [0; 12];
+    path[0..4].copy_from_slice(&media_type.to_le_bytes());
+    path[4..8].copy_from_slice(&(id as u32).to_le_bytes());
+    path[8..12].copy_from_slice(&((id >> 32) as u32).to_le_bytes());
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_encoding() {
+        assert_eq!(encode_utf16(""), [0, 0]);
+        assert_eq!(encode_utf16("/a"), [b'/', 0, b'a', 0, 0, 0]);
+        // Characters outside the BMP are encoded as surrogate pairs.
+        assert_eq!(
+            encode_utf16("ファ🦀"),
+            [0xD5, 0x30, 0xA1, 0x30, 0x3E, 0xD8, 0x80, 0xDD, 0, 0]
+        );
+    }
+
+    #[test]
+    fn ascii_encoding() {
+        assert_eq!(encode_ascii("/save.bin").unwrap(), b"/save.bin\0");
+        assert_eq!(encode_ascii("/ファイル.txt"), None);
+        assert_eq!(encode_ascii("/a\0b"), None);
+    }
+
+    #[test]
+    fn media_id_encoding() {
+        assert_eq!(
+            encode_media_id(1, 0x0004_0000_0012_3400),
+            [1, 0, 0, 0, 0x00, 0x34, 0x12, 0x00, 0x00, 0x00, 0x04, 0x00]
+        );
+    }
+}
@@ -0,0 +1,580 @@
+//! Software 2D drawing.
+//!
+//! The functions of this module draw simple shapes and images on a [`Surface`], which can be borrowed from a screen
+//! (see `Screen::surface()` in `ctru-rs`) or created over any buffer. Shapes and sprites may be partially
+//! (or entirely) outside of the surface: they are clipped to its bounds.
+//!
+//! Colors are given as [`Rgba8`], and are blended with the current content of the surface according to their alpha component,
+//! whatever the [`FramebufferFormat`](crate::gfx::FramebufferFormat) of the surface.
+//!
+//! # Example
+//!
+//! ```
+//! use ctru_formats::gfx::draw::{self, Rect};
+//! use ctru_formats::gfx::pixel::Rgba8;
+//! use ctru_formats::gfx::surface::Surface;
+//! use ctru_formats::gfx::FramebufferFormat;
+//!
+//! let format = FramebufferFormat::Rgb565;
+//! let mut buffer = vec![0; Surface::buffer_len(320, 240, format)];
+//! let mut surface = Surface::new(&mut buffer, 320, 240, format);
+//!
+//! draw::fill_rect(&mut surface, Rect::new(10, 10, 100, 50), Rgba8::opaque(255, 0, 0));
+//! draw::line(&mut surface, (0, 0), (319, 239), Rgba8::opaque(0, 255, 0));
+//!
+//! // Translucent colors are blended with the surface.
+//! draw::fill_circle(&mut surface, (160, 120), 40, Rgba8::new(0, 0, 255, 128));
+//! ```
+
+use super::pixel::{Pixel, Rgba8};
+use super::surface::Surface;
+
+/// A rectangle, whose top-left corner is at (`x`, `y`).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// Horizontal position of the left edge.
+    pub x: i32,
+    /// Vertical position of the top edge.
+    pub y: i32,
+    /// Width of the rectangle.
+    pub width: u32,
+    /// Height of the rectangle.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Create a new rectangle.
+    pub const fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns `true` if the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    // Intersect the rectangle with a surface of the given size,
+    // returning the ranges of covered coordinates.
+    pub(super) fn clip(&self, width: usize, height: usize) -> Option<(usize, usize, usize, usize)> {
+        let (left, top) = (i64::from(self.x), i64::from(self.y));
+
+        clip_area(
+            (left, left + i64::from(self.width)),
+            (top, top + i64::from(self.height)),
+            width,
+            height,
+        )
+    }
+}
+
+// Intersect the area covering the ranges of coordinates `x` and `y` (end excluded) with a surface of the given size.
+//
+// Coordinates are computed in `i64` so that they can't overflow when offsetting the `i32` coordinates of the shapes.
+fn clip_area(
+    x: (i64, i64),
+    y: (i64, i64),
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let clamp = |value: i64, max: usize| value.clamp(0, max as i64) as usize;
+
+    let (left, right) = (clamp(x.0, width), clamp(x.1, width));
+    let (top, bottom) = (clamp(y.0, height), clamp(y.1, height));
+
+    (left < right && top < bottom).then_some((left, right, top, bottom))
+}
+
+/// An image stored row by row, to be drawn with [`blit()`].
+#[derive(Copy, Clone, Debug)]
+pub struct Sprite<'a, P = Rgba8> {
+    pixels: &'a [P],
+    width: usize,
+    height: usize,
+}
+
+impl<'a, P: Pixel> Sprite<'a, P> {
+    /// Create a sprite from its pixels, stored row by row from the top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `pixels` doesn't hold exactly `width * height` pixels.
+    pub fn new(pixels: &'a [P], width: usize, height: usize) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "sprite must hold {width}x{height} pixels"
+        );
+
+        Self {
+            pixels,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the width of the sprite.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the sprite.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the pixel at (`x`, `y`), or `None` if it's outside of the sprite.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<P> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+}
+
+/// Blend `src` over `dst`, according to their alpha components.
+///
+/// Colors aren't premultiplied by their alpha: the result is the "over" operator of the Porter-Duff compositing,
+/// so translucent colors can be blended over translucent destinations (e.g. an [`Rgba8`] surface prepared before being drawn).
+pub fn blend(src: Rgba8, dst: Rgba8) -> Rgba8 {
+    match src.a {
+        u8::MAX => return src,
+        0 => return dst,
+        _ => {}
+    }
+
+    // Weights of both colors, scaled by 255².
+    let src_weight = u32::from(src.a) * 255;
+    let dst_weight = u32::from(dst.a) * (255 - u32::from(src.a));
+    let alpha = src_weight + dst_weight;
+
+    let mix = |src: u8, dst: u8| {
+        ((u32::from(src) * src_weight + u32::from(dst) * dst_weight + alpha / 2) / alpha) as u8
+    };
+
+    Rgba8::new(
+        mix(src.r, dst.r),
+        mix(src.g, dst.g),
+        mix(src.b, dst.b),
+        ((alpha + 127) / 255) as u8,
+    )
+}
+
+/// Draw a single pixel.
+pub fn pixel(surface: &mut Surface, x: i32, y: i32, color: Rgba8) {
+    let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+        return;
+    };
+
+    plot(surface, x, y, color);
+}
+
+/// Fill a rectangle.
+pub fn fill_rect(surface: &mut Surface, rect: Rect, color: Rgba8) {
+    let Some(area) = rect.clip(surface.width(), surface.height()) else {
+        return;
+    };
+
+    fill_area(surface, area, color);
+}
+
+/// Draw the 1 pixel wide outline of a rectangle, inside of its bounds.
+pub fn stroke_rect(surface: &mut Surface, rect: Rect, color: Rgba8) {
+    if rect.is_empty() {
+        return;
+    }
+
+    let (left, top) = (i64::from(rect.x), i64::from(rect.y));
+    let (right, bottom) = (left + i64::from(rect.width), top + i64::from(rect.height));
+    let (width, height) = (surface.width(), surface.height());
+
+    let mut edge = |x: (i64, i64), y: (i64, i64)| {
+        if let Some(area) = clip_area(x, y, width, height) {
+            fill_area(surface, area, color);
+        }
+    };
+
+    edge((left, right), (top, top + 1));
+
+    if rect.height > 1 {
+        edge((left, right), (bottom - 1, bottom));
+    }
+
+    if rect.height > 2 {
+        edge((left, left + 1), (top + 1, bottom - 1));
+
+        if rect.width > 1 {
+            edge((right - 1, right), (top + 1, bottom - 1));
+        }
+    }
+}
+
+/// Draw a 1 pixel wide line between two points (both included).
+pub fn line(surface: &mut Surface, from: (i32, i32), to: (i32, i32), color: Rgba8) {
+    // Only the visible part of the line is drawn, so that its length is bounded by the size of the surface.
+    let Some((from, to)) = clip_line(from, to, surface.width(), surface.height()) else {
+        return;
+    };
+
+    // Bresenham's line algorithm.
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let step_x = if x < to.0 { 1 } else { -1 };
+    let step_y = if y < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    loop {
+        // Both ends are inside of the surface, and so is every point between them.
+        plot(surface, x as usize, y as usize, color);
+
+        if (x, y) == to {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Fill a circle.
+pub fn fill_circle(surface: &mut Surface, center: (i32, i32), radius: u32, color: Rgba8) {
+    let radius = i64::from(radius);
+    let (center_x, center_y) = (i64::from(center.0), i64::from(center.1));
+
+    for dy in circle_rows(surface, center_y, radius) {
+        let half_width = circle_half_width(radius, dy);
+        let y = center_y + dy;
+
+        fill_span(
+            surface,
+            center_x - half_width,
+            center_x + half_width + 1,
+            y,
+            color,
+        );
+    }
+}
+
+/// Draw the 1 pixel wide outline of a circle.
+pub fn stroke_circle(surface: &mut Surface, center: (i32, i32), radius: u32, color: Rgba8) {
+    let radius = i64::from(radius);
+    let (center_x, center_y) = (i64::from(center.0), i64::from(center.1));
+
+    // The outline is made of the pixels of the circle that aren't part of the circle 1 pixel smaller.
+    for dy in circle_rows(surface, center_y, radius) {
+        let outer = circle_half_width(radius, dy);
+        let inner = if dy.abs() < radius {
+            circle_half_width(radius - 1, dy)
+        } else {
+            -1
+        };
+        let y = center_y + dy;
+
+        if inner < 0 {
+            // Rows without inner pixels are drawn entirely.
+            fill_span(surface, center_x - outer, center_x + outer + 1, y, color);
+        } else {
+            fill_span(surface, center_x - outer, center_x - inner, y, color);
+            fill_span(
+                surface,
+                center_x + inner + 1,
+                center_x + outer + 1,
+                y,
+                color,
+            );
+        }
+    }
+}
+
+/// Draw a sprite with its top-left corner at (`x`, `y`).
+pub fn blit<P: Pixel>(surface: &mut Surface, sprite: &Sprite<P>, x: i32, y: i32) {
+    let rect = Rect::new(x, y, sprite.width as u32, sprite.height as u32);
+    let Some((left, right, top, bottom)) = rect.clip(surface.width(), surface.height()) else {
+        return;
+    };
+
+    for surface_x in left..right {
+        for surface_y in top..bottom {
+            let sprite_x = (surface_x as i64 - i64::from(x)) as usize;
+            let sprite_y = (surface_y as i64 - i64::from(y)) as usize;
+
+            let color = sprite.pixels[sprite_y * sprite.width + sprite_x].into();
+            plot(surface, surface_x, surface_y, color);
+        }
+    }
+}
+
+// Fill an area inside of the surface, as returned by `clip_area()`.
+fn fill_area(
+    surface: &mut Surface,
+    (left, right, top, bottom): (usize, usize, usize, usize),
+    color: Rgba8,
+) {
+    for x in left..right {
+        for y in top..bottom {
+            plot(surface, x, y, color);
+        }
+    }
+}
+
+// Fill the pixels of the row `y` from `left` to `right` (excluded), clipped to the surface.
+fn fill_span(surface: &mut Surface, left: i64, right: i64, y: i64, color: Rgba8) {
+    if let Some(area) = clip_area((left, right), (y, y + 1), surface.width(), surface.height()) {
+        fill_area(surface, area, color);
+    }
+}
+
+// Clip a line to the bounds of the surface with the Cohen-Sutherland algorithm, returning the ends of its visible part.
+fn clip_line(
+    from: (i32, i32),
+    to: (i32, i32),
+    width: usize,
+    height: usize,
+) -> Option<((i64, i64), (i64, i64))> {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const TOP: u8 = 4;
+    const BOTTOM: u8 = 8;
+
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let (max_x, max_y) = (width as i64 - 1, height as i64 - 1);
+
+    // Which sides of the surface a point is outside of.
+    let outcode = |(x, y): (i64, i64)| {
+        let mut code = 0;
+        if x < 0 {
+            code |= LEFT;
+        } else if x > max_x {
+            code |= RIGHT;
+        }
+        if y < 0 {
+            code |= TOP;
+        } else if y > max_y {
+            code |= BOTTOM;
+        }
+        code
+    };
+
+    let mut from = (i64::from(from.0), i64::from(from.1));
+    let mut to = (i64::from(to.0), i64::from(to.1));
+
+    loop {
+        let (from_code, to_code) = (outcode(from), outcode(to));
+        if from_code | to_code == 0 {
+            return Some((from, to));
+        }
+        if from_code & to_code != 0 {
+            // Both ends are on the outer side of the same edge.
+            return None;
+        }
+
+        // Move an outside end to the edge it's outside of, along the line.
+        // The other end is on the inner side of that edge, so the line isn't parallel to it.
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let (point, code) = if from_code != 0 {
+            (&mut from, from_code)
+        } else {
+            (&mut to, to_code)
+        };
+        let (x, y) = *point;
+
+        *point = if code & (TOP | BOTTOM) != 0 {
+            let edge = if code & TOP != 0 { 0 } else { max_y };
+            (x + div_round(dx, edge - y, dy), edge)
+        } else {
+            let edge = if code & LEFT != 0 { 0 } else { max_x };
+            (edge, y + div_round(dy, edge - x, dx))
+        };
+    }
+}
+
+// Compute `a * b / c` rounded to the nearest integer, without overflowing.
+fn div_round(a: i64, b: i64, c: i64) -> i64 {
+    let (numerator, denominator) = (i128::from(a) * i128::from(b), i128::from(c));
+    let (numerator, denominator) = if denominator < 0 {
+        (-numerator, -denominator)
+    } else {
+        (numerator, denominator)
+    };
+
+    (2 * numerator + denominator).div_euclid(2 * denominator) as i64
+}
+
+// Offsets from the center of the rows of a circle that are inside of the surface.
+fn circle_rows(surface: &Surface, center_y: i64, radius: i64) -> std::ops::RangeInclusive<i64> {
+    let first = (-radius).max(-center_y);
+    let last = radius.min(surface.height() as i64 - 1 - center_y);
+
+    first..=last
+}
+
+// Draw a pixel inside of the surface, blending it if needed.
+fn plot(surface: &mut Surface, x: usize, y: usize, color: Rgba8) {
+    match color.a {
+        u8::MAX => {
+            surface.set_pixel(x, y, color);
+        }
+        0 => {}
+        _ => {
+            if let Some(dst) = surface.pixel(x, y) {
+                surface.set_pixel(x, y, blend(color, dst));
+            }
+        }
+    }
+}
+
+// Half width of the row `dy` of a circle, i.e. the largest `dx` such that `dx² + dy² <= radius²`.
+// Returns -1 if the row is outside of the circle.
+fn circle_half_width(radius: i64, dy: i64) -> i64 {
+    let squared = radius * radius - dy * dy;
+    if squared < 0 {
+        return -1;
+    }
+
+    let mut half_width = (squared as f64).sqrt() as i64;
+    // Fix the rounding errors of the floating point square root.
+    while half_width * half_width > squared {
+        half_width -= 1;
+    }
+    while (half_width + 1) * (half_width + 1) <= squared {
+        half_width += 1;
+    }
+
+    half_width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::FramebufferFormat;
+
+    const BLACK: Rgba8 = Rgba8::opaque(0, 0, 0);
+    const WHITE: Rgba8 = Rgba8::opaque(255, 255, 255);
+
+    // Render the surface as text, with `#` for white pixels and `.` for black ones.
+    fn render(surface: &Surface) -> Vec<String> {
+        surface
+            .rows()
+            .map(|row| {
+                row.map(|pixel| if pixel == WHITE { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn draw(
+        width: usize,
+        height: usize,
+        format: FramebufferFormat,
+        f: impl FnOnce(&mut Surface),
+    ) -> Vec<String> {
+        let mut buffer = vec![0; Surface::buffer_len(width, height, format)];
+        let mut surface = Surface::new(&mut buffer, width, height, format);
+        surface.fill(BLACK);
+
+        f(&mut surface);
+        render(&surface)
+    }
+
+    #[test]
+    fn rects() {
+        let pixels = draw(6, 4, FramebufferFormat::Bgr8, |surface| {
+            fill_rect(surface, Rect::new(-2, -1, 4, 3), WHITE);
+            stroke_rect(surface, Rect::new(3, 1, 4, 3), WHITE);
+        });
+
+        assert_eq!(pixels, ["##....", "##.###", "...#..", "...###"]);
+
+        // Edges beyond the range of `i32` are outside of the surface.
+        let pixels = draw(3, 2, FramebufferFormat::Bgr8, |surface| {
+            stroke_rect(surface, Rect::new(i32::MAX - 1, 0, 10, 10), WHITE);
+            stroke_rect(surface, Rect::new(-1, -1, u32::MAX, 3), WHITE);
+        });
+
+        assert_eq!(pixels, ["...", "###"]);
+    }
+
+    #[test]
+    fn lines() {
+        let pixels = draw(5, 4, FramebufferFormat::Rgb565, |surface| {
+            line(surface, (0, 0), (4, 2), WHITE);
+            line(surface, (-1, 3), (10, 3), WHITE);
+        });
+
+        assert_eq!(pixels, ["#....", ".##..", "...##", "#####"]);
+
+        // Lines are clipped before being drawn, so their length doesn't matter.
+        let pixels = draw(5, 4, FramebufferFormat::Rgb565, |surface| {
+            line(surface, (i32::MIN, 0), (i32::MAX, 0), WHITE);
+            line(surface, (-2, 0), (6, 4), WHITE);
+            line(surface, (-10, -10), (-1, 10), WHITE);
+        });
+
+        assert_eq!(pixels, ["#####", "#....", ".##..", "...##"]);
+    }
+
+    #[test]
+    fn circles() {
+        let filled = draw(7, 7, FramebufferFormat::Rgba8, |surface| {
+            fill_circle(surface, (3, 3), 3, WHITE);
+        });
+        assert_eq!(
+            filled,
+            ["...#...", ".#####.", ".#####.", "#######", ".#####.", ".#####.", "...#..."]
+        );
+
+        let outline = draw(7, 7, FramebufferFormat::Rgba8, |surface| {
+            stroke_circle(surface, (3, 3), 3, WHITE);
+        });
+        assert_eq!(
+            outline,
+            ["...#...", ".##.##.", ".#...#.", "#.....#", ".#...#.", ".##.##.", "...#..."]
+        );
+    }
+
+    #[test]
+    fn sprites() {
+        let pixels = [WHITE, Rgba8::new(0, 0, 0, 0), WHITE, WHITE];
+        let sprite = Sprite::new(&pixels, 2, 2);
+
+        let rendered = draw(3, 3, FramebufferFormat::Rgb5A1, |surface| {
+            blit(surface, &sprite, 0, 0);
+            blit(surface, &sprite, 2, -1);
+        });
+
+        assert_eq!(rendered, ["#.#", "##.", "..."]);
+    }
+
+    #[test]
+    fn blending() {
+        let half_red = Rgba8::new(255, 0, 0, 128);
+        assert_eq!(blend(half_red, WHITE), Rgba8::new(255, 127, 127, 255));
+        // Colors aren't darkened by transparent destinations.
+        assert_eq!(
+            blend(half_red, Rgba8::new(0, 0, 0, 0)),
+            Rgba8::new(255, 0, 0, 128)
+        );
+        assert_eq!(
+            blend(half_red, Rgba8::new(0, 255, 0, 128)),
+            Rgba8::new(170, 85, 0, 192)
+        );
+
+        let mut buffer = vec![0; Surface::buffer_len(1, 1, FramebufferFormat::Bgr8)];
+        let mut surface = Surface::new(&mut buffer, 1, 1, FramebufferFormat::Bgr8);
+        surface.fill(Rgba8::opaque(0, 0, 255));
+
+        pixel(&mut surface, 0, 0, half_red);
+        assert_eq!(surface.pixel(0, 0), Some(Rgba8::opaque(128, 0, 127)));
+    }
+}
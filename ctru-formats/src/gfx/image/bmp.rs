@@ -0,0 +1,369 @@
+//! BMP decoding.
+//!
+//! Uncompressed images of every bit depth are supported (with a palette for depths of 8 bits or less),
+//! as well as bit fields, but not run-length encoded images.
+
+use std::io;
+
+use super::{check_size, Decoded};
+use crate::bytes::{get, invalid_data, read_u16, read_u32};
+use crate::gfx::pixel::Rgba8;
+
+// Size of the file header, which is followed by the info header.
+const FILE_HEADER_LEN: usize = 14;
+
+// Size of the original OS/2 info header (`BITMAPCOREHEADER`), and of the Windows one (`BITMAPINFOHEADER`).
+const CORE_HEADER_LEN: usize = 12;
+const INFO_HEADER_LEN: usize = 40;
+
+// Compression methods.
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+// A bit field holding a component of the pixels.
+#[derive(Copy, Clone)]
+struct Mask {
+    shift: u32,
+    max: u32,
+}
+
+impl Mask {
+    fn new(mask: u32) -> Self {
+        let shift = mask.trailing_zeros();
+
+        Self {
+            shift,
+            max: mask.checked_shr(shift).unwrap_or(0),
+        }
+    }
+
+    // Extract the component from a pixel, scaled to 8 bits.
+    fn extract(self, pixel: u32, default: u8) -> u8 {
+        if self.max == 0 {
+            return default;
+        }
+
+        let value = u64::from((pixel >> self.shift) & self.max);
+        let max = u64::from(self.max);
+
+        ((value * 255 + max / 2) / max) as u8
+    }
+}
+
+pub(super) fn decode(data: &[u8], max_pixels: usize) -> io::Result<Decoded> {
+    let file_header = get(data, 0, FILE_HEADER_LEN)?;
+    if &file_header[..2] != b"BM" {
+        return Err(invalid_data("not a BMP image"));
+    }
+    let pixels_offset = read_u32(file_header, 10) as usize;
+
+    let header_len = read_u32(get(data, FILE_HEADER_LEN, 4)?, 0) as usize;
+    let header = get(data, FILE_HEADER_LEN, header_len)?;
+
+    let (width, height, bit_depth, compression, palette_len, palette_entry);
+    let mut palette_offset = FILE_HEADER_LEN + header_len;
+    let mut masks = [0; 4];
+
+    match header_len {
+        CORE_HEADER_LEN => {
+            width = i64::from(read_u16(header, 4));
+            height = i64::from(read_u16(header, 6));
+            bit_depth = read_u16(header, 10);
+            compression = BI_RGB;
+            palette_len = 0;
+            palette_entry = 3;
+        }
+        INFO_HEADER_LEN.. => {
+            width = i64::from(read_u32(header, 4) as i32);
+            height = i64::from(read_u32(header, 8) as i32);
+            bit_depth = read_u16(header, 14);
+            compression = read_u32(header, 16);
+            palette_len = read_u32(header, 32) as usize;
+            palette_entry = 4;
+
+            // Newer headers include the masks, which otherwise follow the header when used.
+            let mask_count = match compression {
+                BI_BITFIELDS => 3,
+                BI_ALPHABITFIELDS => 4,
+                _ => 0,
+            };
+            let masks_data = if header_len == INFO_HEADER_LEN {
+                palette_offset += mask_count * 4;
+                get(data, FILE_HEADER_LEN + INFO_HEADER_LEN, mask_count * 4)?
+            } else {
+                &header[INFO_HEADER_LEN..]
+            };
+
+            for (i, mask) in masks.iter_mut().enumerate() {
+                if let Some(bytes) = masks_data.get(i * 4..i * 4 + 4) {
+                    *mask = read_u32(bytes, 0);
+                }
+            }
+        }
+        _ => return Err(invalid_data("invalid BMP header")),
+    }
+
+    // Images are stored from the bottom row to the top one, unless their height is negative.
+    let top_down = height < 0;
+    if width < 0 {
+        return Err(invalid_data("invalid BMP image size"));
+    }
+    let (width, height) = check_size(width as usize, height.unsigned_abs() as usize, max_pixels)?;
+
+    let masks = match (compression, bit_depth) {
+        (BI_RGB, 1 | 4 | 8 | 24) => [0; 4],
+        (BI_RGB, 16) => [0x7C00, 0x03E0, 0x001F, 0],
+        (BI_RGB, 32) => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0],
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => masks,
+        (BI_RGB | BI_BITFIELDS | BI_ALPHABITFIELDS, _) => {
+            return Err(invalid_data("invalid BMP bit depth"))
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compressed BMP images aren't supported",
+            ))
+        }
+    };
+    let masks = masks.map(Mask::new);
+
+    let palette: Vec<Rgba8> = if bit_depth <= 8 {
+        let max_len = 1 << bit_depth;
+        let len = if palette_len == 0 || palette_len > max_len {
+            max_len
+        } else {
+            palette_len
+        };
+
+        get(data, palette_offset, len * palette_entry)?
+            .chunks_exact(palette_entry)
+            .map(|color| Rgba8::opaque(color[2], color[1], color[0]))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Rows are padded to a multiple of 4 bytes.
+    let stride = width
+        .checked_mul(bit_depth.into())
+        .map(|bits| bits.div_ceil(32) * 4)
+        .ok_or_else(|| invalid_data("invalid BMP image size"))?;
+    let rows = stride
+        .checked_mul(height)
+        .ok_or_else(|| invalid_data("invalid BMP image size"))?;
+    let rows = get(data, pixels_offset, rows)?;
+
+    let mut pixels = vec![Rgba8::default(); width * height];
+    for (i, row) in rows.chunks_exact(stride).enumerate() {
+        let y = if top_down { i } else { height - 1 - i };
+
+        for (x, pixel) in pixels[y * width..][..width].iter_mut().enumerate() {
+            *pixel = match bit_depth {
+                1 | 4 | 8 => {
+                    let bit = x * usize::from(bit_depth);
+                    let shift = 8 - usize::from(bit_depth) - bit % 8;
+                    let index = (row[bit / 8] >> shift) & ((1 << bit_depth) - 1) as u8;
+
+                    *palette
+                        .get(usize::from(index))
+                        .ok_or_else(|| invalid_data("invalid BMP palette index"))?
+                }
+                24 => Rgba8::opaque(row[x * 3 + 2], row[x * 3 + 1], row[x * 3]),
+                _ => {
+                    let value = if bit_depth == 16 {
+                        u32::from(read_u16(row, x * 2))
+                    } else {
+                        read_u32(row, x * 4)
+                    };
+
+                    Rgba8::new(
+                        masks[0].extract(value, 0),
+                        masks[1].extract(value, 0),
+                        masks[2].extract(value, 0),
+                        masks[3].extract(value, u8::MAX),
+                    )
+                }
+            };
+        }
+    }
+
+    Ok(Decoded {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_PIXELS: usize = super::super::DEFAULT_PIXEL_LIMIT;
+
+    // Build a BMP image with a `BITMAPINFOHEADER`.
+    fn encode(
+        width: i32,
+        height: i32,
+        bit_depth: u16,
+        compression: u32,
+        masks: &[u32],
+        palette: &[[u8; 4]],
+        rows: &[&[u8]],
+    ) -> Vec<u8> {
+        let pixels_offset = FILE_HEADER_LEN + INFO_HEADER_LEN + masks.len() * 4 + palette.len() * 4;
+
+        let mut bmp = b"BM".to_vec();
+        bmp.extend_from_slice(&[0; 8]);
+        bmp.extend_from_slice(&(pixels_offset as u32).to_le_bytes());
+
+        bmp.extend_from_slice(&(INFO_HEADER_LEN as u32).to_le_bytes());
+        bmp.extend_from_slice(&width.to_le_bytes());
+        bmp.extend_from_slice(&height.to_le_bytes());
+        bmp.extend_from_slice(&1u16.to_le_bytes());
+        bmp.extend_from_slice(&bit_depth.to_le_bytes());
+        bmp.extend_from_slice(&compression.to_le_bytes());
+        bmp.extend_from_slice(&[0; 12]);
+        bmp.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+        bmp.extend_from_slice(&[0; 4]);
+
+        for mask in masks {
+            bmp.extend_from_slice(&mask.to_le_bytes());
+        }
+        bmp.extend(palette.iter().flatten());
+
+        // Pad the rows to a multiple of 4 bytes.
+        for row in rows {
+            bmp.extend_from_slice(row);
+            bmp.resize(bmp.len() + (4 - row.len() % 4) % 4, 0);
+        }
+
+        bmp
+    }
+
+    #[test]
+    fn rgb() {
+        // Bottom-up rows, padded.
+        let bmp = encode(
+            2,
+            2,
+            24,
+            BI_RGB,
+            &[],
+            &[],
+            &[&[0, 0, 255, 0, 255, 0], &[255, 0, 0, 10, 20, 30]],
+        );
+        let decoded = decode(&bmp, MAX_PIXELS).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (2, 2));
+        assert_eq!(
+            decoded.pixels,
+            [
+                Rgba8::opaque(0, 0, 255),
+                Rgba8::opaque(30, 20, 10),
+                Rgba8::opaque(255, 0, 0),
+                Rgba8::opaque(0, 255, 0),
+            ]
+        );
+
+        // Top-down rows, with the default 16 bits layout (5 bits per component).
+        let bmp = encode(1, -2, 16, BI_RGB, &[], &[], &[&[0x00, 0x7C], &[0xFF, 0x03]]);
+        assert_eq!(
+            decode(&bmp, MAX_PIXELS).unwrap().pixels,
+            [Rgba8::opaque(255, 0, 0), Rgba8::opaque(0, 255, 255)]
+        );
+    }
+
+    #[test]
+    fn bit_fields() {
+        let rgb565 = [0xF800, 0x07E0, 0x001F];
+        let bmp = encode(
+            2,
+            1,
+            16,
+            BI_BITFIELDS,
+            &rgb565,
+            &[],
+            &[&[0xE0, 0x07, 0x1F, 0xF8]],
+        );
+        assert_eq!(
+            decode(&bmp, MAX_PIXELS).unwrap().pixels,
+            [Rgba8::opaque(0, 255, 0), Rgba8::opaque(255, 0, 255)]
+        );
+
+        let rgba8 = [0xFF00_0000, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF];
+        let bmp = encode(1, 1, 32, BI_ALPHABITFIELDS, &rgba8, &[], &[&[128, 3, 2, 1]]);
+        assert_eq!(
+            decode(&bmp, MAX_PIXELS).unwrap().pixels,
+            [Rgba8::new(1, 2, 3, 128)]
+        );
+
+        // Without an alpha mask, the 4th byte is unused.
+        let bmp = encode(1, 1, 32, BI_RGB, &[], &[], &[&[3, 2, 1, 0]]);
+        assert_eq!(
+            decode(&bmp, MAX_PIXELS).unwrap().pixels,
+            [Rgba8::opaque(1, 2, 3)]
+        );
+    }
+
+    #[test]
+    fn palettes() {
+        let palette = [[0, 0, 0, 0], [255, 255, 255, 0], [0, 0, 255, 0]];
+
+        let bmp = encode(
+            10,
+            1,
+            1,
+            BI_RGB,
+            &[],
+            &palette[..2],
+            &[&[0b1010_0000, 0b0100_0000]],
+        );
+        let pixels = decode(&bmp, MAX_PIXELS).unwrap().pixels;
+        let lit: Vec<bool> = pixels.iter().map(|pixel| pixel.r == 255).collect();
+        assert_eq!(
+            lit,
+            [true, false, true, false, false, false, false, false, false, true]
+        );
+
+        let bmp = encode(3, 1, 4, BI_RGB, &[], &palette, &[&[0x21, 0x00]]);
+        assert_eq!(
+            decode(&bmp, MAX_PIXELS).unwrap().pixels,
+            [
+                Rgba8::opaque(255, 0, 0),
+                Rgba8::opaque(255, 255, 255),
+                Rgba8::opaque(0, 0, 0),
+            ]
+        );
+
+        // Palette index out of bounds.
+        let bmp = encode(1, 1, 8, BI_RGB, &[], &palette, &[&[3]]);
+        assert!(decode(&bmp, MAX_PIXELS).is_err());
+    }
+
+    #[test]
+    fn invalid_images() {
+        let bmp = encode(2, 2, 24, BI_RGB, &[], &[], &[&[0; 6], &[0; 6]]);
+        assert!(decode(&bmp, MAX_PIXELS).is_ok());
+        assert!(decode(&bmp[..bmp.len() - 1], MAX_PIXELS).is_err());
+
+        // Run-length encoding.
+        let mut rle = bmp.clone();
+        rle[FILE_HEADER_LEN + 16] = 1;
+        assert_eq!(
+            decode(&rle, MAX_PIXELS).err().map(|err| err.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+
+        // Too many pixels, rejected before reading them.
+        let huge = encode(1 << 16, -(1 << 16), 24, BI_RGB, &[], &[], &[]);
+        assert_eq!(
+            decode(&huge, MAX_PIXELS).err().map(|err| err.kind()),
+            Some(io::ErrorKind::OutOfMemory)
+        );
+
+        let mut corrupted = bmp;
+        corrupted[FILE_HEADER_LEN] = 20;
+        assert!(decode(&corrupted, MAX_PIXELS).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Image decoding.
+//!
+//! [`Image`] decodes BMP and PNG images directly into one of the [`FramebufferFormat`]s, stored in the framebuffer layout
+//! (see [`surface`](super::surface)), so that they can be copied to a screen as they are.
+//! Images can be read from the RomFS, from any other file, or from any [`Read`]er.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::gfx::image::Image;
+//! use ctru_formats::gfx::surface::Surface;
+//! use ctru_formats::gfx::FramebufferFormat;
+//!
+//! // On the console, this would be the surface of a screen (and the path something like `romfs:/logo.png`).
+//! let mut framebuffer = vec![0; Surface::buffer_len(320, 240, FramebufferFormat::Bgr8)];
+//! let mut surface = Surface::new(&mut framebuffer, 320, 240, FramebufferFormat::Bgr8);
+//!
+//! let image = Image::open("logo.png", FramebufferFormat::Bgr8)?;
+//! image.copy_to(&mut surface, 10, 10);
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use super::draw::Rect;
+use super::pixel::{self, Rgba8};
+use super::surface::Surface;
+use crate::gfx::FramebufferFormat;
+
+mod bmp;
+mod png;
+
+/// Maximum number of pixels of the images decoded by [`Image::decode()`]: 1024x1024, the size of the largest GPU textures.
+///
+/// Use [`Image::decode_with_limit()`] to decode larger images.
+pub const DEFAULT_PIXEL_LIMIT: usize = 1024 * 1024;
+
+// An image decoded into row-major pixels, before being converted into the framebuffer layout.
+struct Decoded {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgba8>,
+}
+
+// Check the size of an image before allocating it.
+//
+// Like in the PNG specification, the width and height must fit in an `i32`, and the image can't have more than `max_pixels` pixels.
+fn check_size(width: usize, height: usize, max_pixels: usize) -> io::Result<(usize, usize)> {
+    if width == 0 || height == 0 || width > i32::MAX as usize || height > i32::MAX as usize {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid image size",
+        ));
+    }
+
+    match width.checked_mul(height) {
+        Some(pixels) if pixels <= max_pixels => Ok((width, height)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            format!("image of {width}x{height} pixels exceeds the limit of {max_pixels} pixels"),
+        )),
+    }
+}
+
+/// An image stored in the framebuffer layout, in one of the [`FramebufferFormat`]s.
+///
+/// All coordinates are logical: `x` goes from the left to the right of the image, and `y` from its top to its bottom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    data: Vec<u8>,
+    width: usize,
+    height: usize,
+    format: FramebufferFormat,
+}
+
+impl Image {
+    /// Create a new image, filled with zeroes (i.e. black and transparent pixels).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the size of the image in bytes overflows a `usize`.
+    pub fn new(width: usize, height: usize, format: FramebufferFormat) -> Self {
+        Self {
+            data: vec![0; Surface::buffer_len(width, height, format)],
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// Decode a BMP or PNG image, converting it into the given format.
+    ///
+    /// The type of the image is detected from its content. The alpha component of the pixels is dropped if the format doesn't have one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if reading fails, an error of kind [`io::ErrorKind::InvalidData`] if the image is invalid,
+    /// an error of kind [`io::ErrorKind::Unsupported`] if it isn't a BMP or PNG image, or uses an unsupported feature
+    /// (e.g. compressed BMP images), and an error of kind [`io::ErrorKind::OutOfMemory`] if it has more than [`DEFAULT_PIXEL_LIMIT`] pixels.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// use ctru_formats::gfx::image::Image;
+    /// use ctru_formats::gfx::FramebufferFormat;
+    ///
+    /// let file = BufReader::new(File::open("background.bmp")?);
+    /// let image = Image::decode(file, FramebufferFormat::Bgr8)?;
+    ///
+    /// // A 320x240 image can be copied directly into the framebuffer of the bottom screen.
+    /// let mut framebuffer = vec![0; 320 * 240 * 3];
+    /// framebuffer.copy_from_slice(image.as_bytes());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decode<R: Read>(reader: R, format: FramebufferFormat) -> io::Result<Self> {
+        Self::decode_with_limit(reader, format, DEFAULT_PIXEL_LIMIT)
+    }
+
+    /// Decode a BMP or PNG image with at most `max_pixels` pixels, converting it into the given format.
+    ///
+    /// The size of the image is checked before decoding its pixels, so the limit also bounds the memory used by the decoder.
+    ///
+    /// # Errors
+    ///
+    /// See [`Image::decode()`].
+    pub fn decode_with_limit<R: Read>(
+        mut reader: R,
+        format: FramebufferFormat,
+        max_pixels: usize,
+    ) -> io::Result<Self> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let decoded = if data.starts_with(&png::SIGNATURE) {
+            png::decode(&data, max_pixels)?
+        } else if data.starts_with(b"BM") {
+            bmp::decode(&data, max_pixels)?
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unknown image type",
+            ));
+        };
+
+        let mut image = Self::new(decoded.width, decoded.height, format);
+        let mut surface = image.surface();
+        for (i, &pixel) in decoded.pixels.iter().enumerate() {
+            surface.set_pixel(i % decoded.width, i / decoded.width, pixel);
+        }
+
+        Ok(image)
+    }
+
+    /// Decode the BMP or PNG image at the given path (e.g. `romfs:/image.png`), converting it into the given format.
+    ///
+    /// # Errors
+    ///
+    /// See [`Image::decode()`].
+    pub fn open<P: AsRef<Path>>(path: P, format: FramebufferFormat) -> io::Result<Self> {
+        Self::decode(fs::File::open(path)?, format)
+    }
+
+    /// Returns the width of the image in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the format of the image.
+    pub fn format(&self) -> FramebufferFormat {
+        self.format
+    }
+
+    /// Returns the pixel at (`x`, `y`), or `None` if it's outside of the image.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgba8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let depth = self.format.pixel_depth_bytes();
+        let offset = (x * self.height + self.height - 1 - y) * depth;
+
+        Some(pixel::read_pixel(
+            self.format,
+            &self.data[offset..offset + depth],
+        ))
+    }
+
+    /// Returns a [`Surface`] to draw on the image.
+    pub fn surface(&mut self) -> Surface<'_> {
+        Surface::new(&mut self.data, self.width, self.height, self.format)
+    }
+
+    /// Returns the raw data of the image, in the framebuffer layout.
+    ///
+    /// If the image has the size and format of a screen's framebuffer, it can be copied directly into it.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the image, returning its raw data.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Copy the image onto a surface, with its top-left corner at (`x`, `y`).
+    ///
+    /// Pixels are copied as they are, without blending, and converted if the surface has another format.
+    /// The image is clipped to the bounds of the surface.
+    pub fn copy_to(&self, surface: &mut Surface, x: i32, y: i32) {
+        let rect = Rect::new(x, y, self.width as u32, self.height as u32);
+        let Some((left, right, top, bottom)) = rect.clip(surface.width(), surface.height()) else {
+            return;
+        };
+
+        // Position of the clipped area within the image.
+        let image_left = (left as i64 - i64::from(x)) as usize;
+        let image_top = (top as i64 - i64::from(y)) as usize;
+        let image_bottom = image_top + bottom - top;
+
+        if surface.format() != self.format {
+            for surface_x in left..right {
+                for surface_y in top..bottom {
+                    let image_x = image_left + surface_x - left;
+                    let image_y = image_top + surface_y - top;
+
+                    // Both pixels are inside of their image.
+                    let pixel = self.pixel(image_x, image_y).unwrap();
+                    surface.set_pixel(surface_x, surface_y, pixel);
+                }
+            }
+
+            return;
+        }
+
+        // Both images are stored column by column, from the bottom to the top,
+        // so the clipped part of each column can be copied at once.
+        let depth = self.format.pixel_depth_bytes();
+        let surface_height = surface.height();
+        let surface_data = surface.as_bytes_mut();
+
+        for surface_x in left..right {
+            let image_x = image_left + surface_x - left;
+
+            let src = (image_x * self.height + self.height - image_bottom) * depth;
+            let dst = (surface_x * surface_height + surface_height - bottom) * depth;
+            let len = (bottom - top) * depth;
+
+            surface_data[dst..dst + len].copy_from_slice(&self.data[src..src + len]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static FERRIS_PNG: &[u8] = include_bytes!("../../../../ctru-rs/examples/assets/ferris.png");
+    // `ferris.png` converted to BGR and rotated by 90 degrees (i.e. in the framebuffer layout).
+    static FERRIS_RGB: &[u8] = include_bytes!("../../../../ctru-rs/examples/assets/ferris.rgb");
+
+    #[test]
+    fn reference_image() {
+        let image = Image::decode(FERRIS_PNG, FramebufferFormat::Bgr8).unwrap();
+
+        assert_eq!((image.width(), image.height()), (320, 240));
+        assert!(image.as_bytes() == FERRIS_RGB);
+    }
+
+    #[test]
+    fn formats() {
+        let image = Image::decode(FERRIS_PNG, FramebufferFormat::Rgba8).unwrap();
+        let converted = Image::decode(FERRIS_PNG, FramebufferFormat::Rgb565).unwrap();
+
+        for (x, y) in [(0, 0), (160, 120), (319, 239), (100, 200)] {
+            let pixel = image.pixel(x, y).unwrap();
+            assert_eq!(
+                converted.pixel(x, y),
+                Some(pixel::Rgb565::from(pixel).into())
+            );
+        }
+
+        assert_eq!(image.pixel(320, 0), None);
+    }
+
+    // Render white pixels as '#' and red ones as 'r'.
+    fn render(surface: &Surface) -> Vec<String> {
+        surface
+            .rows()
+            .map(|row| {
+                row.map(|pixel| match (pixel.r, pixel.g) {
+                    (255, 255) => '#',
+                    (255, 0) => 'r',
+                    _ => '.',
+                })
+                .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn copy() {
+        let format = FramebufferFormat::Rgb565;
+        let mut image = Image::new(3, 2, format);
+        image.surface().fill(Rgba8::opaque(255, 255, 255));
+        image.surface().set_pixel(0, 0, Rgba8::opaque(255, 0, 0));
+
+        for surface_format in [format, FramebufferFormat::Bgr8] {
+            let mut buffer = vec![0; Surface::buffer_len(4, 4, surface_format)];
+            let mut surface = Surface::new(&mut buffer, 4, 4, surface_format);
+
+            image.copy_to(&mut surface, 2, -1);
+
+            assert_eq!(render(&surface), ["..##", "....", "....", "...."]);
+
+            image.copy_to(&mut surface, -1, 2);
+            image.copy_to(&mut surface, 1, 3);
+            image.copy_to(&mut surface, 10, 10);
+
+            assert_eq!(render(&surface), ["..##", "....", "##..", "#r##"]);
+        }
+    }
+
+    #[test]
+    fn unknown_type() {
+        let err = Image::decode(&b"GIF89a"[..], FramebufferFormat::Bgr8).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
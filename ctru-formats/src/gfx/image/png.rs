@@ -0,0 +1,232 @@
+//! PNG decoding, with the [`png`](::png) crate.
+//!
+//! Every color type and bit depth of the PNG specification is supported, as well as interlaced images and transparency (`tRNS`) chunks.
+//! Samples with a depth of 16 bits are reduced to 8 bits, and the other ancillary chunks (e.g. gamma correction) are ignored.
+
+use std::io;
+
+use ::png::{BitDepth, ColorType, Decoder, DecodingError, Limits, Transformations};
+
+use super::{check_size, Decoded};
+use crate::bytes::invalid_data;
+use crate::gfx::pixel::Rgba8;
+
+/// Signature at the start of every PNG file.
+pub(super) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+pub(super) fn decode(data: &[u8], max_pixels: usize) -> io::Result<Decoded> {
+    // The decoder only allocates its row buffers and ancillary chunks, which are much smaller than the decoded image.
+    let limits = Limits {
+        bytes: max_pixels.saturating_mul(8),
+    };
+    let mut decoder = Decoder::new_with_limits(data, limits);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(decoding_error)?;
+
+    // The size is checked before allocating the image, so that a small (but highly compressed) file can't exhaust the memory.
+    let info = reader.info();
+    let (width, height) = check_size(info.width as usize, info.height as usize, max_pixels)?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut buffer).map_err(decoding_error)?;
+
+    let pixels = match reader.output_color_type() {
+        (ColorType::Grayscale, BitDepth::Eight) => buffer
+            .iter()
+            .map(|&gray| Rgba8::opaque(gray, gray, gray))
+            .collect(),
+        (ColorType::GrayscaleAlpha, BitDepth::Eight) => buffer
+            .chunks_exact(2)
+            .map(|pixel| Rgba8::new(pixel[0], pixel[0], pixel[0], pixel[1]))
+            .collect(),
+        (ColorType::Rgb, BitDepth::Eight) => buffer
+            .chunks_exact(3)
+            .map(|pixel| Rgba8::opaque(pixel[0], pixel[1], pixel[2]))
+            .collect(),
+        (ColorType::Rgba, BitDepth::Eight) => buffer
+            .chunks_exact(4)
+            .map(|pixel| Rgba8::new(pixel[0], pixel[1], pixel[2], pixel[3]))
+            .collect(),
+        // Palettes and smaller bit depths are expanded by the transformations.
+        _ => return Err(invalid_data("unexpected PNG output format")),
+    };
+
+    Ok(Decoded {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn decoding_error(err: DecodingError) -> io::Error {
+    match err {
+        DecodingError::IoError(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            invalid_data("unexpected end of data")
+        }
+        DecodingError::IoError(err) => err,
+        DecodingError::LimitsExceeded => io::Error::new(
+            io::ErrorKind::OutOfMemory,
+            "PNG image exceeds the memory limit",
+        ),
+        err => invalid_data(&err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::png::Encoder;
+
+    use super::*;
+    use crate::checksum::crc32;
+
+    const MAX_PIXELS: usize = super::super::DEFAULT_PIXEL_LIMIT;
+
+    fn encode(
+        width: u32,
+        height: u32,
+        color_type: ColorType,
+        bit_depth: BitDepth,
+        palette: &[u8],
+        transparency: &[u8],
+        image_data: &[u8],
+    ) -> Vec<u8> {
+        let mut png = Vec::new();
+
+        let mut encoder = Encoder::new(&mut png, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(bit_depth);
+        if !palette.is_empty() {
+            encoder.set_palette(palette);
+        }
+        if !transparency.is_empty() {
+            encoder.set_trns(transparency);
+        }
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(image_data).unwrap();
+        writer.finish().unwrap();
+
+        png
+    }
+
+    // Build a PNG image with a header and empty image data, which is enough to check its size.
+    fn header_only(width: u32, height: u32) -> Vec<u8> {
+        let mut header = width.to_be_bytes().to_vec();
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[8, 0, 0, 0, 0]);
+
+        let mut png = SIGNATURE.to_vec();
+        for (kind, data) in [(b"IHDR", &header[..]), (b"IDAT", &[])] {
+            let mut chunk = kind.to_vec();
+            chunk.extend_from_slice(data);
+
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(&chunk);
+            png.extend_from_slice(&crc32(&chunk).to_be_bytes());
+        }
+        png
+    }
+
+    #[test]
+    fn color_types() {
+        // 2 bits grayscale, with a transparent gray level.
+        let png = encode(
+            5,
+            1,
+            ColorType::Grayscale,
+            BitDepth::Two,
+            &[],
+            &[0, 2],
+            &[0b00011011, 0b11000000],
+        );
+        assert_eq!(
+            decode(&png, MAX_PIXELS).unwrap().pixels,
+            [
+                Rgba8::opaque(0, 0, 0),
+                Rgba8::opaque(85, 85, 85),
+                Rgba8::new(170, 170, 170, 0),
+                Rgba8::opaque(255, 255, 255),
+                Rgba8::opaque(255, 255, 255),
+            ]
+        );
+
+        // 4 bits palette, with the alpha of the first entries.
+        let palette = [255, 0, 0, 0, 255, 0, 0, 0, 255];
+        let png = encode(
+            3,
+            1,
+            ColorType::Indexed,
+            BitDepth::Four,
+            &palette,
+            &[128],
+            &[0x21, 0x00],
+        );
+        assert_eq!(
+            decode(&png, MAX_PIXELS).unwrap().pixels,
+            [
+                Rgba8::opaque(0, 0, 255),
+                Rgba8::opaque(0, 255, 0),
+                Rgba8::new(255, 0, 0, 128),
+            ]
+        );
+
+        // 16 bits grayscale with alpha.
+        let png = encode(
+            1,
+            1,
+            ColorType::GrayscaleAlpha,
+            BitDepth::Sixteen,
+            &[],
+            &[],
+            &[0x12, 0x34, 0xAB, 0xCD],
+        );
+        assert_eq!(
+            decode(&png, MAX_PIXELS).unwrap().pixels,
+            [Rgba8::new(0x12, 0x12, 0x12, 0xAB)]
+        );
+    }
+
+    #[test]
+    fn size_limits() {
+        let png = encode(3, 2, ColorType::Rgb, BitDepth::Eight, &[], &[], &[0; 18]);
+        let decoded = decode(&png, 6).unwrap();
+        assert_eq!((decoded.width, decoded.height), (3, 2));
+
+        assert_eq!(
+            decode(&png, 5).err().map(|err| err.kind()),
+            Some(io::ErrorKind::OutOfMemory)
+        );
+
+        // Rejected without decoding the (missing) image data.
+        assert_eq!(
+            decode(&header_only(1 << 20, 1 << 20), MAX_PIXELS)
+                .err()
+                .map(|err| err.kind()),
+            Some(io::ErrorKind::OutOfMemory)
+        );
+        assert_eq!(
+            decode(&header_only(1 << 31, 1), usize::MAX)
+                .err()
+                .map(|err| err.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    fn invalid_images() {
+        let png = encode(1, 1, ColorType::Grayscale, BitDepth::Eight, &[], &[], &[0]);
+        assert!(decode(&png, MAX_PIXELS).is_ok());
+
+        // Checksum mismatch.
+        let mut corrupted = png.clone();
+        corrupted[SIGNATURE.len() + 8] ^= 1;
+        assert!(decode(&corrupted, MAX_PIXELS).is_err());
+
+        // Truncated image.
+        assert_eq!(
+            decode(&png[..png.len() - 12], MAX_PIXELS)
+                .err()
+                .map(|err| err.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Framebuffer graphics.
+//!
+//! The modules in here work on image data laid out like the framebuffers of the console's screens,
+//! in any of the [`FramebufferFormat`]s: typed [`pixel`]s, [`surface`]s to access them with logical coordinates,
+//! a software rasterizer to [`draw`] on them, `image` decoding (with the `image` feature) and the [`tiling`] used by GPU textures.
+
+pub mod draw;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod pixel;
+pub mod surface;
+pub mod tiling;
+
+#[doc(alias = "GSPGPU_FramebufferFormat")]
+/// Framebuffer formats supported by the 3DS' screens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FramebufferFormat {
+    /// RGBA8. 4 bytes per pixel
+    Rgba8 = 0,
+    /// BGR8. 3 bytes per pixel
+    Bgr8 = 1,
+    /// RGB565. 2 bytes per pixel
+    Rgb565 = 2,
+    /// RGB5A1. 2 bytes per pixel
+    Rgb5A1 = 3,
+    /// RGBA4. 2 bytes per pixel
+    Rgba4 = 4,
+}
+
+impl FramebufferFormat {
+    /// Returns the number of bytes per pixel used by this FramebufferFormat
+    pub fn pixel_depth_bytes(&self) -> usize {
+        use self::FramebufferFormat::*;
+        match *self {
+            Rgba8 => 4,
+            Bgr8 => 3,
+            Rgb565 => 2,
+            Rgb5A1 => 2,
+            Rgba4 => 2,
+        }
+    }
+}
+
+/// Conversion from the raw `GSPGPU_FramebufferFormat` values of `libctru`.
+impl TryFrom<u32> for FramebufferFormat {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        use self::FramebufferFormat::*;
+        match value {
+            0 => Ok(Rgba8),
+            1 => Ok(Bgr8),
+            2 => Ok(Rgb565),
+            3 => Ok(Rgb5A1),
+            4 => Ok(Rgba4),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<FramebufferFormat> for u32 {
+    fn from(v: FramebufferFormat) -> Self {
+        v as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_framebuffer_formats() {
+        for raw in 0..5 {
+            let format = FramebufferFormat::try_from(raw).unwrap();
+            assert_eq!(u32::from(format), raw);
+        }
+
+        assert_eq!(FramebufferFormat::try_from(5), Err(()));
+    }
+}
@@ -0,0 +1,518 @@
+//! Typed pixels for every [`FramebufferFormat`].
+//!
+//! Each framebuffer format has a matching pixel type implementing [`Pixel`], which knows how the pixel is laid out in memory.
+//! All pixel types can be converted into each other with [`From`], going through [`Rgba8`] if needed,
+//! and whole buffers can be converted with [`convert()`] and [`convert_bytes()`].
+//!
+//! # Example
+//!
+//! ```
+//! use ctru_formats::gfx::pixel::{self, Bgr8, Rgb565};
+//! use ctru_formats::gfx::FramebufferFormat;
+//!
+//! assert_eq!(Bgr8::from(Rgb565::new(255, 0, 0)), Bgr8::new(255, 0, 0));
+//!
+//! // Convert an RGB565 image (e.g. taken by the camera) into the default framebuffer format.
+//! let image = vec![0xFF; 400 * 240 * 2];
+//!
+//! let mut converted = vec![0; 400 * 240 * 3];
+//! pixel::convert_bytes(&image, FramebufferFormat::Rgb565, &mut converted, FramebufferFormat::Bgr8);
+//! ```
+
+use crate::gfx::FramebufferFormat;
+use crate::sealed::Sealed;
+
+/// A pixel of one of the [`FramebufferFormat`]s.
+///
+/// This trait is sealed, and implemented by [`Rgba8`], [`Bgr8`], [`Rgb565`], [`Rgb5A1`] and [`Rgba4`].
+pub trait Pixel: Copy + From<Rgba8> + Into<Rgba8> + Sealed {
+    /// Framebuffer format made of this kind of pixels.
+    const FORMAT: FramebufferFormat;
+
+    /// Number of bytes used by a pixel.
+    const SIZE: usize;
+
+    /// Read a pixel from the first [`Pixel::SIZE`] bytes of `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `bytes` is shorter than [`Pixel::SIZE`].
+    fn from_bytes(bytes: &[u8]) -> Self;
+
+    /// Write the pixel to the first [`Pixel::SIZE`] bytes of `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `bytes` is shorter than [`Pixel::SIZE`].
+    fn write_bytes(self, bytes: &mut [u8]);
+}
+
+/// 32 bits RGBA pixel, used by [`FramebufferFormat::Rgba8`].
+///
+/// It's stored in memory as `[a, b, g, r]`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgba8 {
+    /// Red component.
+    pub r: u8,
+    /// Green component.
+    pub g: u8,
+    /// Blue component.
+    pub b: u8,
+    /// Alpha (opacity) component.
+    pub a: u8,
+}
+
+/// 24 bits RGB pixel, used by [`FramebufferFormat::Bgr8`].
+///
+/// It's stored in memory as `[b, g, r]`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Bgr8 {
+    /// Blue component.
+    pub b: u8,
+    /// Green component.
+    pub g: u8,
+    /// Red component.
+    pub r: u8,
+}
+
+/// 16 bits RGB pixel, with 5 bits of red, 6 bits of green and 5 bits of blue, used by [`FramebufferFormat::Rgb565`].
+///
+/// It's stored in memory as a little endian `u16`, with red in the most significant bits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb565(u16);
+
+/// 16 bits RGBA pixel, with 5 bits per color and 1 bit of alpha, used by [`FramebufferFormat::Rgb5A1`].
+///
+/// It's stored in memory as a little endian `u16`, with red in the most significant bits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgb5A1(u16);
+
+/// 16 bits RGBA pixel, with 4 bits per component, used by [`FramebufferFormat::Rgba4`].
+///
+/// It's stored in memory as a little endian `u16`, with red in the most significant bits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rgba4(u16);
+
+impl Rgba8 {
+    /// Create a new pixel.
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create a new opaque pixel.
+    pub const fn opaque(r: u8, g: u8, b: u8) -> Self {
+        Self::new(r, g, b, u8::MAX)
+    }
+}
+
+impl Bgr8 {
+    /// Create a new pixel.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { b, g, r }
+    }
+}
+
+impl Rgb565 {
+    /// Create a new pixel from 8 bits components, dropping their least significant bits.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self(((r as u16 >> 3) << 11) | ((g as u16 >> 2) << 5) | (b as u16 >> 3))
+    }
+
+    /// Create a pixel from its packed representation.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the packed representation of the pixel.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+impl Rgb5A1 {
+    /// Create a new pixel from 8 bits components, dropping their least significant bits.
+    ///
+    /// The pixel is opaque if `a` is at least 128.
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self(
+            ((r as u16 >> 3) << 11)
+                | ((g as u16 >> 3) << 6)
+                | ((b as u16 >> 3) << 1)
+                | (a as u16 >> 7),
+        )
+    }
+
+    /// Create a pixel from its packed representation.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the packed representation of the pixel.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+impl Rgba4 {
+    /// Create a new pixel from 8 bits components, dropping their least significant bits.
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self(
+            ((r as u16 >> 4) << 12)
+                | ((g as u16 >> 4) << 8)
+                | ((b as u16 >> 4) << 4)
+                | (a as u16 >> 4),
+        )
+    }
+
+    /// Create a pixel from its packed representation.
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the packed representation of the pixel.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+// Expand the lowest `bits` bits of `value` to an 8 bits component, by repeating them.
+// This maps 0 to 0 and the maximum value to 255.
+fn expand(value: u16, bits: u32) -> u8 {
+    let value = u32::from(value) & ((1 << bits) - 1);
+
+    let mut expanded = 0;
+    let mut filled = 0;
+    while filled < 8 {
+        expanded = (expanded << bits) | value;
+        filled += bits;
+    }
+
+    (expanded >> (filled - 8)) as u8
+}
+
+impl Pixel for Rgba8 {
+    const FORMAT: FramebufferFormat = FramebufferFormat::Rgba8;
+    const SIZE: usize = 4;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new(bytes[3], bytes[2], bytes[1], bytes[0])
+    }
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes[..4].copy_from_slice(&[self.a, self.b, self.g, self.r]);
+    }
+}
+
+impl Pixel for Bgr8 {
+    const FORMAT: FramebufferFormat = FramebufferFormat::Bgr8;
+    const SIZE: usize = 3;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self::new(bytes[2], bytes[1], bytes[0])
+    }
+
+    fn write_bytes(self, bytes: &mut [u8]) {
+        bytes[..3].copy_from_slice(&[self.b, self.g, self.r]);
+    }
+}
+
+macro_rules! packed_pixel {
+    ($pixel:ident) => {
+        impl Pixel for $pixel {
+            const FORMAT: FramebufferFormat = FramebufferFormat::$pixel;
+            const SIZE: usize = 2;
+
+            fn from_bytes(bytes: &[u8]) -> Self {
+                Self(u16::from_le_bytes([bytes[0], bytes[1]]))
+            }
+
+            fn write_bytes(self, bytes: &mut [u8]) {
+                bytes[..2].copy_from_slice(&self.0.to_le_bytes());
+            }
+        }
+    };
+}
+
+packed_pixel!(Rgb565);
+packed_pixel!(Rgb5A1);
+packed_pixel!(Rgba4);
+
+impl From<Bgr8> for Rgba8 {
+    fn from(pixel: Bgr8) -> Self {
+        Self::opaque(pixel.r, pixel.g, pixel.b)
+    }
+}
+
+impl From<Rgb565> for Rgba8 {
+    fn from(pixel: Rgb565) -> Self {
+        Self::opaque(
+            expand(pixel.0 >> 11, 5),
+            expand(pixel.0 >> 5, 6),
+            expand(pixel.0, 5),
+        )
+    }
+}
+
+impl From<Rgb5A1> for Rgba8 {
+    fn from(pixel: Rgb5A1) -> Self {
+        Self::new(
+            expand(pixel.0 >> 11, 5),
+            expand(pixel.0 >> 6, 5),
+            expand(pixel.0 >> 1, 5),
+            expand(pixel.0, 1),
+        )
+    }
+}
+
+impl From<Rgba4> for Rgba8 {
+    fn from(pixel: Rgba4) -> Self {
+        Self::new(
+            expand(pixel.0 >> 12, 4),
+            expand(pixel.0 >> 8, 4),
+            expand(pixel.0 >> 4, 4),
+            expand(pixel.0, 4),
+        )
+    }
+}
+
+impl From<Rgba8> for Bgr8 {
+    /// The alpha component is dropped.
+    fn from(pixel: Rgba8) -> Self {
+        Self::new(pixel.r, pixel.g, pixel.b)
+    }
+}
+
+impl From<Rgba8> for Rgb565 {
+    /// The alpha component is dropped.
+    fn from(pixel: Rgba8) -> Self {
+        Self::new(pixel.r, pixel.g, pixel.b)
+    }
+}
+
+impl From<Rgba8> for Rgb5A1 {
+    fn from(pixel: Rgba8) -> Self {
+        Self::new(pixel.r, pixel.g, pixel.b, pixel.a)
+    }
+}
+
+impl From<Rgba8> for Rgba4 {
+    fn from(pixel: Rgba8) -> Self {
+        Self::new(pixel.r, pixel.g, pixel.b, pixel.a)
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for Rgba8 {
+    fn from(color: embedded_graphics_core::pixelcolor::Rgb888) -> Self {
+        use embedded_graphics_core::pixelcolor::RgbColor;
+
+        Self::opaque(color.r(), color.g(), color.b())
+    }
+}
+
+#[cfg(feature = "embedded-graphics")]
+impl From<Rgba8> for embedded_graphics_core::pixelcolor::Rgb888 {
+    /// The alpha component is dropped.
+    fn from(pixel: Rgba8) -> Self {
+        Self::new(pixel.r, pixel.g, pixel.b)
+    }
+}
+
+// Conversions between the other formats go through `Rgba8`, which can represent every pixel exactly.
+macro_rules! from_via_rgba8 {
+    ($($from:ident => $($to:ident),+;)+) => {
+        $($(
+            impl From<$from> for $to {
+                fn from(pixel: $from) -> Self {
+                    Rgba8::from(pixel).into()
+                }
+            }
+        )+)+
+    };
+}
+
+from_via_rgba8! {
+    Bgr8 => Rgb565, Rgb5A1, Rgba4;
+    Rgb565 => Bgr8, Rgb5A1, Rgba4;
+    Rgb5A1 => Bgr8, Rgb565, Rgba4;
+    Rgba4 => Bgr8, Rgb565, Rgb5A1;
+}
+
+/// Convert a slice of pixels into another pixel format.
+///
+/// # Panics
+///
+/// This function will panic if `src` and `dst` don't have the same length.
+pub fn convert<S: Pixel, D: Pixel>(src: &[S], dst: &mut [D]) {
+    assert_eq!(
+        src.len(),
+        dst.len(),
+        "pixel slices must have the same length"
+    );
+
+    for (src, dst) in src.iter().zip(dst) {
+        *dst = D::from((*src).into());
+    }
+}
+
+/// Convert raw pixel data (e.g. a framebuffer, or a camera image) into another framebuffer format.
+///
+/// # Panics
+///
+/// This function will panic if `src` and `dst` don't hold the same number of pixels.
+pub fn convert_bytes(
+    src: &[u8],
+    src_format: FramebufferFormat,
+    dst: &mut [u8],
+    dst_format: FramebufferFormat,
+) {
+    let count = src.len() / src_format.pixel_depth_bytes();
+    assert!(
+        src.len() % src_format.pixel_depth_bytes() == 0
+            && dst.len() == count * dst_format.pixel_depth_bytes(),
+        "pixel buffers must hold the same number of pixels"
+    );
+
+    if src_format == dst_format {
+        dst.copy_from_slice(src);
+        return;
+    }
+
+    let convert = match src_format {
+        FramebufferFormat::Rgba8 => converter::<Rgba8>(dst_format),
+        FramebufferFormat::Bgr8 => converter::<Bgr8>(dst_format),
+        FramebufferFormat::Rgb565 => converter::<Rgb565>(dst_format),
+        FramebufferFormat::Rgb5A1 => converter::<Rgb5A1>(dst_format),
+        FramebufferFormat::Rgba4 => converter::<Rgba4>(dst_format),
+    };
+
+    convert(src, dst);
+}
+
+// Pick the (monomorphized) conversion from `S` into the given format.
+fn converter<S: Pixel>(dst_format: FramebufferFormat) -> fn(&[u8], &mut [u8]) {
+    match dst_format {
+        FramebufferFormat::Rgba8 => convert_bytes_as::<S, Rgba8>,
+        FramebufferFormat::Bgr8 => convert_bytes_as::<S, Bgr8>,
+        FramebufferFormat::Rgb565 => convert_bytes_as::<S, Rgb565>,
+        FramebufferFormat::Rgb5A1 => convert_bytes_as::<S, Rgb5A1>,
+        FramebufferFormat::Rgba4 => convert_bytes_as::<S, Rgba4>,
+    }
+}
+
+fn convert_bytes_as<S: Pixel, D: Pixel>(src: &[u8], dst: &mut [u8]) {
+    for (src, dst) in src.chunks_exact(S::SIZE).zip(dst.chunks_exact_mut(D::SIZE)) {
+        D::from(S::from_bytes(src).into()).write_bytes(dst);
+    }
+}
+
+// Read a pixel stored in the given format.
+pub(crate) fn read_pixel(format: FramebufferFormat, bytes: &[u8]) -> Rgba8 {
+    match format {
+        FramebufferFormat::Rgba8 => Rgba8::from_bytes(bytes),
+        FramebufferFormat::Bgr8 => Bgr8::from_bytes(bytes).into(),
+        FramebufferFormat::Rgb565 => Rgb565::from_bytes(bytes).into(),
+        FramebufferFormat::Rgb5A1 => Rgb5A1::from_bytes(bytes).into(),
+        FramebufferFormat::Rgba4 => Rgba4::from_bytes(bytes).into(),
+    }
+}
+
+// Write a pixel in the given format, skipping the conversion if it's already in that format.
+pub(crate) fn write_pixel<P: Pixel>(format: FramebufferFormat, bytes: &mut [u8], pixel: P) {
+    if P::FORMAT == format {
+        return pixel.write_bytes(bytes);
+    }
+
+    let pixel: Rgba8 = pixel.into();
+
+    match format {
+        FramebufferFormat::Rgba8 => pixel.write_bytes(bytes),
+        FramebufferFormat::Bgr8 => Bgr8::from(pixel).write_bytes(bytes),
+        FramebufferFormat::Rgb565 => Rgb565::from(pixel).write_bytes(bytes),
+        FramebufferFormat::Rgb5A1 => Rgb5A1::from(pixel).write_bytes(bytes),
+        FramebufferFormat::Rgba4 => Rgba4::from(pixel).write_bytes(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_layout() {
+        let mut bytes = [0; 4];
+
+        Rgba8::new(1, 2, 3, 4).write_bytes(&mut bytes);
+        assert_eq!(bytes, [4, 3, 2, 1]);
+
+        Bgr8::new(1, 2, 3).write_bytes(&mut bytes);
+        assert_eq!(bytes[..3], [3, 2, 1]);
+
+        Rgb565::new(255, 0, 0).write_bytes(&mut bytes);
+        assert_eq!(bytes[..2], [0x00, 0xF8]);
+        assert_eq!(Rgb565::from_bytes(&[0x1F, 0x00]), Rgb565::new(0, 0, 255));
+
+        assert_eq!(Rgb5A1::new(0, 0, 0, 255).to_bits(), 0x0001);
+        assert_eq!(Rgba4::new(0x10, 0x20, 0x30, 0x40).to_bits(), 0x1234);
+    }
+
+    #[test]
+    fn expansion() {
+        assert_eq!(
+            Rgba8::from(Rgb565::from_bits(0xFFFF)),
+            Rgba8::opaque(255, 255, 255)
+        );
+        assert_eq!(
+            Rgba8::from(Rgb5A1::from_bits(0xFFFE)),
+            Rgba8::new(255, 255, 255, 0)
+        );
+        assert_eq!(
+            Rgba8::from(Rgba4::from_bits(0x8F0F)),
+            Rgba8::new(0x88, 0xFF, 0, 0xFF)
+        );
+        assert_eq!(
+            Bgr8::from(Rgb565::new(0x84, 0x82, 0x84)),
+            Bgr8::new(0x84, 0x82, 0x84)
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        for bits in 0..=u16::MAX {
+            let pixel = Rgb565::from_bits(bits);
+            assert_eq!(Rgb565::from(Bgr8::from(pixel)), pixel);
+
+            let pixel = Rgb5A1::from_bits(bits);
+            assert_eq!(Rgb5A1::from(Rgba8::from(pixel)), pixel);
+
+            let pixel = Rgba4::from_bits(bits);
+            assert_eq!(Rgba4::from(Rgba8::from(pixel)), pixel);
+        }
+    }
+
+    #[test]
+    fn bulk_conversion() {
+        let src = [Rgb565::new(255, 0, 0), Rgb565::new(0, 0, 255)];
+        let mut dst = [Bgr8::default(); 2];
+        convert(&src, &mut dst);
+        assert_eq!(dst, [Bgr8::new(255, 0, 0), Bgr8::new(0, 0, 255)]);
+
+        let mut bytes = [0; 6];
+        convert_bytes(
+            &[0x00, 0xF8, 0x1F, 0x00],
+            FramebufferFormat::Rgb565,
+            &mut bytes,
+            FramebufferFormat::Bgr8,
+        );
+        assert_eq!(bytes, [0, 0, 0xFF, 0xFF, 0, 0]);
+
+        let mut rgba = [0; 8];
+        convert_bytes(
+            &bytes,
+            FramebufferFormat::Bgr8,
+            &mut rgba,
+            FramebufferFormat::Rgba8,
+        );
+        assert_eq!(rgba, [0xFF, 0, 0, 0xFF, 0xFF, 0xFF, 0, 0]);
+    }
+}
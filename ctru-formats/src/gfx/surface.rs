@@ -0,0 +1,484 @@
+//! Safe, rotation-aware access to the pixels of a framebuffer.
+//!
+//! The LCD screens of the console are mounted sideways, so their framebuffers store the image rotated by 90 degrees:
+//! pixels are stored column by column (from the left of the screen to its right), and each column goes from the bottom of the screen to its top.
+//! A [`Surface`] hides this layout, and exposes the pixels in the usual orientation, with (0, 0) at the top-left corner.
+//!
+//! With `ctru-rs`, the surface of a screen's framebuffer is borrowed with `Screen::surface()`.
+//!
+//! # Example
+//!
+//! ```
+//! use ctru_formats::gfx::pixel::Rgba8;
+//! use ctru_formats::gfx::surface::Surface;
+//! use ctru_formats::gfx::FramebufferFormat;
+//!
+//! // A buffer laid out like the framebuffer of the bottom screen.
+//! let format = FramebufferFormat::Bgr8;
+//! let mut buffer = vec![0; Surface::buffer_len(320, 240, format)];
+//! let mut surface = Surface::new(&mut buffer, 320, 240, format);
+//!
+//! surface.fill(Rgba8::opaque(0, 0, 0));
+//!
+//! // Draw a red horizontal line at the top of the screen.
+//! for x in 0..surface.width() {
+//!     surface.set_pixel(x, 0, Rgba8::opaque(255, 0, 0));
+//! }
+//!
+//! // The first pixel of the buffer is at the bottom-left corner of the screen.
+//! assert_eq!(surface.pixel(0, 239), Some(Rgba8::opaque(0, 0, 0)));
+//! assert_eq!(&buffer[..3], &[0, 0, 0]);
+//! ```
+
+use std::marker::PhantomData;
+
+use super::pixel::{self, Pixel, Rgba8};
+use crate::gfx::FramebufferFormat;
+
+/// A mutable view of an image stored in the framebuffer layout.
+///
+/// Surfaces can be borrowed from a screen with `Screen::surface()` in `ctru-rs`,
+/// or created over any buffer with [`Surface::new()`] (e.g. to prepare an image before copying it to the screen).
+///
+/// All coordinates are logical: `x` goes from the left to the right of the screen, and `y` from its top to its bottom.
+#[derive(Debug)]
+pub struct Surface<'a> {
+    data: &'a mut [u8],
+    width: usize,
+    height: usize,
+    format: FramebufferFormat,
+}
+
+impl<'a> Surface<'a> {
+    /// Create a surface over a buffer holding an image of the given size, stored in the framebuffer layout.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `data` is shorter than [`Surface::buffer_len()`], or if that length overflows a `usize`.
+    pub fn new(data: &'a mut [u8], width: usize, height: usize, format: FramebufferFormat) -> Self {
+        let len = Self::buffer_len(width, height, format);
+        assert!(
+            data.len() >= len,
+            "surface buffer must hold at least {len} bytes"
+        );
+
+        Self {
+            data: &mut data[..len],
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// Returns the number of bytes needed to store an image of the given size and format.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of bytes overflows a `usize`.
+    pub fn buffer_len(width: usize, height: usize, format: FramebufferFormat) -> usize {
+        width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(format.pixel_depth_bytes()))
+            .expect("surface size overflows a usize")
+    }
+
+    /// Returns the width of the surface in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the surface in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the format of the pixels of the surface.
+    pub fn format(&self) -> FramebufferFormat {
+        self.format
+    }
+
+    /// Returns the pixel at (`x`, `y`), or `None` if it's outside of the surface.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgba8> {
+        let offset = self.offset(x, y)?;
+
+        Some(pixel::read_pixel(self.format, &self.data[offset..]))
+    }
+
+    /// Set the pixel at (`x`, `y`), converting it to the format of the surface.
+    ///
+    /// Returns `false` (and does nothing) if the pixel is outside of the surface.
+    pub fn set_pixel<P: Pixel>(&mut self, x: usize, y: usize, pixel: P) -> bool {
+        match self.offset(x, y) {
+            Some(offset) => {
+                pixel::write_pixel(self.format, &mut self.data[offset..], pixel);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set every pixel of the surface.
+    pub fn fill<P: Pixel>(&mut self, pixel: P) {
+        let bytes_per_pixel = self.format.pixel_depth_bytes();
+
+        let mut encoded = [0; 4];
+        pixel::write_pixel(self.format, &mut encoded, pixel);
+
+        for chunk in self.data.chunks_exact_mut(bytes_per_pixel) {
+            chunk.copy_from_slice(&encoded[..bytes_per_pixel]);
+        }
+    }
+
+    /// Returns the pixels of the row `y`, from left to right, or `None` if it's outside of the surface.
+    pub fn row(&self, y: usize) -> Option<Line<'_>> {
+        (y < self.height).then(|| {
+            let (start, stride) = row_layout(self.height, self.format, y);
+            Line::new(self.data, self.format, start, stride, self.width)
+        })
+    }
+
+    /// Returns the pixels of the column `x`, from top to bottom, or `None` if it's outside of the surface.
+    pub fn column(&self, x: usize) -> Option<Line<'_>> {
+        (x < self.width).then(|| {
+            let (start, stride) = column_layout(self.height, self.format, x);
+            Line::new(self.data, self.format, start, stride, self.height)
+        })
+    }
+
+    /// Returns an iterator over the rows of the surface, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = Line<'_>> + '_ {
+        (0..self.height).filter_map(|y| self.row(y))
+    }
+
+    /// Returns an iterator over the columns of the surface, from left to right.
+    pub fn columns(&self) -> impl Iterator<Item = Line<'_>> + '_ {
+        (0..self.width).filter_map(|x| self.column(x))
+    }
+
+    /// Returns an iterator over the mutable rows of the surface, from top to bottom.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = LineMut<'_>> + '_ {
+        let (width, height, format) = (self.width, self.height, self.format);
+        let ptr = self.data.as_mut_ptr();
+
+        // SAFETY: the rows are made of disjoint pixels of `data`, which stays borrowed as long as the lines live.
+        (0..height).map(move |y| {
+            let (start, stride) = row_layout(height, format, y);
+            unsafe { LineMut::new(ptr, format, start, stride, width) }
+        })
+    }
+
+    /// Returns an iterator over the mutable columns of the surface, from left to right.
+    pub fn columns_mut(&mut self) -> impl Iterator<Item = LineMut<'_>> + '_ {
+        let (width, height, format) = (self.width, self.height, self.format);
+        let ptr = self.data.as_mut_ptr();
+
+        // SAFETY: the columns are made of disjoint pixels of `data`, which stays borrowed as long as the lines live.
+        (0..width).map(move |x| {
+            let (start, stride) = column_layout(height, format, x);
+            unsafe { LineMut::new(ptr, format, start, stride, height) }
+        })
+    }
+
+    /// Returns the raw data of the surface, in the framebuffer layout.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the mutable raw data of the surface, in the framebuffer layout.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    // Offset of the pixel at (x, y) in `data`.
+    fn offset(&self, x: usize, y: usize) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some((x * self.height + (self.height - 1 - y)) * self.format.pixel_depth_bytes())
+    }
+}
+
+// Offset of the first pixel of a row, and the distance (in bytes) between its pixels.
+fn row_layout(height: usize, format: FramebufferFormat, y: usize) -> (usize, isize) {
+    let bytes_per_pixel = format.pixel_depth_bytes();
+
+    (
+        (height - 1 - y) * bytes_per_pixel,
+        (height * bytes_per_pixel) as isize,
+    )
+}
+
+// Offset of the first pixel of a column, and the distance (in bytes) between its pixels.
+fn column_layout(height: usize, format: FramebufferFormat, x: usize) -> (usize, isize) {
+    let bytes_per_pixel = format.pixel_depth_bytes();
+    // The columns of a surface without rows are empty, so their start doesn't matter.
+    let bottom = (x * height + height).saturating_sub(1);
+
+    (bottom * bytes_per_pixel, -(bytes_per_pixel as isize))
+}
+
+/// Iterator over the pixels of a row or a column of a [`Surface`].
+#[derive(Clone, Debug)]
+pub struct Line<'a> {
+    data: &'a [u8],
+    format: FramebufferFormat,
+    start: usize,
+    stride: isize,
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(
+        data: &'a [u8],
+        format: FramebufferFormat,
+        start: usize,
+        stride: isize,
+        len: usize,
+    ) -> Self {
+        Self {
+            data,
+            format,
+            start,
+            stride,
+            front: 0,
+            back: len,
+        }
+    }
+
+    fn get(&self, index: usize) -> Rgba8 {
+        let offset = self.start.wrapping_add_signed(self.stride * index as isize);
+
+        pixel::read_pixel(self.format, &self.data[offset..])
+    }
+}
+
+impl Iterator for Line<'_> {
+    type Item = Rgba8;
+
+    fn next(&mut self) -> Option<Rgba8> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        Some(self.get(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Line<'_> {
+    fn next_back(&mut self) -> Option<Rgba8> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        Some(self.get(self.back))
+    }
+}
+
+impl ExactSizeIterator for Line<'_> {}
+
+/// A mutable row or column of a [`Surface`].
+#[derive(Debug)]
+pub struct LineMut<'a> {
+    ptr: *mut u8,
+    format: FramebufferFormat,
+    stride: isize,
+    len: usize,
+    _data: PhantomData<&'a mut [u8]>,
+}
+
+impl LineMut<'_> {
+    // SAFETY: the `len` pixels starting at `ptr + start`, `stride` bytes apart, must be valid for writes
+    // for the lifetime of the line, and must not be accessed through anything else.
+    // Empty lines never offset `ptr`, since it may dangle (e.g. for the rows of a surface without columns).
+    unsafe fn new(
+        ptr: *mut u8,
+        format: FramebufferFormat,
+        start: usize,
+        stride: isize,
+        len: usize,
+    ) -> Self {
+        Self {
+            ptr: if len == 0 {
+                ptr
+            } else {
+                unsafe { ptr.add(start) }
+            },
+            format,
+            stride,
+            len,
+            _data: PhantomData,
+        }
+    }
+
+    /// Returns the number of pixels of the line.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the line has no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the pixel at `index` (from the left or the top), or `None` if it's outside of the line.
+    pub fn pixel(&self, index: usize) -> Option<Rgba8> {
+        let bytes = self.bytes(index)?;
+
+        Some(pixel::read_pixel(self.format, bytes))
+    }
+
+    /// Set the pixel at `index` (from the left or the top), converting it to the format of the surface.
+    ///
+    /// Returns `false` (and does nothing) if the pixel is outside of the line.
+    pub fn set_pixel<P: Pixel>(&mut self, index: usize, pixel: P) -> bool {
+        let format = self.format;
+
+        match self.bytes_mut(index) {
+            Some(bytes) => {
+                pixel::write_pixel(format, bytes, pixel);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set every pixel of the line.
+    pub fn fill<P: Pixel>(&mut self, pixel: P) {
+        for index in 0..self.len {
+            self.set_pixel(index, pixel);
+        }
+    }
+
+    fn bytes(&self, index: usize) -> Option<&[u8]> {
+        if index >= self.len {
+            return None;
+        }
+
+        // SAFETY: the pixel is part of the line.
+        Some(unsafe {
+            std::slice::from_raw_parts(
+                self.ptr.offset(self.stride * index as isize),
+                self.format.pixel_depth_bytes(),
+            )
+        })
+    }
+
+    fn bytes_mut(&mut self, index: usize) -> Option<&mut [u8]> {
+        if index >= self.len {
+            return None;
+        }
+
+        // SAFETY: the pixel is part of the line, which is borrowed mutably.
+        Some(unsafe {
+            std::slice::from_raw_parts_mut(
+                self.ptr.offset(self.stride * index as isize),
+                self.format.pixel_depth_bytes(),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gfx::pixel::Bgr8;
+
+    const RED: Rgba8 = Rgba8::opaque(255, 0, 0);
+    const BLUE: Rgba8 = Rgba8::opaque(0, 0, 255);
+
+    #[test]
+    fn rotated_layout() {
+        let mut data = vec![0; Surface::buffer_len(3, 2, FramebufferFormat::Bgr8)];
+        let mut surface = Surface::new(&mut data, 3, 2, FramebufferFormat::Bgr8);
+
+        // The top-left pixel is at the end of the first column.
+        assert!(surface.set_pixel(0, 0, Bgr8::new(1, 2, 3)));
+        assert_eq!(surface.as_bytes()[3..6], [3, 2, 1]);
+
+        // The bottom-right pixel is at the start of the last column.
+        assert!(surface.set_pixel(2, 1, Bgr8::new(4, 5, 6)));
+        assert_eq!(surface.as_bytes()[12..15], [6, 5, 4]);
+
+        assert!(!surface.set_pixel(3, 0, RED));
+        assert!(!surface.set_pixel(0, 2, RED));
+        assert_eq!(surface.pixel(0, 2), None);
+        assert_eq!(surface.pixel(0, 0), Some(Rgba8::opaque(1, 2, 3)));
+    }
+
+    #[test]
+    fn lines() {
+        let mut data = vec![0; Surface::buffer_len(4, 3, FramebufferFormat::Rgb565)];
+        let mut surface = Surface::new(&mut data, 4, 3, FramebufferFormat::Rgb565);
+
+        surface.fill(BLUE);
+        surface.set_pixel(1, 0, RED);
+        surface.set_pixel(3, 2, RED);
+
+        let row: Vec<_> = surface.row(0).unwrap().collect();
+        assert_eq!(row, [BLUE, RED, BLUE, BLUE]);
+
+        let column: Vec<_> = surface.column(3).unwrap().rev().collect();
+        assert_eq!(column, [RED, BLUE, BLUE]);
+
+        assert_eq!(surface.rows().count(), 3);
+        assert_eq!(
+            surface.columns().map(|column| column.len()).sum::<usize>(),
+            12
+        );
+        assert!(surface.row(3).is_none());
+
+        for (y, mut row) in surface.rows_mut().enumerate() {
+            row.set_pixel(y, RED);
+        }
+        for mut column in surface.columns_mut().skip(3) {
+            column.fill(RED);
+            assert!(!column.set_pixel(3, BLUE));
+        }
+
+        let red: Vec<(usize, usize)> = (0..3)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| surface.pixel(x, y) == Some(RED))
+            .collect();
+        assert_eq!(
+            red,
+            [(0, 0), (1, 0), (3, 0), (1, 1), (3, 1), (2, 2), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        for (width, height) in [(3, 0), (0, 3), (0, 0)] {
+            let mut surface = Surface::new(&mut [], width, height, FramebufferFormat::Bgr8);
+
+            assert_eq!(
+                surface.column(0).map(|column| column.count()),
+                (width > 0).then_some(0)
+            );
+            assert_eq!(
+                surface.row(0).map(|row| row.count()),
+                (height > 0).then_some(0)
+            );
+            assert_eq!(surface.rows().flatten().count(), 0);
+            assert_eq!(surface.columns().flatten().count(), 0);
+
+            surface.fill(RED);
+            for mut column in surface.columns_mut() {
+                column.fill(RED);
+            }
+            for mut row in surface.rows_mut() {
+                row.fill(RED);
+            }
+            assert_eq!(surface.pixel(0, 0), None);
+        }
+    }
+}
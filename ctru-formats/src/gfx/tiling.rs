@@ -0,0 +1,312 @@
+//! Conversion between linear and tiled image layouts.
+//!
+//! The GPU (for textures and render targets) and the [SMDH](crate::smdh) icons don't store images row by row.
+//! Instead, images are split into 8x8 tiles, stored left to right and top to bottom, and the pixels of each tile
+//! are stored in Morton (Z) order:
+//!
+//! ```text
+//!  0  1  4  5 16 17 20 21
+//!  2  3  6  7 18 19 22 23
+//!  8  9 12 13 24 25 28 29
+//! 10 11 14 15 26 27 30 31
+//! 32 33 36 37 48 49 52 53
+//! 34 35 38 39 50 51 54 55
+//! 40 41 44 45 56 57 60 61
+//! 42 43 46 47 58 59 62 63
+//! ```
+//!
+//! The functions of this module convert images between the two layouts without changing their orientation.
+//!
+//! # Example
+//!
+//! ```
+//! use ctru_formats::gfx::tiling::{self, PixelSize};
+//!
+//! // A 16x8 RGB565 image.
+//! let linear: Vec<u8> = (0..16 * 8 * 2).map(|i| i as u8).collect();
+//!
+//! let mut tiled = vec![0; linear.len()];
+//! tiling::linear_to_tiled(&linear, &mut tiled, 16, 8, PixelSize::Bits16);
+//!
+//! let mut untiled = vec![0; linear.len()];
+//! tiling::tiled_to_linear(&tiled, &mut untiled, 16, 8, PixelSize::Bits16);
+//!
+//! assert_eq!(linear, untiled);
+//! ```
+
+use crate::gfx::FramebufferFormat;
+
+/// Width and height of a tile, in pixels.
+pub const TILE_SIZE: usize = 8;
+
+// Morton index of the first pixel of each pair of horizontally adjacent pixels of a tile, in linear order.
+//
+// Both pixels of a pair are always next to each other in the tiled layout, so they can be copied at once.
+const PAIR_OFFSETS: [usize; TILE_SIZE * TILE_SIZE / 2] = {
+    let mut offsets = [0; TILE_SIZE * TILE_SIZE / 2];
+    let mut i = 0;
+
+    while i < offsets.len() {
+        offsets[i] = morton_index((i % 4) * 2, i / 4);
+        i += 1;
+    }
+
+    offsets
+};
+
+/// Size of the pixels (or texels) of an image.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PixelSize {
+    /// 4 bits per pixel (e.g. L4 and A4 textures).
+    ///
+    /// Two pixels are packed in each byte, with the leftmost one (the even X coordinate) in the lower nibble.
+    Bits4,
+    /// 8 bits per pixel (e.g. L8 and A8 textures).
+    Bits8,
+    /// 16 bits per pixel (e.g. [`FramebufferFormat::Rgb565`], [`FramebufferFormat::Rgb5A1`] and [`FramebufferFormat::Rgba4`]).
+    Bits16,
+    /// 24 bits per pixel (e.g. [`FramebufferFormat::Bgr8`]).
+    Bits24,
+    /// 32 bits per pixel (e.g. [`FramebufferFormat::Rgba8`]).
+    Bits32,
+}
+
+impl PixelSize {
+    /// Returns the number of bits per pixel.
+    pub fn bits(self) -> usize {
+        match self {
+            Self::Bits4 => 4,
+            Self::Bits8 => 8,
+            Self::Bits16 => 16,
+            Self::Bits24 => 24,
+            Self::Bits32 => 32,
+        }
+    }
+
+    /// Returns the number of bytes used by an image of the given size.
+    pub fn image_len(self, width: usize, height: usize) -> usize {
+        width * height * self.bits() / 8
+    }
+}
+
+impl From<FramebufferFormat> for PixelSize {
+    fn from(format: FramebufferFormat) -> Self {
+        match format.pixel_depth_bytes() {
+            2 => Self::Bits16,
+            3 => Self::Bits24,
+            _ => Self::Bits32,
+        }
+    }
+}
+
+/// Returns the index of the pixel at (`x`, `y`) within an 8x8 tile.
+///
+/// # Panics
+///
+/// This function will panic if `x` or `y` is outside of the tile.
+pub const fn morton_index(x: usize, y: usize) -> usize {
+    assert!(x < TILE_SIZE && y < TILE_SIZE);
+
+    (x & 1) | ((y & 1) << 1) | ((x & 2) << 1) | ((y & 2) << 2) | ((x & 4) << 2) | ((y & 4) << 3)
+}
+
+/// Returns the index of the pixel at (`x`, `y`) within a tiled image of the given width.
+///
+/// `width` must be a multiple of [`TILE_SIZE`].
+pub fn tiled_index(x: usize, y: usize, width: usize) -> usize {
+    let tile = (y / TILE_SIZE) * (width / TILE_SIZE) + x / TILE_SIZE;
+
+    tile * TILE_SIZE * TILE_SIZE + morton_index(x % TILE_SIZE, y % TILE_SIZE)
+}
+
+/// Convert an image stored row by row into the tiled layout.
+///
+/// # Panics
+///
+/// This function will panic if `width` or `height` isn't a multiple of [`TILE_SIZE`],
+/// or if `linear` or `tiled` is shorter than [`PixelSize::image_len()`].
+pub fn linear_to_tiled(
+    linear: &[u8],
+    tiled: &mut [u8],
+    width: usize,
+    height: usize,
+    pixel_size: PixelSize,
+) {
+    swizzle::<true>(linear, tiled, width, height, pixel_size);
+}
+
+/// Convert a tiled image into an image stored row by row.
+///
+/// # Panics
+///
+/// This function will panic if `width` or `height` isn't a multiple of [`TILE_SIZE`],
+/// or if `tiled` or `linear` is shorter than [`PixelSize::image_len()`].
+pub fn tiled_to_linear(
+    tiled: &[u8],
+    linear: &mut [u8],
+    width: usize,
+    height: usize,
+    pixel_size: PixelSize,
+) {
+    swizzle::<false>(tiled, linear, width, height, pixel_size);
+}
+
+fn swizzle<const TO_TILED: bool>(
+    src: &[u8],
+    dst: &mut [u8],
+    width: usize,
+    height: usize,
+    pixel_size: PixelSize,
+) {
+    assert!(
+        width % TILE_SIZE == 0 && height % TILE_SIZE == 0,
+        "image size ({width}x{height}) must be a multiple of the tile size"
+    );
+
+    let len = pixel_size.image_len(width, height);
+    assert!(
+        src.len() >= len && dst.len() >= len,
+        "buffers must hold at least {len} bytes"
+    );
+
+    // Monomorphize the copy for each pair size, so that it compiles down to plain loads and stores.
+    match pixel_size {
+        PixelSize::Bits4 => swizzle_pairs::<1, TO_TILED>(src, dst, width, height),
+        PixelSize::Bits8 => swizzle_pairs::<2, TO_TILED>(src, dst, width, height),
+        PixelSize::Bits16 => swizzle_pairs::<4, TO_TILED>(src, dst, width, height),
+        PixelSize::Bits24 => swizzle_pairs::<6, TO_TILED>(src, dst, width, height),
+        PixelSize::Bits32 => swizzle_pairs::<8, TO_TILED>(src, dst, width, height),
+    }
+}
+
+// Copy the image one pair of horizontally adjacent pixels (`PAIR` bytes) at a time.
+fn swizzle_pairs<const PAIR: usize, const TO_TILED: bool>(
+    src: &[u8],
+    dst: &mut [u8],
+    width: usize,
+    height: usize,
+) {
+    let pairs_per_row = width / 2;
+    let tiles_per_row = width / TILE_SIZE;
+
+    for tile_y in 0..height / TILE_SIZE {
+        for tile_x in 0..tiles_per_row {
+            let tile_start = (tile_y * tiles_per_row + tile_x) * TILE_SIZE * TILE_SIZE / 2;
+            let linear_start = tile_y * TILE_SIZE * pairs_per_row + tile_x * TILE_SIZE / 2;
+
+            for (i, &offset) in PAIR_OFFSETS.iter().enumerate() {
+                let linear = (linear_start + (i / 4) * pairs_per_row + i % 4) * PAIR;
+                let tiled = (tile_start + offset / 2) * PAIR;
+
+                let (from, to) = if TO_TILED {
+                    (linear, tiled)
+                } else {
+                    (tiled, linear)
+                };
+
+                dst[to..to + PAIR].copy_from_slice(&src[from..from + PAIR]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [PixelSize; 5] = [
+        PixelSize::Bits4,
+        PixelSize::Bits8,
+        PixelSize::Bits16,
+        PixelSize::Bits24,
+        PixelSize::Bits32,
+    ];
+
+    // Read the pixel at the given index, as a sequence of nibbles.
+    fn get(data: &[u8], index: usize, pixel_size: PixelSize) -> Vec<u8> {
+        let nibbles = pixel_size.bits() / 4;
+
+        (index * nibbles..(index + 1) * nibbles)
+            .map(|nibble| (data[nibble / 2] >> (4 * (nibble % 2))) & 0xF)
+            .collect()
+    }
+
+    fn set(data: &mut [u8], index: usize, pixel_size: PixelSize, pixel: &[u8]) {
+        let nibbles = pixel_size.bits() / 4;
+
+        for (nibble, value) in (index * nibbles..(index + 1) * nibbles).zip(pixel) {
+            let shift = 4 * (nibble % 2);
+            data[nibble / 2] = (data[nibble / 2] & !(0xF << shift)) | (value << shift);
+        }
+    }
+
+    // Straightforward pixel-by-pixel conversion.
+    fn reference(linear: &[u8], width: usize, height: usize, pixel_size: PixelSize) -> Vec<u8> {
+        let mut tiled = vec![0; linear.len()];
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = get(linear, y * width + x, pixel_size);
+                set(&mut tiled, tiled_index(x, y, width), pixel_size, &pixel);
+            }
+        }
+
+        tiled
+    }
+
+    // Deterministic pseudo-random data.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x1234_5678u32;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn morton_order() {
+        let order: Vec<usize> = (0..8).map(|x| morton_index(x, 0)).collect();
+        assert_eq!(order, [0, 1, 4, 5, 16, 17, 20, 21]);
+
+        let order: Vec<usize> = (0..8).map(|y| morton_index(0, y)).collect();
+        assert_eq!(order, [0, 2, 8, 10, 32, 34, 40, 42]);
+
+        assert_eq!(morton_index(7, 7), 63);
+        assert_eq!(tiled_index(8, 0, 16), 64);
+        assert_eq!(tiled_index(0, 8, 16), 128);
+    }
+
+    #[test]
+    fn matches_reference() {
+        for pixel_size in SIZES {
+            for (width, height) in [(8, 8), (16, 8), (8, 24), (48, 32)] {
+                let linear = noise(pixel_size.image_len(width, height));
+                let expected = reference(&linear, width, height, pixel_size);
+
+                let mut tiled = vec![0; linear.len()];
+                linear_to_tiled(&linear, &mut tiled, width, height, pixel_size);
+                assert_eq!(tiled, expected, "{pixel_size:?} {width}x{height}");
+
+                let mut untiled = vec![0; linear.len()];
+                tiled_to_linear(&tiled, &mut untiled, width, height, pixel_size);
+                assert_eq!(untiled, linear, "{pixel_size:?} {width}x{height}");
+            }
+        }
+    }
+
+    #[test]
+    fn framebuffer_formats() {
+        assert_eq!(PixelSize::from(FramebufferFormat::Rgba8), PixelSize::Bits32);
+        assert_eq!(PixelSize::from(FramebufferFormat::Bgr8), PixelSize::Bits24);
+        assert_eq!(PixelSize::from(FramebufferFormat::Rgba4), PixelSize::Bits16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unaligned_size() {
+        linear_to_tiled(&[0; 12 * 8], &mut [0; 12 * 8], 12, 8, PixelSize::Bits8);
+    }
+}
@@ -0,0 +1,41 @@
+//! Platform-independent file formats and framebuffer graphics of the Nintendo 3DS.
+//!
+//! # About
+//!
+//! This crate holds the parts of [`ctru-rs`](https://rust3ds.github.io/ctru-rs/crates/ctru) which don't interact with the console's
+//! operating system: parsers and writers for the file formats used by the console, and the pixel formats and layouts of its framebuffers.
+//! Since it doesn't depend on `libctru`, it builds for any target, so it can be used in build scripts and in tools running on the host machine
+//! (e.g. to create a RomFS image, or to check the output of a build).
+//!
+//! When developing for the console, use these modules through the re-exports of `ctru-rs` instead (e.g. `ctru::formats::romfs`).
+//!
+//! # Features
+//!
+//! - `build`: generate a [manifest](romfs::manifest) of the RomFS directory from the build script of an application.
+//! - `embedded-graphics`: implement conversions between the [`pixel`](gfx::pixel) types and the [`embedded-graphics`](https://docs.rs/embedded-graphics) colors.
+//! - `image`: decode BMP and PNG images into the framebuffer formats, in the `gfx::image` module.
+
+#![warn(missing_docs)]
+#![doc(
+    html_favicon_url = "https://user-images.githubusercontent.com/11131775/225929072-2fa1741c-93ae-4b47-9bdf-af70f3d59910.png"
+)]
+#![doc(
+    html_logo_url = "https://user-images.githubusercontent.com/11131775/225929072-2fa1741c-93ae-4b47-9bdf-af70f3d59910.png"
+)]
+#![doc(html_root_url = "https://rust3ds.github.io/ctru-rs/crates")]
+
+mod bytes;
+pub mod cfg;
+pub mod cfnt;
+pub mod checksum;
+pub mod cia;
+pub mod exefs;
+pub mod fs_path;
+pub mod gfx;
+pub mod ncch;
+pub mod romfs;
+mod sealed;
+mod sha256;
+pub mod smdh;
+pub mod threedsx;
+pub mod title_id;
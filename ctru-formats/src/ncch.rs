@@ -0,0 +1,430 @@
+//! NCCH container parsing.
+//!
+//! NCCH containers are the contents of installed titles and game cards (usually the first content of a [CIA](crate::cia) file).
+//! They hold the extended header, the [ExeFS](crate::exefs) and the [RomFS](crate::romfs) of an application.
+//!
+//! Only unencrypted containers (see [`NcchHeader::is_encrypted()`]) can have their sections opened and verified.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::cia::Cia;
+//! use ctru_formats::ncch::Ncch;
+//!
+//! let mut cia = Cia::new(std::fs::File::open("app.cia")?)?;
+//! let mut ncch = Ncch::new(cia.open_content(0)?)?;
+//!
+//! println!("{} ({})", ncch.header().product_code(), ncch.header().program_id());
+//!
+//! if !ncch.header().is_encrypted() {
+//!     assert!(ncch.verify_exefs()?);
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::exefs::ExeFs;
+use super::romfs::RomFsImage;
+use super::sha256::Sha256;
+use crate::bytes::{read_string, read_u32, read_u64};
+use crate::title_id::TitleId;
+
+/// Size of the NCCH header.
+pub const HEADER_LEN: usize = 0x200;
+
+/// Size of the media units in which NCCH offsets and sizes are expressed.
+pub const MEDIA_UNIT: u64 = 0x200;
+
+const MAGIC: [u8; 4] = *b"NCCH";
+
+// Size of the hashed part of the extended header.
+const EXTENDED_HEADER_LEN: u64 = 0x400;
+
+/// Location of a section of an NCCH container, in bytes from the start of the container.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Section {
+    /// Offset of the section.
+    pub offset: u64,
+    /// Size of the section.
+    pub size: u64,
+}
+
+/// Header of an NCCH container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NcchHeader {
+    content_size: u64,
+    partition_id: u64,
+    maker_code: String,
+    version: u16,
+    program_id: TitleId,
+    logo_hash: [u8; 32],
+    product_code: String,
+    extended_header_hash: [u8; 32],
+    extended_header_size: u32,
+    flags: [u8; 8],
+    plain_region: Section,
+    logo: Section,
+    exefs: Section,
+    exefs_hash_region_size: u64,
+    romfs: Section,
+    romfs_hash_region_size: u64,
+    exefs_superblock_hash: [u8; 32],
+    romfs_superblock_hash: [u8; 32],
+}
+
+impl NcchHeader {
+    /// Parse an NCCH header.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if `data` isn't a valid NCCH header.
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        if data.len() < HEADER_LEN || data[0x100..0x104] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an NCCH container",
+            ));
+        }
+
+        let media = |offset: usize| u64::from(read_u32(data, offset)) * MEDIA_UNIT;
+        let section = |offset: usize| Section {
+            offset: media(offset),
+            size: media(offset + 4),
+        };
+
+        Ok(Self {
+            content_size: media(0x104),
+            partition_id: read_u64(data, 0x108),
+            maker_code: read_string(&data[0x110..0x112]),
+            version: u16::from_le_bytes([data[0x112], data[0x113]]),
+            program_id: TitleId::new(read_u64(data, 0x118)),
+            logo_hash: data[0x130..0x150].try_into().unwrap(),
+            product_code: read_string(&data[0x150..0x160]),
+            extended_header_hash: data[0x160..0x180].try_into().unwrap(),
+            extended_header_size: read_u32(data, 0x180),
+            flags: data[0x188..0x190].try_into().unwrap(),
+            plain_region: section(0x190),
+            logo: section(0x198),
+            exefs: section(0x1A0),
+            exefs_hash_region_size: media(0x1A8),
+            romfs: section(0x1B0),
+            romfs_hash_region_size: media(0x1B8),
+            exefs_superblock_hash: data[0x1C0..0x1E0].try_into().unwrap(),
+            romfs_superblock_hash: data[0x1E0..0x200].try_into().unwrap(),
+        })
+    }
+
+    /// Returns the size of the whole container in bytes.
+    pub fn content_size(&self) -> u64 {
+        self.content_size
+    }
+
+    /// Returns the partition ID, usually equal to the [program ID](NcchHeader::program_id).
+    pub fn partition_id(&self) -> u64 {
+        self.partition_id
+    }
+
+    /// Returns the two character code of the maker.
+    pub fn maker_code(&self) -> &str {
+        &self.maker_code
+    }
+
+    /// Returns the version of the NCCH format.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the program ID, which is the title ID of the application.
+    pub fn program_id(&self) -> TitleId {
+        self.program_id
+    }
+
+    /// Returns the product code (e.g. `"CTR-P-ABCE"`).
+    pub fn product_code(&self) -> &str {
+        &self.product_code
+    }
+
+    /// Returns the SHA-256 hash of the logo region.
+    pub fn logo_hash(&self) -> [u8; 32] {
+        self.logo_hash
+    }
+
+    /// Returns the SHA-256 hash of the extended header.
+    pub fn extended_header_hash(&self) -> [u8; 32] {
+        self.extended_header_hash
+    }
+
+    /// Returns the size of the extended header in bytes, or 0 if there is none.
+    pub fn extended_header_size(&self) -> u32 {
+        self.extended_header_size
+    }
+
+    /// Returns the raw flags of the container.
+    pub fn flags(&self) -> [u8; 8] {
+        self.flags
+    }
+
+    /// Returns `true` if the sections of the container are encrypted.
+    pub fn is_encrypted(&self) -> bool {
+        self.flags[7] & 0x4 == 0
+    }
+
+    /// Returns `true` if the container holds an executable (as opposed to e.g. a manual or DLC).
+    pub fn is_executable(&self) -> bool {
+        self.flags[5] & 0x2 != 0
+    }
+
+    /// Returns the location of the plain region, holding the SDK versions used to build the application.
+    pub fn plain_region(&self) -> Section {
+        self.plain_region
+    }
+
+    /// Returns the location of the logo shown when launching the application.
+    pub fn logo(&self) -> Section {
+        self.logo
+    }
+
+    /// Returns the location of the ExeFS.
+    pub fn exefs(&self) -> Section {
+        self.exefs
+    }
+
+    /// Returns the location of the RomFS.
+    pub fn romfs(&self) -> Section {
+        self.romfs
+    }
+
+    /// Returns the SHA-256 hash of the start of the ExeFS.
+    pub fn exefs_superblock_hash(&self) -> [u8; 32] {
+        self.exefs_superblock_hash
+    }
+
+    /// Returns the SHA-256 hash of the start of the RomFS.
+    pub fn romfs_superblock_hash(&self) -> [u8; 32] {
+        self.romfs_superblock_hash
+    }
+}
+
+/// An NCCH container.
+pub struct Ncch<R> {
+    reader: R,
+    start: u64,
+    header: NcchHeader,
+}
+
+impl<R: Read + Seek> Ncch<R> {
+    /// Parse the NCCH container starting at the current position of `reader`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        Ok(Self {
+            reader,
+            start,
+            header: NcchHeader::parse(&header)?,
+        })
+    }
+
+    /// Open the ExeFS of the container.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::Unsupported`] if the container is encrypted,
+    /// or of kind [`io::ErrorKind::NotFound`] if it has no ExeFS.
+    pub fn exefs(&mut self) -> io::Result<ExeFs<&mut R>> {
+        let section = self.section(self.header.exefs)?;
+        self.reader
+            .seek(SeekFrom::Start(self.start + section.offset))?;
+
+        ExeFs::new(&mut self.reader)
+    }
+
+    /// Open the RomFS of the container.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::Unsupported`] if the container is encrypted,
+    /// or of kind [`io::ErrorKind::NotFound`] if it has no RomFS.
+    pub fn romfs(&mut self) -> io::Result<RomFsImage<&mut R>> {
+        let section = self.section(self.header.romfs)?;
+        self.reader
+            .seek(SeekFrom::Start(self.start + section.offset))?;
+
+        RomFsImage::new(&mut self.reader)
+    }
+
+    /// Check the hash of the extended header.
+    pub fn verify_extended_header(&mut self) -> io::Result<bool> {
+        let section = self.section(Section {
+            offset: HEADER_LEN as u64,
+            size: u64::from(self.header.extended_header_size),
+        })?;
+
+        let hash = self.hash(section.offset, EXTENDED_HEADER_LEN.min(section.size))?;
+
+        Ok(hash == self.header.extended_header_hash)
+    }
+
+    /// Check the hash of the ExeFS header and of every ExeFS file.
+    pub fn verify_exefs(&mut self) -> io::Result<bool> {
+        let section = self.section(self.header.exefs)?;
+        let hash = self.hash(section.offset, self.header.exefs_hash_region_size)?;
+
+        if hash != self.header.exefs_superblock_hash {
+            return Ok(false);
+        }
+
+        self.exefs()?.verify_all()
+    }
+
+    /// Check the hash of the start of the RomFS (its IVFC master hash).
+    pub fn verify_romfs_superblock(&mut self) -> io::Result<bool> {
+        let section = self.section(self.header.romfs)?;
+        let hash = self.hash(section.offset, self.header.romfs_hash_region_size)?;
+
+        Ok(hash == self.header.romfs_superblock_hash)
+    }
+
+    // Check that a section exists and can be read.
+    fn section(&self, section: Section) -> io::Result<Section> {
+        if section.size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "section not present in the NCCH container",
+            ));
+        }
+
+        if self.header.is_encrypted() {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "can't read sections of encrypted NCCH containers",
+            ));
+        }
+
+        Ok(section)
+    }
+
+    fn hash(&mut self, offset: u64, len: u64) -> io::Result<[u8; 32]> {
+        self.reader.seek(SeekFrom::Start(self.start + offset))?;
+
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; 0x10000];
+        let mut remaining = len;
+
+        while remaining > 0 {
+            let chunk = &mut buf[..remaining.min(0x10000) as usize];
+            self.reader.read_exact(chunk)?;
+            hasher.update(chunk);
+            remaining -= chunk.len() as u64;
+        }
+
+        Ok(hasher.finalize())
+    }
+}
+
+impl<R> Ncch<R> {
+    /// Returns the header of the container.
+    pub fn header(&self) -> &NcchHeader {
+        &self.header
+    }
+
+    /// Consume the container, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::exefs;
+    use crate::sha256::sha256;
+
+    const PROGRAM_ID: u64 = 0x0004_0000_0ABC_DE00;
+
+    // Build an unencrypted NCCH with an extended header and an ExeFS.
+    fn sample() -> Vec<u8> {
+        let extended_header = vec![0x11; 0x800];
+        let exefs = exefs::tests::build(&[(".code", b"code"), ("icon", b"smdh")]);
+        let exefs_offset = HEADER_LEN + extended_header.len();
+
+        let mut header = vec![0; HEADER_LEN];
+        header[0x100..0x104].copy_from_slice(b"NCCH");
+        let size = (exefs_offset + exefs.len()) as u64 / MEDIA_UNIT;
+        header[0x104..0x108].copy_from_slice(&(size as u32).to_le_bytes());
+        header[0x108..0x110].copy_from_slice(&PROGRAM_ID.to_le_bytes());
+        header[0x110..0x112].copy_from_slice(b"01");
+        header[0x118..0x120].copy_from_slice(&PROGRAM_ID.to_le_bytes());
+        header[0x150..0x15A].copy_from_slice(b"CTR-P-ABCE");
+        header[0x160..0x180].copy_from_slice(&sha256(&extended_header[..0x400]));
+        header[0x180..0x184].copy_from_slice(&0x400u32.to_le_bytes());
+        header[0x18D] = 0x3;
+        header[0x18F] = 0x4;
+        header[0x1A0..0x1A4]
+            .copy_from_slice(&((exefs_offset as u64 / MEDIA_UNIT) as u32).to_le_bytes());
+        header[0x1A4..0x1A8]
+            .copy_from_slice(&((exefs.len() as u64 / MEDIA_UNIT) as u32).to_le_bytes());
+        header[0x1A8..0x1AC].copy_from_slice(&1u32.to_le_bytes());
+        header[0x1C0..0x1E0].copy_from_slice(&sha256(&exefs[..0x200]));
+
+        [header, extended_header, exefs].concat()
+    }
+
+    #[test]
+    fn parse_header() {
+        let data = sample();
+        let header = NcchHeader::parse(&data).unwrap();
+
+        assert_eq!(header.program_id(), TitleId::new(PROGRAM_ID));
+        assert_eq!(header.maker_code(), "01");
+        assert_eq!(header.product_code(), "CTR-P-ABCE");
+        assert_eq!(header.content_size(), data.len() as u64);
+        assert!(!header.is_encrypted());
+        assert!(header.is_executable());
+        assert_eq!(header.exefs().offset, 0xA00);
+        assert_eq!(header.romfs(), Section::default());
+
+        assert!(NcchHeader::parse(&data[..0x100]).is_err());
+    }
+
+    #[test]
+    fn sections() {
+        let mut ncch = Ncch::new(Cursor::new(sample())).unwrap();
+
+        assert!(ncch.verify_extended_header().unwrap());
+        assert!(ncch.verify_exefs().unwrap());
+        assert_eq!(ncch.exefs().unwrap().read("icon").unwrap(), b"smdh");
+        assert_eq!(
+            ncch.romfs().err().map(|e| e.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+
+        let mut corrupted = sample();
+        corrupted[0xA00 + 0x200] ^= 0xFF;
+        let mut ncch = Ncch::new(Cursor::new(corrupted)).unwrap();
+        assert!(!ncch.verify_exefs().unwrap());
+    }
+
+    #[test]
+    fn encrypted() {
+        let mut data = sample();
+        data[0x18F] = 0;
+
+        let mut ncch = Ncch::new(Cursor::new(data)).unwrap();
+        assert!(ncch.header().is_encrypted());
+        assert_eq!(
+            ncch.exefs().err().map(|e| e.kind()),
+            Some(io::ErrorKind::Unsupported)
+        );
+    }
+}
@@ -0,0 +1,476 @@
+//! RomFS image creation.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{hash, hash_table_len, DIR_ENTRY_LEN, EMPTY, FILE_ENTRY_LEN, HEADER_LEN};
+
+// Alignment of the file data section and of each file within it.
+const FILE_DATA_ALIGNMENT: u64 = 0x10;
+
+/// Builder of level 3 RomFS images.
+///
+/// Images can be built from a directory of the host machine (see [`RomFsBuilder::from_dir()`]) or from files added in memory,
+/// and can then be read back with [`RomFsImage`](super::RomFsImage) or mounted on the console.
+///
+/// # Example
+///
+/// ```
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru_formats::romfs::RomFsBuilder;
+///
+/// let mut builder = RomFsBuilder::new();
+/// builder.add_file("/config/default.toml", b"volume = 10".to_vec())?;
+///
+/// let image: Vec<u8> = builder.build()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Since this crate builds for the host, images can also be created by the build script of an application
+/// (with `ctru-formats` as a build dependency), e.g. to ship an asset pack mounted at runtime with `RomFSMount` from `ctru-rs`:
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// // In the `main()` function of `build.rs`:
+/// use std::fs::File;
+/// use std::path::PathBuf;
+///
+/// use ctru_formats::romfs::RomFsBuilder;
+///
+/// let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+/// let image = File::create(out_dir.join("levels.bin"))?;
+///
+/// RomFsBuilder::from_dir("levels")?.write(image)?;
+/// println!("cargo:rerun-if-changed=levels");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct RomFsBuilder {
+    root: DirNode,
+}
+
+#[derive(Debug, Default)]
+struct DirNode {
+    dirs: BTreeMap<String, DirNode>,
+    files: BTreeMap<String, FileSource>,
+}
+
+#[derive(Debug)]
+enum FileSource {
+    Memory(Vec<u8>),
+    Host { path: PathBuf, len: u64 },
+}
+
+impl FileSource {
+    fn len(&self) -> u64 {
+        match self {
+            Self::Memory(data) => data.len() as u64,
+            Self::Host { len, .. } => *len,
+        }
+    }
+}
+
+impl RomFsBuilder {
+    /// Create a builder for an empty image.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder holding all files and directories found (recursively) within `dir`.
+    ///
+    /// The contents of the files are only read once the image is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory can't be read, or if any of the entries' names isn't valid UTF-8.
+    pub fn from_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let mut builder = Self::new();
+        add_host_dir(&mut builder.root, dir.as_ref())?;
+
+        Ok(builder)
+    }
+
+    /// Add a file with the specified contents at `path`, creating the missing parent directories.
+    ///
+    /// Any existing file at the same path is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::InvalidInput`] error if `path` doesn't name a file.
+    pub fn add_file(&mut self, path: &str, contents: Vec<u8>) -> io::Result<()> {
+        let (parent, name) = split_file_path(path)?;
+
+        self.dir_mut(parent)
+            .files
+            .insert(name.to_owned(), FileSource::Memory(contents));
+
+        Ok(())
+    }
+
+    /// Add an empty directory at `path`, creating the missing parent directories.
+    pub fn add_dir(&mut self, path: &str) {
+        self.dir_mut(path);
+    }
+
+    /// Build the image in memory.
+    pub fn build(&self) -> io::Result<Vec<u8>> {
+        let mut image = Vec::new();
+        self.write(&mut image)?;
+
+        Ok(image)
+    }
+
+    /// Write the image to `writer`.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let layout = Layout::new(&self.root);
+
+        let dir_hashes_len = layout.dir_hashes.len() as u32 * 4;
+        let file_hashes_len = layout.file_hashes.len() as u32 * 4;
+
+        let dir_hashes_offset = HEADER_LEN;
+        let dir_meta_offset = dir_hashes_offset + dir_hashes_len;
+        let file_hashes_offset = dir_meta_offset + layout.dir_meta.len() as u32;
+        let file_meta_offset = file_hashes_offset + file_hashes_len;
+        let metadata_end = file_meta_offset + layout.file_meta.len() as u32;
+        let file_data_offset = u64::from(metadata_end).next_multiple_of(FILE_DATA_ALIGNMENT);
+
+        let header = [
+            HEADER_LEN,
+            dir_hashes_offset,
+            dir_hashes_len,
+            dir_meta_offset,
+            layout.dir_meta.len() as u32,
+            file_hashes_offset,
+            file_hashes_len,
+            file_meta_offset,
+            layout.file_meta.len() as u32,
+            file_data_offset as u32,
+        ];
+
+        for word in header.iter().chain(&layout.dir_hashes) {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        writer.write_all(&layout.dir_meta)?;
+        for word in &layout.file_hashes {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        writer.write_all(&layout.file_meta)?;
+
+        let mut position = u64::from(metadata_end);
+        for (data_offset, source) in layout.files {
+            write_padding(&mut writer, file_data_offset + data_offset - position)?;
+
+            match source {
+                FileSource::Memory(data) => writer.write_all(data)?,
+                FileSource::Host { path, len } => {
+                    let copied = io::copy(&mut fs::File::open(path)?, &mut writer)?;
+                    if copied != *len {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            format!("{} changed while building the image", path.display()),
+                        ));
+                    }
+                }
+            }
+
+            position = file_data_offset + data_offset + source.len();
+        }
+
+        Ok(())
+    }
+
+    fn dir_mut(&mut self, path: &str) -> &mut DirNode {
+        super::components(path).fold(&mut self.root, |dir, name| {
+            dir.dirs.entry(name.to_owned()).or_default()
+        })
+    }
+}
+
+// Serialized metadata of an image, along with the position of each file's data.
+struct Layout<'a> {
+    dir_hashes: Vec<u32>,
+    dir_meta: Vec<u8>,
+    file_hashes: Vec<u32>,
+    file_meta: Vec<u8>,
+    files: Vec<(u64, &'a FileSource)>,
+}
+
+// Directory flattened in breadth-first order.
+struct FlatDir<'a> {
+    node: &'a DirNode,
+    name: Vec<u16>,
+    parent: usize,
+    offset: u32,
+    children: Vec<usize>,
+    files: Vec<usize>,
+}
+
+struct FlatFile<'a> {
+    source: &'a FileSource,
+    name: Vec<u16>,
+    parent: usize,
+    offset: u32,
+    data_offset: u64,
+}
+
+impl<'a> Layout<'a> {
+    fn new(root: &'a DirNode) -> Self {
+        let mut dirs = vec![FlatDir {
+            node: root,
+            name: Vec::new(),
+            parent: 0,
+            offset: 0,
+            children: Vec::new(),
+            files: Vec::new(),
+        }];
+        let mut files: Vec<FlatFile> = Vec::new();
+
+        let mut dir_offset = 0;
+        let mut file_offset = 0;
+        let mut data_offset: u64 = 0;
+
+        let mut index = 0;
+        while index < dirs.len() {
+            dirs[index].offset = dir_offset;
+            dir_offset += entry_len(DIR_ENTRY_LEN, &dirs[index].name);
+
+            let node = dirs[index].node;
+
+            for (name, child) in &node.dirs {
+                let child_index = dirs.len();
+                dirs[index].children.push(child_index);
+                dirs.push(FlatDir {
+                    node: child,
+                    name: name.encode_utf16().collect(),
+                    parent: index,
+                    offset: 0,
+                    children: Vec::new(),
+                    files: Vec::new(),
+                });
+            }
+
+            for (name, source) in &node.files {
+                let name: Vec<u16> = name.encode_utf16().collect();
+
+                dirs[index].files.push(files.len());
+                data_offset = data_offset.next_multiple_of(FILE_DATA_ALIGNMENT);
+                files.push(FlatFile {
+                    source,
+                    parent: index,
+                    offset: file_offset,
+                    data_offset,
+                    name,
+                });
+
+                let file = &files[files.len() - 1];
+                file_offset += entry_len(FILE_ENTRY_LEN, &file.name);
+                data_offset += source.len();
+            }
+
+            index += 1;
+        }
+
+        let dir_offsets: Vec<u32> = dirs.iter().map(|d| d.offset).collect();
+        let file_offsets: Vec<u32> = files.iter().map(|f| f.offset).collect();
+
+        let mut dir_next = vec![EMPTY; dirs.len()];
+        let mut file_next = vec![EMPTY; files.len()];
+        for dir in &dirs {
+            for pair in dir.children.windows(2) {
+                dir_next[pair[0]] = dir_offsets[pair[1]];
+            }
+            for pair in dir.files.windows(2) {
+                file_next[pair[0]] = file_offsets[pair[1]];
+            }
+        }
+
+        let mut dir_hashes = vec![EMPTY; hash_table_len(dirs.len() as u32) as usize];
+        let mut dir_meta = Vec::with_capacity(dir_offset as usize);
+
+        for (index, dir) in dirs.iter().enumerate() {
+            let parent = dir_offsets[dir.parent];
+            let first_child = dir.children.first().map_or(EMPTY, |&i| dir_offsets[i]);
+            let first_file = dir.files.first().map_or(EMPTY, |&i| file_offsets[i]);
+            let next_in_bucket = insert_hash(&mut dir_hashes, parent, &dir.name, dir.offset);
+
+            push_words(
+                &mut dir_meta,
+                &[
+                    parent,
+                    dir_next[index],
+                    first_child,
+                    first_file,
+                    next_in_bucket,
+                    dir.name.len() as u32 * 2,
+                ],
+            );
+            push_name(&mut dir_meta, &dir.name);
+        }
+
+        let mut file_hashes = vec![EMPTY; hash_table_len(files.len() as u32) as usize];
+        let mut file_meta = Vec::with_capacity(file_offset as usize);
+
+        for (index, file) in files.iter().enumerate() {
+            let parent = dir_offsets[file.parent];
+            let next_in_bucket = insert_hash(&mut file_hashes, parent, &file.name, file.offset);
+
+            push_words(&mut file_meta, &[parent, file_next[index]]);
+            file_meta.extend_from_slice(&file.data_offset.to_le_bytes());
+            file_meta.extend_from_slice(&file.source.len().to_le_bytes());
+            push_words(
+                &mut file_meta,
+                &[next_in_bucket, file.name.len() as u32 * 2],
+            );
+            push_name(&mut file_meta, &file.name);
+        }
+
+        Self {
+            dir_hashes,
+            dir_meta,
+            file_hashes,
+            file_meta,
+            files: files.iter().map(|f| (f.data_offset, f.source)).collect(),
+        }
+    }
+}
+
+// Insert an entry in its hash bucket, returning the entry it was chained to.
+fn insert_hash(table: &mut [u32], parent: u32, name: &[u16], offset: u32) -> u32 {
+    let bucket = (hash(parent, name) % table.len() as u32) as usize;
+    std::mem::replace(&mut table[bucket], offset)
+}
+
+fn entry_len(fixed_len: usize, name: &[u16]) -> u32 {
+    (fixed_len + (name.len() * 2).next_multiple_of(4)) as u32
+}
+
+fn push_words(buf: &mut Vec<u8>, words: &[u32]) {
+    for word in words {
+        buf.extend_from_slice(&word.to_le_bytes());
+    }
+}
+
+fn push_name(buf: &mut Vec<u8>, name: &[u16]) {
+    for c in name {
+        buf.extend_from_slice(&c.to_le_bytes());
+    }
+
+    if name.len() % 2 != 0 {
+        buf.extend_from_slice(&[0, 0]);
+    }
+}
+
+fn write_padding<W: Write>(writer: &mut W, len: u64) -> io::Result<()> {
+    io::copy(&mut io::repeat(0).take(len), writer)?;
+    Ok(())
+}
+
+fn split_file_path(path: &str) -> io::Result<(&str, &str)> {
+    let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+
+    if name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{path:?} doesn't name a file"),
+        ));
+    }
+
+    Ok((parent, name))
+}
+
+fn add_host_dir(node: &mut DirNode, dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{name:?} isn't valid UTF-8"),
+            )
+        })?;
+
+        let metadata = fs::metadata(&path)?;
+        if metadata.is_dir() {
+            add_host_dir(node.dirs.entry(name).or_default(), &path)?;
+        } else {
+            let len = metadata.len();
+            node.files.insert(name, FileSource::Host { path, len });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RomFsImage;
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn round_trip() {
+        let mut builder = RomFsBuilder::new();
+        builder.add_file("test-file.txt", b"test".to_vec()).unwrap();
+        builder
+            .add_file("/ファイル.txt", "ファイル".as_bytes().to_vec())
+            .unwrap();
+        builder
+            .add_file("/nested/dir/garbage-data", vec![0xAB; 1000])
+            .unwrap();
+        builder.add_dir("/empty");
+
+        let mut image = RomFsImage::new(Cursor::new(builder.build().unwrap())).unwrap();
+
+        let names: Vec<_> = image
+            .read_dir("/")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name().to_owned())
+            .collect();
+        assert_eq!(names, ["empty", "nested", "test-file.txt", "ファイル.txt"]);
+
+        assert!(image.read_dir("/empty").unwrap().is_empty());
+        assert_eq!(image.read("/test-file.txt").unwrap(), b"test");
+        assert_eq!(image.read("/ファイル.txt").unwrap(), "ファイル".as_bytes());
+        assert_eq!(
+            image.read("/nested/dir/garbage-data").unwrap(),
+            vec![0xAB; 1000]
+        );
+    }
+
+    #[test]
+    fn many_entries() {
+        let mut builder = RomFsBuilder::new();
+        for i in 0..100 {
+            builder
+                .add_file(&format!("/dir{}/file{i}.bin", i % 7), vec![i as u8; i])
+                .unwrap();
+        }
+
+        let mut image = RomFsImage::new(Cursor::new(builder.build().unwrap())).unwrap();
+
+        assert_eq!(image.read_dir("/").unwrap().len(), 7);
+        for i in 0..100 {
+            let path = format!("/dir{}/file{i}.bin", i % 7);
+            assert_eq!(image.read(&path).unwrap(), vec![i as u8; i]);
+        }
+    }
+
+    #[test]
+    fn invalid_file_paths() {
+        let mut builder = RomFsBuilder::new();
+
+        assert!(builder.add_file("/dir/", Vec::new()).is_err());
+        assert!(builder.add_file("", Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! Compile-time manifest of a package's RomFS directory.
+//!
+//! With the `build` feature enabled, [`generate()`] can be called from the build script of an application to list
+//! every file of its RomFS directory (configured with `package.metadata.cargo-3ds.romfs_dir` in its `Cargo.toml`,
+//! `"romfs"` by default), along with its size and CRC32 checksum. It writes `romfs_manifest.rs` to `OUT_DIR`, which defines:
+//!
+//! - `ROMFS_MANIFEST`, a `&[ManifestEntry]` sorted by path, which can be searched with [`find()`].
+//! - `romfs_path!`, a macro expanding to the `romfs:/` path of a file of the RomFS directory,
+//!   which fails to compile if the file doesn't exist. This turns misspelled asset paths into compile-time errors.
+//!
+//! # Example
+//!
+//! The build script (`build.rs`) of the application, which needs `ctru-formats` as a build dependency:
+//!
+#![cfg_attr(feature = "build", doc = "```no_run")]
+#![cfg_attr(not(feature = "build"), doc = "```ignore")]
+//! // In the `main()` function of `build.rs`:
+//! ctru_formats::romfs::manifest::generate().unwrap();
+//! ```
+//!
+//! The generated file must be included where items are expected (e.g. at the root of `main.rs`,
+//! since macros must be defined before their use):
+//!
+//! ```ignore
+//! use ctru::formats::romfs::manifest::{self, ManifestEntry};
+//!
+//! include!(concat!(env!("OUT_DIR"), "/romfs_manifest.rs"));
+//!
+//! fn main() {
+//!     let _romfs = ctru::services::romfs::RomFS::new().unwrap();
+//!
+//!     let path = romfs_path!("images/logo.png");
+//!     let entry = manifest::find(ROMFS_MANIFEST, path).unwrap();
+//!
+//!     let logo = std::fs::read(path).unwrap();
+//!     assert_eq!(logo.len() as u64, entry.size);
+//! }
+//! ```
+
+/// A file of the RomFS directory, as found at build time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Path of the file, relative to the RomFS root and separated by `/`.
+    pub path: &'static str,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// CRC32 checksum of the file contents, as computed by [`crc32()`](crate::checksum::crc32).
+    pub crc32: u32,
+}
+
+/// Look up a file in a manifest sorted by path.
+///
+/// The path is relative to the RomFS root, and may start with `romfs:/`.
+pub fn find<'a>(manifest: &'a [ManifestEntry], path: &str) -> Option<&'a ManifestEntry> {
+    let path = path.strip_prefix("romfs:").unwrap_or(path);
+    let path = path.trim_start_matches('/');
+
+    manifest
+        .binary_search_by(|entry| entry.path.cmp(path))
+        .ok()
+        .map(|index| &manifest[index])
+}
+
+#[cfg(feature = "build")]
+pub use self::generate::generate;
+
+#[cfg(feature = "build")]
+mod generate {
+    use std::env;
+    use std::fmt::Write;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use crate::bytes::invalid_data;
+    use crate::checksum::crc32;
+
+    /// Generate the manifest of the RomFS directory of the package being built.
+    ///
+    /// This function must be called from a build script. It reads the RomFS directory configured in the package's `Cargo.toml`,
+    /// writes `romfs_manifest.rs` to `OUT_DIR` and tells Cargo to run the build script again when the directory changes.
+    /// If the directory doesn't exist, the manifest is empty and `romfs_path!` fails for every path.
+    ///
+    /// Returns the path of the RomFS directory, or `None` if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `CARGO_MANIFEST_DIR` or `OUT_DIR` environment variables aren't set
+    /// (i.e. it isn't called from a build script), if `Cargo.toml` is invalid, or if reading the RomFS directory fails.
+    /// Files with a non UTF-8 name are skipped with a warning.
+    pub fn generate() -> io::Result<Option<PathBuf>> {
+        let manifest_dir = PathBuf::from(env_var("CARGO_MANIFEST_DIR")?);
+        let out_dir = PathBuf::from(env_var("OUT_DIR")?);
+
+        let cargo_toml = manifest_dir.join("Cargo.toml");
+        println!("cargo:rerun-if-changed={}", cargo_toml.display());
+
+        let romfs_dir = manifest_dir.join(romfs_dir_setting(&fs::read_to_string(&cargo_toml)?)?);
+
+        let mut files = Vec::new();
+        let exists = romfs_dir.is_dir();
+        if exists {
+            collect_files(&romfs_dir, String::new(), &mut files)?;
+            files.sort();
+        }
+        // Also detects the creation of the directory.
+        println!("cargo:rerun-if-changed={}", romfs_dir.display());
+
+        fs::write(out_dir.join("romfs_manifest.rs"), manifest_source(&files))?;
+
+        Ok(exists.then_some(romfs_dir))
+    }
+
+    fn env_var(name: &str) -> io::Result<String> {
+        env::var(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{name} isn't set, the manifest must be generated by a build script"),
+            )
+        })
+    }
+
+    // Read `package.metadata.cargo-3ds.romfs_dir`, which defaults to "romfs" like in `cargo-3ds`.
+    fn romfs_dir_setting(cargo_toml: &str) -> io::Result<String> {
+        let manifest: toml::Value = toml::from_str(cargo_toml)
+            .map_err(|err| invalid_data(&format!("invalid Cargo.toml: {err}")))?;
+
+        Ok(manifest
+            .get("package")
+            .and_then(|package| package.get("metadata"))
+            .and_then(|metadata| metadata.get("cargo-3ds"))
+            .and_then(|cargo_3ds| cargo_3ds.get("romfs_dir"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("romfs")
+            .to_owned())
+    }
+
+    // Recursively list every file in `dir` with its size and checksum, keyed by its `/`-separated path relative to the RomFS root.
+    fn collect_files(
+        dir: &Path,
+        prefix: String,
+        files: &mut Vec<(String, u64, u32)>,
+    ) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                println!(
+                    "cargo:warning=Skipping RomFS entry with a non UTF-8 name: {}",
+                    entry.path().display()
+                );
+                continue;
+            };
+            let path = format!("{prefix}{name}");
+
+            if entry.file_type()?.is_dir() {
+                collect_files(&entry.path(), format!("{path}/"), files)?;
+            } else {
+                let data = fs::read(entry.path())?;
+                files.push((path, data.len() as u64, crc32(&data)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn manifest_source(files: &[(String, u64, u32)]) -> String {
+        let mut entries = String::new();
+        let mut macro_arms = String::new();
+
+        for (path, size, crc32) in files {
+            writeln!(
+                entries,
+                "    ManifestEntry {{ path: {path:?}, size: {size}, crc32: {crc32:#010x} }},"
+            )
+            .unwrap();
+            writeln!(
+                macro_arms,
+                "    ({path:?}) => {{ {:?} }};",
+                format!("romfs:/{path}")
+            )
+            .unwrap();
+        }
+
+        format!(
+            "// Generated by `ctru_formats::romfs::manifest::generate()`. `ManifestEntry` must be in scope.
+
+/// Every file of the RomFS directory with its size and CRC32 checksum, sorted by path.
+#[allow(dead_code)]
+static ROMFS_MANIFEST: &[ManifestEntry] = &[
+{entries}];
+
+/// Expands to the `romfs:/` path of a file of the RomFS directory.
+///
+/// Compilation fails if no file with the given path (relative to the RomFS directory) exists.
+#[allow(unused_macros)]
+macro_rules! romfs_path {{
+{macro_arms}    ($path:literal) => {{
+        ::core::compile_error!(::core::concat!(\"file not found in the RomFS directory: \", $path))
+    }};
+}}
+"
+        )
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn romfs_dir_setting_default() {
+            let configured = "[package]\nname = \"app\"\n\n[package.metadata.cargo-3ds]\nromfs_dir = \"assets\"\n";
+
+            assert_eq!(romfs_dir_setting(configured).unwrap(), "assets");
+            assert_eq!(
+                romfs_dir_setting("[package]\nname = \"app\"\n").unwrap(),
+                "romfs"
+            );
+            assert!(romfs_dir_setting("[package").is_err());
+        }
+
+        #[test]
+        fn source() {
+            let source = manifest_source(&[("dir/a.txt".to_owned(), 5, 0x3610_A686)]);
+
+            assert!(source
+                .contains("ManifestEntry { path: \"dir/a.txt\", size: 5, crc32: 0x3610a686 },"));
+            assert!(source.contains("(\"dir/a.txt\") => { \"romfs:/dir/a.txt\" };"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() {
+        let manifest = [
+            ManifestEntry {
+                path: "a.txt",
+                size: 1,
+                crc32: 0,
+            },
+            ManifestEntry {
+                path: "dir/b.txt",
+                size: 2,
+                crc32: 0,
+            },
+        ];
+
+        assert_eq!(find(&manifest, "dir/b.txt").map(|e| e.size), Some(2));
+        assert_eq!(find(&manifest, "romfs:/a.txt").map(|e| e.size), Some(1));
+        assert_eq!(find(&manifest, "/a.txt").map(|e| e.size), Some(1));
+        assert!(find(&manifest, "c.txt").is_none());
+    }
+}
@@ -0,0 +1,691 @@
+//! RomFS image parsing and creation.
+//!
+//! RomFS is the read-only file system bundled with applications. While the `RomFS` service of `ctru-rs`
+//! mounts the application's own RomFS through `libctru`, [`RomFsImage`] reads RomFS images directly from any [`Read`] + [`Seek`] source,
+//! such as a `romfs.bin` file on the host machine. New images can be created from a directory tree with [`RomFsBuilder`].
+//!
+//! Both bare level 3 images (as embedded in 3DSX files) and images wrapped in an IVFC hash tree (as found in NCCH containers) are supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use std::io::Read;
+//!
+//! use ctru_formats::romfs::RomFsImage;
+//!
+//! let mut image = RomFsImage::new(std::fs::File::open("romfs.bin")?)?;
+//!
+//! for entry in image.read_dir("/")? {
+//!     println!("{}", entry.name());
+//! }
+//!
+//! let mut contents = String::new();
+//! image.open("/test-file.txt")?.read_to_string(&mut contents)?;
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bytes::{invalid_data, read_u32, read_u64, read_vec};
+use std::io::{self, Read, Seek, SeekFrom};
+
+mod builder;
+pub mod manifest;
+
+pub use self::builder::RomFsBuilder;
+
+// Size of the level 3 header.
+const HEADER_LEN: u32 = 0x28;
+
+// Offset of an empty entry in the metadata tables.
+const EMPTY: u32 = 0xFFFF_FFFF;
+
+// Size of the IVFC header, preceding the master hash.
+const IVFC_HEADER_LEN: u64 = 0x60;
+
+const IVFC_MAGIC: [u8; 4] = *b"IVFC";
+
+/// A RomFS image.
+pub struct RomFsImage<R> {
+    reader: R,
+    base: u64,
+    file_data: u64,
+    dir_hashes: Vec<u32>,
+    dir_meta: Vec<u8>,
+    file_hashes: Vec<u32>,
+    file_meta: Vec<u8>,
+}
+
+/// Kind of a [`DirEntry`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// The entry is a directory.
+    Directory,
+    /// The entry is a file.
+    File,
+}
+
+/// Entry of a directory within a [`RomFsImage`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    name: String,
+    kind: EntryKind,
+    size: u64,
+}
+
+impl DirEntry {
+    /// Returns the name of this entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of this entry.
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// Returns `true` if this entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+
+    /// Returns `true` if this entry is a file.
+    pub fn is_file(&self) -> bool {
+        self.kind == EntryKind::File
+    }
+
+    /// Returns the size of this entry in bytes.
+    ///
+    /// Directories always have a size of 0.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+}
+
+/// A file within a [`RomFsImage`], opened with [`RomFsImage::open()`].
+pub struct RomFsFile<'a, R> {
+    reader: &'a mut R,
+    start: u64,
+    len: u64,
+    position: u64,
+}
+
+impl<R: Read + Seek> RomFsImage<R> {
+    /// Parse the RomFS image read from `reader`.
+    ///
+    /// The image may either start with an IVFC header or directly with the level 3 header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::InvalidData`] error if the image's metadata is malformed.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        let base = if magic == IVFC_MAGIC {
+            let mut ivfc = [0; 0x5C];
+            reader.seek(SeekFrom::Start(start))?;
+            reader.read_exact(&mut ivfc)?;
+
+            let master_hash_len = u64::from(read_u32(&ivfc, 0x08));
+            let level3_block_log2 = read_u32(&ivfc, 0x4C);
+            if level3_block_log2 >= 32 {
+                return Err(invalid_data("invalid IVFC level 3 block size"));
+            }
+
+            start + (IVFC_HEADER_LEN + master_hash_len).next_multiple_of(1 << level3_block_log2)
+        } else {
+            start
+        };
+
+        let mut header = [0; HEADER_LEN as usize];
+        reader.seek(SeekFrom::Start(base))?;
+        reader.read_exact(&mut header)?;
+
+        if read_u32(&header, 0x00) != HEADER_LEN {
+            return Err(invalid_data("invalid RomFS level 3 header"));
+        }
+
+        let mut read_table = |offset: usize| -> io::Result<Vec<u8>> {
+            let table_offset = u64::from(read_u32(&header, offset));
+            let table_len = u64::from(read_u32(&header, offset + 4));
+
+            reader.seek(SeekFrom::Start(base + table_offset))?;
+            read_vec(&mut reader, table_len)
+        };
+
+        let dir_hashes = u32_table(&read_table(0x04)?);
+        let dir_meta = read_table(0x0C)?;
+        let file_hashes = u32_table(&read_table(0x14)?);
+        let file_meta = read_table(0x1C)?;
+
+        if dir_meta.len() < DIR_ENTRY_LEN {
+            return Err(invalid_data("RomFS image has no root directory"));
+        }
+
+        Ok(Self {
+            file_data: base + u64::from(read_u32(&header, 0x24)),
+            reader,
+            base,
+            dir_hashes,
+            dir_meta,
+            file_hashes,
+            file_meta,
+        })
+    }
+
+    /// Returns the offset of the level 3 data within the underlying reader.
+    pub fn level3_offset(&self) -> u64 {
+        self.base
+    }
+
+    /// Returns the entries of the directory at `path`.
+    ///
+    /// Subdirectories are listed first, followed by files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::NotFound`] error if the directory doesn't exist.
+    pub fn read_dir(&self, path: &str) -> io::Result<Vec<DirEntry>> {
+        let dir = self.find_dir(path)?;
+        let mut entries = Vec::new();
+
+        let mut steps = StepLimit::new(&self.dir_meta, DIR_ENTRY_LEN);
+        let mut child = self.dir(dir)?.first_child;
+        while child != EMPTY {
+            steps.step()?;
+            let entry = self.dir(child)?;
+            entries.push(DirEntry {
+                name: entry.name,
+                kind: EntryKind::Directory,
+                size: 0,
+            });
+            child = entry.next_sibling;
+        }
+
+        let mut steps = StepLimit::new(&self.file_meta, FILE_ENTRY_LEN);
+        let mut file = self.dir(dir)?.first_file;
+        while file != EMPTY {
+            steps.step()?;
+            let entry = self.file(file)?;
+            entries.push(DirEntry {
+                name: entry.name,
+                kind: EntryKind::File,
+                size: entry.data_len,
+            });
+            file = entry.next_sibling;
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns `true` if a file or directory exists at `path`.
+    pub fn exists(&self, path: &str) -> bool {
+        self.find_dir(path).is_ok() || self.find_file(path).is_ok()
+    }
+
+    /// Returns the size of the file at `path` in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::NotFound`] error if the file doesn't exist.
+    pub fn file_len(&self, path: &str) -> io::Result<u64> {
+        Ok(self.file(self.find_file(path)?)?.data_len)
+    }
+
+    /// Open the file at `path` for reading.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::NotFound`] error if the file doesn't exist.
+    pub fn open(&mut self, path: &str) -> io::Result<RomFsFile<'_, R>> {
+        let file = self.file(self.find_file(path)?)?;
+        let start = self
+            .file_data
+            .checked_add(file.data_offset)
+            .filter(|start| start.checked_add(file.data_len).is_some())
+            .ok_or_else(|| invalid_data("RomFS file data out of bounds"))?;
+
+        Ok(RomFsFile {
+            reader: &mut self.reader,
+            start,
+            len: file.data_len,
+            position: 0,
+        })
+    }
+
+    /// Read the whole contents of the file at `path`.
+    pub fn read(&mut self, path: &str) -> io::Result<Vec<u8>> {
+        let file = self.open(path)?;
+        let len = file.len;
+
+        read_vec(file, len)
+    }
+
+    /// Consume the image, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // Find the metadata offset of the directory at `path`.
+    fn find_dir(&self, path: &str) -> io::Result<u32> {
+        components(path).try_fold(0, |parent, name| self.find_child_dir(parent, name))
+    }
+
+    // Find the metadata offset of the file at `path`.
+    fn find_file(&self, path: &str) -> io::Result<u32> {
+        let (parent, name) = match path.trim_end_matches('/').rsplit_once('/') {
+            Some((parent, name)) => (parent, name),
+            None => ("", path),
+        };
+
+        if name.is_empty() {
+            return Err(not_found(path));
+        }
+
+        let parent = self.find_dir(parent)?;
+        let name_utf16: Vec<u16> = name.encode_utf16().collect();
+
+        let mut steps = StepLimit::new(&self.file_meta, FILE_ENTRY_LEN);
+        let mut offset = bucket(&self.file_hashes, parent, &name_utf16)?;
+        while offset != EMPTY {
+            steps.step()?;
+            let entry = self.file(offset)?;
+            if entry.parent == parent && entry.name == name {
+                return Ok(offset);
+            }
+            offset = entry.next_in_bucket;
+        }
+
+        Err(not_found(path))
+    }
+
+    fn find_child_dir(&self, parent: u32, name: &str) -> io::Result<u32> {
+        let name_utf16: Vec<u16> = name.encode_utf16().collect();
+
+        let mut steps = StepLimit::new(&self.dir_meta, DIR_ENTRY_LEN);
+        let mut offset = bucket(&self.dir_hashes, parent, &name_utf16)?;
+        while offset != EMPTY {
+            steps.step()?;
+            let entry = self.dir(offset)?;
+            if entry.parent == parent && entry.name == name {
+                return Ok(offset);
+            }
+            offset = entry.next_in_bucket;
+        }
+
+        Err(not_found(name))
+    }
+
+    fn dir(&self, offset: u32) -> io::Result<DirMeta> {
+        DirMeta::parse(&self.dir_meta, offset as usize)
+    }
+
+    fn file(&self, offset: u32) -> io::Result<FileMeta> {
+        FileMeta::parse(&self.file_meta, offset as usize)
+    }
+}
+
+impl<R: Read + Seek> RomFsFile<'_, R> {
+    /// Returns the size of the file in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl<R: Read + Seek> Read for RomFsFile<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.position);
+        let wanted = (buf.len() as u64).min(remaining) as usize;
+
+        if wanted == 0 {
+            return Ok(0);
+        }
+
+        let offset = self
+            .start
+            .checked_add(self.position)
+            .ok_or_else(|| invalid_data("RomFS file data out of bounds"))?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let read = self.reader.read(&mut buf[..wanted])?;
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for RomFsFile<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(delta) => (self.len, delta),
+            SeekFrom::Current(delta) => (self.position, delta),
+        };
+
+        match base.checked_add_signed(delta) {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// Compute the hash used to index entries in the RomFS hash tables.
+///
+/// `parent` is the metadata offset of the parent directory and `name` the UTF-16 encoded name of the entry.
+/// The result must be reduced modulo the amount of buckets in the table.
+pub fn hash(parent: u32, name: &[u16]) -> u32 {
+    name.iter().fold(parent ^ 123_456_789, |hash, &c| {
+        hash.rotate_right(5) ^ u32::from(c)
+    })
+}
+
+/// Returns the amount of buckets of a hash table holding `count` entries.
+///
+/// This is the computation used by the official tools, which picks a number not divisible by any small prime.
+/// It applies to the hash tables of RomFS images as well as to those of save data archives.
+pub fn hash_table_len(count: u32) -> u32 {
+    match count {
+        0..=2 => 3,
+        3..=18 => count | 1,
+        _ => (count..=u32::MAX)
+            .find(|len| [2, 3, 5, 7, 11, 13, 17].iter().all(|p| len % p != 0))
+            .unwrap_or(count),
+    }
+}
+
+// Size of the fixed part of a directory metadata entry.
+const DIR_ENTRY_LEN: usize = 0x18;
+
+// Size of the fixed part of a file metadata entry.
+const FILE_ENTRY_LEN: usize = 0x20;
+
+struct DirMeta {
+    parent: u32,
+    next_sibling: u32,
+    first_child: u32,
+    first_file: u32,
+    next_in_bucket: u32,
+    name: String,
+}
+
+impl DirMeta {
+    fn parse(table: &[u8], offset: usize) -> io::Result<Self> {
+        let entry = table
+            .get(offset..)
+            .and_then(|entry| entry.get(..DIR_ENTRY_LEN))
+            .ok_or_else(|| invalid_data("directory entry out of bounds"))?;
+
+        Ok(Self {
+            parent: read_u32(entry, 0x00),
+            next_sibling: read_u32(entry, 0x04),
+            first_child: read_u32(entry, 0x08),
+            first_file: read_u32(entry, 0x0C),
+            next_in_bucket: read_u32(entry, 0x10),
+            name: read_name(table, offset + DIR_ENTRY_LEN, read_u32(entry, 0x14))?,
+        })
+    }
+}
+
+struct FileMeta {
+    parent: u32,
+    next_sibling: u32,
+    data_offset: u64,
+    data_len: u64,
+    next_in_bucket: u32,
+    name: String,
+}
+
+impl FileMeta {
+    fn parse(table: &[u8], offset: usize) -> io::Result<Self> {
+        let entry = table
+            .get(offset..)
+            .and_then(|entry| entry.get(..FILE_ENTRY_LEN))
+            .ok_or_else(|| invalid_data("file entry out of bounds"))?;
+
+        Ok(Self {
+            parent: read_u32(entry, 0x00),
+            next_sibling: read_u32(entry, 0x04),
+            data_offset: read_u64(entry, 0x08),
+            data_len: read_u64(entry, 0x10),
+            next_in_bucket: read_u32(entry, 0x18),
+            name: read_name(table, offset + FILE_ENTRY_LEN, read_u32(entry, 0x1C))?,
+        })
+    }
+}
+
+// Bounds the number of links followed along a chain of metadata entries (siblings or hash buckets).
+// A valid chain visits each entry at most once, so it can't be longer than the amount of entries fitting in the table:
+// following more links means that the chain loops.
+struct StepLimit {
+    remaining: usize,
+}
+
+impl StepLimit {
+    fn new(table: &[u8], entry_len: usize) -> Self {
+        Self {
+            remaining: table.len() / entry_len,
+        }
+    }
+
+    fn step(&mut self) -> io::Result<()> {
+        self.remaining = self
+            .remaining
+            .checked_sub(1)
+            .ok_or_else(|| invalid_data("loop in RomFS metadata"))?;
+
+        Ok(())
+    }
+}
+
+// Returns the first entry in the hash bucket of the entry named `name` within `parent`.
+fn bucket(table: &[u32], parent: u32, name: &[u16]) -> io::Result<u32> {
+    if table.is_empty() {
+        return Err(invalid_data("empty RomFS hash table"));
+    }
+
+    Ok(table[(hash(parent, name) % table.len() as u32) as usize])
+}
+
+// Split a path into its components, ignoring empty ones.
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|c| !c.is_empty())
+}
+
+fn read_name(table: &[u8], offset: usize, len: u32) -> io::Result<String> {
+    let bytes = table
+        .get(offset..)
+        .and_then(|name| name.get(..len as usize))
+        .ok_or_else(|| invalid_data("entry name out of bounds"))?;
+
+    let name: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+
+    String::from_utf16(&name).map_err(|_| invalid_data("entry name isn't valid UTF-16"))
+}
+
+fn u32_table(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
+
+fn not_found(path: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{path:?} not found in RomFS image"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn utf16(name: &str) -> Vec<u8> {
+        name.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn words(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    // Hand-made image with single-bucket hash tables, so lookups must walk the bucket chains:
+    //
+    // /a.txt           "hello"
+    // /sub/ファイル.txt "data"
+    fn test_image() -> Vec<u8> {
+        let mut dir_meta = words(&[0, EMPTY, 0x18, 0x00, 0x18, 0]);
+        dir_meta.extend(words(&[0, EMPTY, EMPTY, 0x2C, EMPTY, 6]));
+        dir_meta.extend(utf16("sub"));
+        dir_meta.extend([0, 0]);
+
+        let mut file_meta = words(&[0, EMPTY, 0, 0, 5, 0, 0x2C, 10]);
+        file_meta.extend(utf16("a.txt"));
+        file_meta.extend([0, 0]);
+        file_meta.extend(words(&[0x18, EMPTY, 16, 0, 4, 0, EMPTY, 16]));
+        file_meta.extend(utf16("ファイル.txt"));
+
+        let mut image = words(&[HEADER_LEN, 0x28, 4, 0x2C, 0x38, 0x64, 4, 0x68, 0x5C, 0xD0]);
+        image.extend(words(&[0]));
+        image.extend(dir_meta);
+        image.extend(words(&[0]));
+        image.extend(file_meta);
+        image.resize(0xD0, 0);
+        image.extend(b"hello");
+        image.resize(0xE0, 0);
+        image.extend(b"data");
+
+        image
+    }
+
+    #[test]
+    fn hash_values() {
+        // Reference values computed with the algorithm used by `libctru`.
+        assert_eq!(hash(0, &[]), 0x075B_CD15);
+        assert_eq!(hash(0, &[0x73, 0x75, 0x62]), 0x2EEA_0ED6);
+    }
+
+    #[test]
+    fn hash_table_lens() {
+        assert_eq!(hash_table_len(0), 3);
+        assert_eq!(hash_table_len(10), 11);
+        assert_eq!(hash_table_len(11), 11);
+        assert_eq!(hash_table_len(19), 19);
+        assert_eq!(hash_table_len(20), 23);
+        assert_eq!(hash_table_len(u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn list_and_read() {
+        let mut image = RomFsImage::new(Cursor::new(test_image())).unwrap();
+
+        let root = image.read_dir("/").unwrap();
+        assert_eq!(root.len(), 2);
+        assert!(root[0].is_dir() && root[0].name() == "sub");
+        assert!(root[1].is_file() && root[1].name() == "a.txt" && root[1].len() == 5);
+
+        let sub = image.read_dir("sub/").unwrap();
+        assert_eq!(sub[0].name(), "ファイル.txt");
+
+        assert_eq!(image.read("/a.txt").unwrap(), b"hello");
+        assert_eq!(image.read("/sub/ファイル.txt").unwrap(), b"data");
+
+        let mut file = image.open("/a.txt").unwrap();
+        file.seek(SeekFrom::End(-2)).unwrap();
+        let mut tail = String::new();
+        file.read_to_string(&mut tail).unwrap();
+        assert_eq!(tail, "lo");
+    }
+
+    #[test]
+    fn missing_entries() {
+        let image = RomFsImage::new(Cursor::new(test_image())).unwrap();
+
+        assert!(image.exists("/sub"));
+        assert!(!image.exists("/b.txt"));
+        assert_eq!(
+            image.file_len("/sub").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            image.read_dir("/a.txt/").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn corrupted_metadata() {
+        fn patched(offset: usize, value: u32) -> RomFsImage<Cursor<Vec<u8>>> {
+            let mut data = test_image();
+            data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+
+            RomFsImage::new(Cursor::new(data)).unwrap()
+        }
+
+        // The directory metadata starts at 0x2C, the file metadata at 0x68.
+        let sibling_loop = patched(0x2C + 0x18 + 0x04, 0x18);
+        let bucket_loop = patched(0x68 + 0x2C + 0x18, 0x00);
+        let out_of_bounds = patched(0x2C + 0x08, 0xFFFF_FFF0);
+        let long_name = patched(0x68 + 0x1C, 0xFFFF_FFF0);
+
+        for result in [
+            sibling_loop.read_dir("/").map(drop),
+            bucket_loop.file_len("/b.txt").map(drop),
+            out_of_bounds.read_dir("/").map(drop),
+            long_name.read_dir("/").map(drop),
+        ] {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+
+        // File data offsets that overflow when added to the start of the file data.
+        for data_offset in [u64::MAX, u64::MAX - 0xD0 - 2] {
+            let mut data = test_image();
+            data[0x68 + 0x08..0x68 + 0x10].copy_from_slice(&data_offset.to_le_bytes());
+            let mut image = RomFsImage::new(Cursor::new(data)).unwrap();
+
+            let result = image.open("/a.txt").map(drop);
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+
+        // Tables can't be larger than the image.
+        let mut data = test_image();
+        data[0x10..0x14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            RomFsImage::new(Cursor::new(data)).err().unwrap().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn ivfc_wrapped() {
+        let mut data = vec![0; 0x1000];
+        data[..4].copy_from_slice(&IVFC_MAGIC);
+        data[0x08..0x0C].copy_from_slice(&0x20u32.to_le_bytes());
+        data[0x4C..0x50].copy_from_slice(&12u32.to_le_bytes());
+        data.extend(test_image());
+
+        let mut image = RomFsImage::new(Cursor::new(data)).unwrap();
+
+        assert_eq!(image.level3_offset(), 0x1000);
+        assert_eq!(image.read("/a.txt").unwrap(), b"hello");
+    }
+}
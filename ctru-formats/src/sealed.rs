@@ -0,0 +1,13 @@
+//! This is a private module to prevent users from implementing certain traits.
+//! This is done by requiring a `Sealed` trait implementation, which can only be
+//! done in this crate.
+
+use crate::gfx::pixel::{Bgr8, Rgb565, Rgb5A1, Rgba4, Rgba8};
+
+pub trait Sealed {}
+
+impl Sealed for Rgba8 {}
+impl Sealed for Bgr8 {}
+impl Sealed for Rgb565 {}
+impl Sealed for Rgb5A1 {}
+impl Sealed for Rgba4 {}
@@ -0,0 +1,546 @@
+//! SMDH parsing and creation.
+//!
+//! The SMDH contains the metadata shown by the HOME Menu for an application: its title, description and publisher in every language,
+//! its icons, the regions it can be played in, its age ratings and a few behaviour flags.
+//! It's found in the ExeFS of installed titles (see `Title::smdh()` in `ctru-rs`) and in the extended header of 3DSX files.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::cfg::Language;
+//! use ctru_formats::smdh::Smdh;
+//!
+//! let smdh = Smdh::parse(&std::fs::read("icon.smdh")?)?;
+//!
+//! println!("{}", smdh.title(Language::English).short_description);
+//!
+//! // Icons are decoded to linear RGB565 pixels, from the top-left corner.
+//! let top_left = smdh.large_icon[0];
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+
+use crate::bytes::{read_u16, read_u32};
+use crate::cfg::{Language, Region};
+use crate::gfx::tiling::{self, PixelSize};
+
+/// Size of an SMDH file in bytes.
+pub const SMDH_LEN: usize = 0x36C0;
+
+/// Width and height of the small icon, in pixels.
+pub const SMALL_ICON_SIZE: usize = 24;
+
+/// Width and height of the large icon, in pixels.
+pub const LARGE_ICON_SIZE: usize = 48;
+
+/// Number of title slots in an SMDH, one per (possibly unused) language.
+pub const TITLE_COUNT: usize = 16;
+
+/// Number of age rating slots in an SMDH, one per (possibly unused) rating board.
+pub const RATING_COUNT: usize = 16;
+
+const MAGIC: [u8; 4] = *b"SMDH";
+
+// Lengths of the title fields, in UTF-16 code units.
+const SHORT_DESCRIPTION_LEN: usize = 0x40;
+const LONG_DESCRIPTION_LEN: usize = 0x80;
+const PUBLISHER_LEN: usize = 0x40;
+const TITLE_LEN: usize = 2 * (SHORT_DESCRIPTION_LEN + LONG_DESCRIPTION_LEN + PUBLISHER_LEN);
+
+const TITLES_OFFSET: usize = 0x8;
+const RATINGS_OFFSET: usize = 0x2008;
+const REGION_LOCKOUT_OFFSET: usize = 0x2018;
+const MATCH_MAKER_ID_OFFSET: usize = 0x201C;
+const MATCH_MAKER_BIT_ID_OFFSET: usize = 0x2020;
+const FLAGS_OFFSET: usize = 0x2028;
+const EULA_VERSION_OFFSET: usize = 0x202C;
+const ANIMATION_FRAME_OFFSET: usize = 0x2030;
+const CEC_ID_OFFSET: usize = 0x2034;
+const SMALL_ICON_OFFSET: usize = 0x2040;
+const LARGE_ICON_OFFSET: usize = SMALL_ICON_OFFSET + 2 * SMALL_ICON_SIZE * SMALL_ICON_SIZE;
+
+bitflags::bitflags! {
+    /// Regions an application can be launched in.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct RegionLockout: u32 {
+        /// Japan.
+        const JAPAN = 1 << 0;
+        /// North America.
+        const NORTH_AMERICA = 1 << 1;
+        /// Europe.
+        const EUROPE = 1 << 2;
+        /// Australia.
+        const AUSTRALIA = 1 << 3;
+        /// China.
+        const CHINA = 1 << 4;
+        /// Korea.
+        const KOREA = 1 << 5;
+        /// Taiwan.
+        const TAIWAN = 1 << 6;
+        /// Every region, now and in the future.
+        const REGION_FREE = 0x7FFF_FFFF;
+    }
+
+    /// Behaviour flags of an application.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Flags: u32 {
+        /// The application is shown in the HOME Menu.
+        const VISIBLE = 1 << 0;
+        /// The application is launched automatically (for game cards).
+        const AUTO_BOOT = 1 << 1;
+        /// The application makes use of the 3D effect.
+        const ALLOW_3D = 1 << 2;
+        /// The EULA must be accepted before launching the application.
+        const REQUIRE_EULA = 1 << 3;
+        /// The application's state is saved automatically when exiting.
+        const AUTO_SAVE_ON_EXIT = 1 << 4;
+        /// The application uses an extended banner.
+        const EXTENDED_BANNER = 1 << 5;
+        /// The age rating for the console's region is required.
+        const REGION_RATING_REQUIRED = 1 << 6;
+        /// The application uses save data.
+        const USES_SAVE_DATA = 1 << 7;
+        /// The application's usage is recorded in the Activity Log.
+        const RECORD_USAGE = 1 << 8;
+        /// SD card save data backups are disabled.
+        const DISABLE_SAVE_BACKUPS = 1 << 10;
+        /// The application only runs on the New 3DS family.
+        const NEW_3DS_EXCLUSIVE = 1 << 12;
+    }
+}
+
+impl RegionLockout {
+    /// Returns `true` if an application with this lockout can be launched on consoles of the given region.
+    pub fn allows(self, region: Region) -> bool {
+        let region = match region {
+            Region::Japan => Self::JAPAN,
+            Region::USA => Self::NORTH_AMERICA,
+            Region::Europe => Self::EUROPE,
+            Region::Australia => Self::AUSTRALIA,
+            Region::China => Self::CHINA,
+            Region::Korea => Self::KOREA,
+            Region::Taiwan => Self::TAIWAN,
+        };
+
+        self.contains(region)
+    }
+}
+
+/// Organization issuing an [`AgeRating`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum RatingBoard {
+    /// CERO (Japan).
+    Cero = 0,
+    /// ESRB (North America).
+    Esrb = 1,
+    /// USK (Germany).
+    Usk = 3,
+    /// PEGI (Europe).
+    PegiGen = 4,
+    /// PEGI (Portugal).
+    PegiPrt = 6,
+    /// PEGI and BBFC (United Kingdom).
+    PegiBbfc = 7,
+    /// COB (Australia).
+    Cob = 8,
+    /// GRB (South Korea).
+    Grb = 9,
+    /// CGSRR (Taiwan).
+    Cgsrr = 10,
+}
+
+/// Age rating given by a [`RatingBoard`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AgeRating(u8);
+
+impl AgeRating {
+    const ENABLED: u8 = 0x80;
+    const PENDING: u8 = 0x40;
+    const NO_RESTRICTION: u8 = 0x20;
+    const AGE_MASK: u8 = 0x1F;
+
+    /// Rating which isn't set.
+    pub const UNSET: Self = Self(0);
+
+    /// Create a rating restricting the application to players of at least `age` years.
+    ///
+    /// Ages are clamped to 31, the highest representable value.
+    pub fn new(age: u8) -> Self {
+        Self(Self::ENABLED | age.min(Self::AGE_MASK))
+    }
+
+    /// Create a rating which hasn't been decided yet.
+    pub fn pending() -> Self {
+        Self(Self::ENABLED | Self::PENDING)
+    }
+
+    /// Create a rating without any age restriction.
+    pub fn no_restriction() -> Self {
+        Self(Self::ENABLED | Self::NO_RESTRICTION)
+    }
+
+    /// Create a rating from its raw byte representation.
+    pub fn from_raw(raw: u8) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw byte representation of this rating.
+    pub fn into_raw(self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if this rating is set.
+    pub fn is_enabled(self) -> bool {
+        self.0 & Self::ENABLED != 0
+    }
+
+    /// Returns `true` if this rating hasn't been decided yet.
+    pub fn is_pending(self) -> bool {
+        self.0 & Self::PENDING != 0
+    }
+
+    /// Returns `true` if this rating doesn't restrict the age of players.
+    pub fn is_unrestricted(self) -> bool {
+        self.0 & Self::NO_RESTRICTION != 0
+    }
+
+    /// Returns the minimum age of players, if this rating is set.
+    pub fn age(self) -> Option<u8> {
+        self.is_enabled().then_some(self.0 & Self::AGE_MASK)
+    }
+}
+
+/// Localized names of an application.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ApplicationTitle {
+    /// Short name of the application (up to 64 UTF-16 code units).
+    pub short_description: String,
+    /// Long name of the application (up to 128 UTF-16 code units).
+    pub long_description: String,
+    /// Publisher of the application (up to 64 UTF-16 code units).
+    pub publisher: String,
+}
+
+/// Contents of an SMDH file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Smdh {
+    /// Version of the SMDH format.
+    pub version: u16,
+    /// Titles of the application, indexed by language.
+    ///
+    /// Use [`Smdh::title()`] to access them with a [`Language`].
+    pub titles: [ApplicationTitle; TITLE_COUNT],
+    /// Age ratings of the application, indexed by rating board.
+    ///
+    /// Use [`Smdh::age_rating()`] to access them with a [`RatingBoard`].
+    pub age_ratings: [AgeRating; RATING_COUNT],
+    /// Regions the application can be launched in.
+    pub region_lockout: RegionLockout,
+    /// Online matchmaking ID.
+    pub match_maker_id: u32,
+    /// Online matchmaking BIT ID.
+    pub match_maker_bit_id: u64,
+    /// Behaviour flags.
+    pub flags: Flags,
+    /// EULA version the application requires, as `(major, minor)`.
+    pub eula_version: (u8, u8),
+    /// Frame of the banner animation shown when the application is selected.
+    pub optimal_animation_frame: f32,
+    /// StreetPass ID.
+    pub cec_id: u32,
+    /// 24x24 icon, as linear RGB565 pixels in row-major order.
+    pub small_icon: Vec<u16>,
+    /// 48x48 icon, as linear RGB565 pixels in row-major order.
+    pub large_icon: Vec<u16>,
+}
+
+impl Smdh {
+    /// Parse an SMDH file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if `data` is too short or doesn't start with the SMDH magic.
+    pub fn parse(data: &[u8]) -> io::Result<Self> {
+        if data.len() < SMDH_LEN || data[..4] != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a valid SMDH file",
+            ));
+        }
+
+        let titles = std::array::from_fn(|index| {
+            let title = &data[TITLES_OFFSET + index * TITLE_LEN..][..TITLE_LEN];
+            let (short_description, rest) = title.split_at(2 * SHORT_DESCRIPTION_LEN);
+            let (long_description, publisher) = rest.split_at(2 * LONG_DESCRIPTION_LEN);
+
+            ApplicationTitle {
+                short_description: decode_utf16(short_description),
+                long_description: decode_utf16(long_description),
+                publisher: decode_utf16(publisher),
+            }
+        });
+
+        let age_ratings = std::array::from_fn(|index| AgeRating(data[RATINGS_OFFSET + index]));
+
+        Ok(Self {
+            version: read_u16(data, 0x4),
+            titles,
+            age_ratings,
+            region_lockout: RegionLockout::from_bits_retain(read_u32(data, REGION_LOCKOUT_OFFSET)),
+            match_maker_id: read_u32(data, MATCH_MAKER_ID_OFFSET),
+            match_maker_bit_id: u64::from_le_bytes(
+                data[MATCH_MAKER_BIT_ID_OFFSET..][..8].try_into().unwrap(),
+            ),
+            flags: Flags::from_bits_retain(read_u32(data, FLAGS_OFFSET)),
+            eula_version: (data[EULA_VERSION_OFFSET + 1], data[EULA_VERSION_OFFSET]),
+            optimal_animation_frame: f32::from_bits(read_u32(data, ANIMATION_FRAME_OFFSET)),
+            cec_id: read_u32(data, CEC_ID_OFFSET),
+            small_icon: decode_icon(&data[SMALL_ICON_OFFSET..], SMALL_ICON_SIZE),
+            large_icon: decode_icon(&data[LARGE_ICON_OFFSET..], LARGE_ICON_SIZE),
+        })
+    }
+
+    /// Encode the SMDH into its binary representation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidInput`] if a title doesn't fit in its field
+    /// or if an icon doesn't have the expected number of pixels.
+    pub fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        if self.small_icon.len() != SMALL_ICON_SIZE * SMALL_ICON_SIZE
+            || self.large_icon.len() != LARGE_ICON_SIZE * LARGE_ICON_SIZE
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SMDH icon has the wrong size",
+            ));
+        }
+
+        let mut data = vec![0; SMDH_LEN];
+
+        data[..4].copy_from_slice(&MAGIC);
+        data[0x4..0x6].copy_from_slice(&self.version.to_le_bytes());
+
+        for (index, title) in self.titles.iter().enumerate() {
+            let field = &mut data[TITLES_OFFSET + index * TITLE_LEN..][..TITLE_LEN];
+            let (short_description, rest) = field.split_at_mut(2 * SHORT_DESCRIPTION_LEN);
+            let (long_description, publisher) = rest.split_at_mut(2 * LONG_DESCRIPTION_LEN);
+
+            encode_utf16(&title.short_description, short_description)?;
+            encode_utf16(&title.long_description, long_description)?;
+            encode_utf16(&title.publisher, publisher)?;
+        }
+
+        for (index, rating) in self.age_ratings.iter().enumerate() {
+            data[RATINGS_OFFSET + index] = rating.0;
+        }
+
+        write_u32(&mut data, REGION_LOCKOUT_OFFSET, self.region_lockout.bits());
+        write_u32(&mut data, MATCH_MAKER_ID_OFFSET, self.match_maker_id);
+        data[MATCH_MAKER_BIT_ID_OFFSET..][..8]
+            .copy_from_slice(&self.match_maker_bit_id.to_le_bytes());
+        write_u32(&mut data, FLAGS_OFFSET, self.flags.bits());
+        data[EULA_VERSION_OFFSET] = self.eula_version.1;
+        data[EULA_VERSION_OFFSET + 1] = self.eula_version.0;
+        write_u32(
+            &mut data,
+            ANIMATION_FRAME_OFFSET,
+            self.optimal_animation_frame.to_bits(),
+        );
+        write_u32(&mut data, CEC_ID_OFFSET, self.cec_id);
+
+        encode_icon(
+            &self.small_icon,
+            SMALL_ICON_SIZE,
+            &mut data[SMALL_ICON_OFFSET..],
+        );
+        encode_icon(
+            &self.large_icon,
+            LARGE_ICON_SIZE,
+            &mut data[LARGE_ICON_OFFSET..],
+        );
+
+        Ok(data)
+    }
+
+    /// Returns the title of the application in the given language.
+    pub fn title(&self, language: Language) -> &ApplicationTitle {
+        &self.titles[language as usize]
+    }
+
+    /// Returns a mutable reference to the title of the application in the given language.
+    pub fn title_mut(&mut self, language: Language) -> &mut ApplicationTitle {
+        &mut self.titles[language as usize]
+    }
+
+    /// Returns the age rating given by a rating board.
+    pub fn age_rating(&self, board: RatingBoard) -> AgeRating {
+        self.age_ratings[board as usize]
+    }
+
+    /// Set the age rating given by a rating board.
+    pub fn set_age_rating(&mut self, board: RatingBoard, rating: AgeRating) {
+        self.age_ratings[board as usize] = rating;
+    }
+}
+
+impl Default for Smdh {
+    /// Empty metadata for a region free application, with black icons.
+    fn default() -> Self {
+        Self {
+            version: 0,
+            titles: Default::default(),
+            age_ratings: [AgeRating::UNSET; RATING_COUNT],
+            region_lockout: RegionLockout::REGION_FREE,
+            match_maker_id: 0,
+            match_maker_bit_id: 0,
+            flags: Flags::VISIBLE | Flags::RECORD_USAGE,
+            eula_version: (0, 0),
+            optimal_animation_frame: 0.0,
+            cec_id: 0,
+            small_icon: vec![0; SMALL_ICON_SIZE * SMALL_ICON_SIZE],
+            large_icon: vec![0; LARGE_ICON_SIZE * LARGE_ICON_SIZE],
+        }
+    }
+}
+
+fn decode_icon(data: &[u8], size: usize) -> Vec<u16> {
+    let mut linear = vec![0; PixelSize::Bits16.image_len(size, size)];
+    tiling::tiled_to_linear(data, &mut linear, size, size, PixelSize::Bits16);
+
+    linear
+        .chunks_exact(2)
+        .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]))
+        .collect()
+}
+
+fn encode_icon(pixels: &[u16], size: usize, data: &mut [u8]) {
+    let linear: Vec<u8> = pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_le_bytes())
+        .collect();
+
+    tiling::linear_to_tiled(&linear, data, size, size, PixelSize::Bits16);
+}
+
+// Decode a NUL-terminated (or field-filling) UTF-16 string.
+fn decode_utf16(data: &[u8]) -> String {
+    let units = data
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .take_while(|&unit| unit != 0);
+
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn encode_utf16(string: &str, field: &mut [u8]) -> io::Result<()> {
+    if string.encode_utf16().count() * 2 > field.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("SMDH title {string:?} is too long"),
+        ));
+    }
+
+    for (unit, bytes) in string.encode_utf16().zip(field.chunks_exact_mut(2)) {
+        bytes.copy_from_slice(&unit.to_le_bytes());
+    }
+
+    Ok(())
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Smdh {
+        let mut smdh = Smdh {
+            region_lockout: RegionLockout::EUROPE | RegionLockout::AUSTRALIA,
+            match_maker_bit_id: 0x0123_4567_89AB_CDEF,
+            flags: Flags::VISIBLE | Flags::ALLOW_3D | Flags::USES_SAVE_DATA,
+            eula_version: (1, 2),
+            optimal_animation_frame: 12.5,
+            cec_id: 0xDEAD,
+            small_icon: (0..576).map(|i| i as u16 * 7).collect(),
+            large_icon: (0..2304).map(|i| i as u16 * 13).collect(),
+            ..Default::default()
+        };
+
+        *smdh.title_mut(Language::English) = ApplicationTitle {
+            short_description: "Example".to_owned(),
+            long_description: "Example application".to_owned(),
+            publisher: "Rust3DS".to_owned(),
+        };
+        smdh.title_mut(Language::Japanese).short_description = "サンプル".to_owned();
+        smdh.set_age_rating(RatingBoard::PegiGen, AgeRating::new(7));
+        smdh.set_age_rating(RatingBoard::Esrb, AgeRating::pending());
+
+        smdh
+    }
+
+    #[test]
+    fn round_trip() {
+        let smdh = sample();
+        let data = smdh.to_bytes().unwrap();
+
+        assert_eq!(data.len(), SMDH_LEN);
+        assert_eq!(&data[..4], b"SMDH");
+
+        let parsed = Smdh::parse(&data).unwrap();
+        assert_eq!(parsed, smdh);
+        assert_eq!(parsed.to_bytes().unwrap(), data);
+
+        assert_eq!(
+            parsed.title(Language::Japanese).short_description,
+            "サンプル"
+        );
+        assert_eq!(parsed.age_rating(RatingBoard::PegiGen).age(), Some(7));
+        assert!(parsed.age_rating(RatingBoard::Esrb).is_pending());
+        assert_eq!(parsed.age_rating(RatingBoard::Cero).age(), None);
+        assert!(parsed.region_lockout.allows(Region::Europe));
+        assert!(!parsed.region_lockout.allows(Region::Japan));
+    }
+
+    #[test]
+    fn icon_tiling() {
+        let data = sample().to_bytes().unwrap();
+        let pixel = |index: usize| read_u16(&data, SMALL_ICON_OFFSET + 2 * index);
+
+        // The first tile stores its pixels in Z order.
+        assert_eq!(pixel(0), 0);
+        assert_eq!(pixel(1), 7);
+        assert_eq!(pixel(2), 24 * 7);
+        assert_eq!(pixel(3), 25 * 7);
+        assert_eq!(pixel(4), 2 * 7);
+        assert_eq!(pixel(63), (7 * 24 + 7) * 7);
+
+        // The second tile starts at x = 8.
+        assert_eq!(pixel(64), 8 * 7);
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(Smdh::parse(&[0; 16]).is_err());
+        assert!(Smdh::parse(&vec![0; SMDH_LEN]).is_err());
+
+        let mut smdh = sample();
+        smdh.titles[0].short_description = "a".repeat(SHORT_DESCRIPTION_LEN + 1);
+        assert!(smdh.to_bytes().is_err());
+
+        let mut smdh = sample();
+        smdh.large_icon.pop();
+        assert!(smdh.to_bytes().is_err());
+    }
+}
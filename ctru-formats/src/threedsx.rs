@@ -0,0 +1,464 @@
+//! 3DSX executable parsing.
+//!
+//! 3DSX is the executable format used by homebrew applications. Other than the code, read-only data and data segments
+//! (with the relocations the loader applies to them), a 3DSX file may have an extended header pointing to an embedded
+//! [SMDH](crate::smdh) and [RomFS](crate::romfs) image.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru_formats::cfg::Language;
+//! use ctru_formats::threedsx::ThreeDsx;
+//!
+//! for entry in std::fs::read_dir("target/armv6k-nintendo-3ds/release")? {
+//!     let path = entry?.path();
+//!
+//!     if path.extension().map_or(false, |ext| ext == "3dsx") {
+//!         let mut executable = ThreeDsx::new(std::fs::File::open(&path)?)?;
+//!
+//!         if let Some(smdh) = executable.smdh()? {
+//!             println!("{}", smdh.title(Language::English).short_description);
+//!         }
+//!     }
+//! }
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::romfs::RomFsImage;
+use super::smdh::Smdh;
+use crate::bytes::{invalid_data, read_u16, read_u32, read_vec};
+
+const MAGIC: [u8; 4] = *b"3DSX";
+
+// Size of the header without the extended header.
+const HEADER_LEN: u16 = 0x20;
+
+// Size of the header including the extended header.
+const EXTENDED_HEADER_LEN: u16 = 0x2C;
+
+// Minimum size of a relocation header: the absolute and relative relocation counts.
+const RELOC_HEADER_LEN: u16 = 0x8;
+
+/// Segment of a 3DSX executable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// Executable code.
+    Code = 0,
+    /// Read-only data.
+    Rodata = 1,
+    /// Read-write data, followed by the BSS.
+    Data = 2,
+}
+
+impl Segment {
+    const ALL: [Segment; 3] = [Segment::Code, Segment::Rodata, Segment::Data];
+}
+
+/// A relocation applied by the 3DSX loader.
+///
+/// Relocations of a segment are applied in sequence over its words: the loader skips `skip` words, then patches the following `patch` words.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Relocation {
+    /// Number of words to skip.
+    pub skip: u16,
+    /// Number of words to patch.
+    pub patch: u16,
+}
+
+/// Relocations applied to one [`Segment`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Relocations {
+    /// Relocations replaced with absolute addresses.
+    pub absolute: Vec<Relocation>,
+    /// Relocations replaced with addresses relative to the patched word.
+    pub relative: Vec<Relocation>,
+}
+
+/// Location of the SMDH and RomFS image embedded in a 3DSX file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtendedHeader {
+    /// Offset of the SMDH from the start of the file.
+    pub smdh_offset: u32,
+    /// Size of the SMDH in bytes.
+    pub smdh_size: u32,
+    /// Offset of the RomFS image from the start of the file, or 0 if there is none.
+    pub romfs_offset: u32,
+}
+
+/// A 3DSX executable.
+pub struct ThreeDsx<R> {
+    reader: R,
+    start: u64,
+    header_len: u16,
+    reloc_header_len: u16,
+    version: u32,
+    flags: u32,
+    segment_sizes: [u32; 3],
+    bss_size: u32,
+    extended_header: Option<ExtendedHeader>,
+    relocations: [Relocations; 3],
+}
+
+impl<R: Read + Seek> ThreeDsx<R> {
+    /// Parse the 3DSX file starting at the current position of `reader`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error of kind [`io::ErrorKind::InvalidData`] if the file isn't a valid 3DSX file.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut header = [0; EXTENDED_HEADER_LEN as usize];
+        reader.read_exact(&mut header[..HEADER_LEN as usize])?;
+
+        if header[..4] != MAGIC {
+            return Err(invalid_data("not a 3DSX file"));
+        }
+
+        let header_len = read_u16(&header, 0x04);
+        let reloc_header_len = read_u16(&header, 0x06);
+        if header_len < HEADER_LEN || reloc_header_len < RELOC_HEADER_LEN {
+            return Err(invalid_data("invalid 3DSX header size"));
+        }
+
+        let extended_header = if header_len >= EXTENDED_HEADER_LEN {
+            reader.read_exact(&mut header[HEADER_LEN as usize..])?;
+
+            Some(ExtendedHeader {
+                smdh_offset: read_u32(&header, 0x20),
+                smdh_size: read_u32(&header, 0x24),
+                romfs_offset: read_u32(&header, 0x28),
+            })
+        } else {
+            None
+        };
+
+        let segment_sizes = [
+            read_u32(&header, 0x10),
+            read_u32(&header, 0x14),
+            read_u32(&header, 0x18),
+        ];
+        let bss_size = read_u32(&header, 0x1C);
+        if bss_size > segment_sizes[Segment::Data as usize] {
+            return Err(invalid_data("3DSX BSS is larger than the data segment"));
+        }
+
+        let mut this = Self {
+            reader,
+            start,
+            header_len,
+            reloc_header_len,
+            version: read_u32(&header, 0x08),
+            flags: read_u32(&header, 0x0C),
+            segment_sizes,
+            bss_size,
+            extended_header,
+            relocations: Default::default(),
+        };
+
+        this.read_relocations()?;
+
+        Ok(this)
+    }
+
+    /// Read the contents of a segment, as stored in the file.
+    ///
+    /// The BSS isn't stored in the file, so it isn't part of the returned data segment.
+    pub fn read_segment(&mut self, segment: Segment) -> io::Result<Vec<u8>> {
+        let offset = self.segment_offset(segment);
+        let size = self.file_size(segment);
+
+        self.reader.seek(SeekFrom::Start(offset))?;
+        read_vec(&mut self.reader, size)
+    }
+
+    /// Read the embedded SMDH, if the file has one.
+    pub fn smdh(&mut self) -> io::Result<Option<Smdh>> {
+        let Some(extended_header) = self.extended_header else {
+            return Ok(None);
+        };
+
+        if extended_header.smdh_size == 0 {
+            return Ok(None);
+        }
+
+        self.reader.seek(SeekFrom::Start(
+            self.start + u64::from(extended_header.smdh_offset),
+        ))?;
+        let data = read_vec(&mut self.reader, u64::from(extended_header.smdh_size))?;
+
+        Smdh::parse(&data).map(Some)
+    }
+
+    /// Open the embedded RomFS image, if the file has one.
+    pub fn into_romfs(mut self) -> io::Result<Option<RomFsImage<R>>> {
+        let romfs_offset = match self.extended_header {
+            Some(ExtendedHeader { romfs_offset, .. }) if romfs_offset != 0 => romfs_offset,
+            _ => return Ok(None),
+        };
+
+        self.reader
+            .seek(SeekFrom::Start(self.start + u64::from(romfs_offset)))?;
+
+        RomFsImage::new(self.reader).map(Some)
+    }
+
+    // Read the relocation tables, which are stored after the segments.
+    fn read_relocations(&mut self) -> io::Result<()> {
+        let mut counts = [[0; 2]; 3];
+
+        for segment in Segment::ALL {
+            let mut reloc_header = [0; RELOC_HEADER_LEN as usize];
+            self.reader.seek(SeekFrom::Start(
+                self.start
+                    + u64::from(self.header_len)
+                    + u64::from(self.reloc_header_len) * segment as u64,
+            ))?;
+            self.reader.read_exact(&mut reloc_header)?;
+
+            counts[segment as usize] = [read_u32(&reloc_header, 0), read_u32(&reloc_header, 4)];
+        }
+
+        let tables_offset = self.segment_offset(Segment::Data) + self.file_size(Segment::Data);
+        self.reader.seek(SeekFrom::Start(tables_offset))?;
+
+        for (segment, [absolute, relative]) in counts.into_iter().enumerate() {
+            self.relocations[segment] = Relocations {
+                absolute: read_relocation_table(&mut self.reader, absolute)?,
+                relative: read_relocation_table(&mut self.reader, relative)?,
+            };
+        }
+
+        Ok(())
+    }
+
+    // Offset of a segment's data from the start of the reader.
+    fn segment_offset(&self, segment: Segment) -> u64 {
+        let preceding: u64 = Segment::ALL[..segment as usize]
+            .iter()
+            .map(|&segment| self.file_size(segment))
+            .sum();
+
+        self.start + u64::from(self.header_len) + 3 * u64::from(self.reloc_header_len) + preceding
+    }
+
+    // Size of a segment's data within the file.
+    fn file_size(&self, segment: Segment) -> u64 {
+        let size = self.segment_sizes[segment as usize];
+
+        match segment {
+            Segment::Data => u64::from(size - self.bss_size),
+            _ => u64::from(size),
+        }
+    }
+}
+
+impl<R> ThreeDsx<R> {
+    /// Returns the version of the 3DSX format.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the flags of the executable.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Returns the size in bytes of a segment once loaded into memory.
+    ///
+    /// For [`Segment::Data`], this includes the BSS.
+    pub fn segment_size(&self, segment: Segment) -> u32 {
+        self.segment_sizes[segment as usize]
+    }
+
+    /// Returns the size of the BSS in bytes.
+    pub fn bss_size(&self) -> u32 {
+        self.bss_size
+    }
+
+    /// Returns the relocations applied to a segment.
+    pub fn relocations(&self, segment: Segment) -> &Relocations {
+        &self.relocations[segment as usize]
+    }
+
+    /// Returns the extended header, if the file has one.
+    pub fn extended_header(&self) -> Option<ExtendedHeader> {
+        self.extended_header
+    }
+
+    /// Consume the executable, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn read_relocation_table(reader: &mut impl Read, count: u32) -> io::Result<Vec<Relocation>> {
+    // Each relocation takes 4 bytes. The size is computed in 64 bits, so it can't overflow on 32-bit targets.
+    let table = read_vec(reader, u64::from(count) * 4)?;
+
+    Ok(table
+        .chunks_exact(4)
+        .map(|entry| Relocation {
+            skip: read_u16(entry, 0),
+            patch: read_u16(entry, 2),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::cfg::Language;
+    use crate::romfs::RomFsBuilder;
+
+    fn push_u16(data: &mut Vec<u8>, value: u16) {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn push_u32(data: &mut Vec<u8>, value: u32) {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    // Build a 3DSX file with 8 bytes of code, 4 of rodata and 4 of data (+ 12 of BSS).
+    fn sample(extended: bool) -> Vec<u8> {
+        let mut smdh = Smdh::default();
+        smdh.title_mut(Language::English).short_description = "Homebrew".to_owned();
+        let smdh = smdh.to_bytes().unwrap();
+
+        let mut romfs = RomFsBuilder::new();
+        romfs.add_file("/hello.txt", b"hello".to_vec()).unwrap();
+        let romfs = romfs.build().unwrap();
+
+        let header_len = if extended {
+            EXTENDED_HEADER_LEN
+        } else {
+            HEADER_LEN
+        };
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"3DSX");
+        push_u16(&mut data, header_len);
+        push_u16(&mut data, RELOC_HEADER_LEN);
+        push_u32(&mut data, 0);
+        push_u32(&mut data, 0);
+        push_u32(&mut data, 8);
+        push_u32(&mut data, 4);
+        push_u32(&mut data, 16);
+        push_u32(&mut data, 12);
+
+        let extended_header_offset = data.len();
+        if extended {
+            data.resize(data.len() + 12, 0);
+        }
+
+        // Relocation headers: code has 1 absolute and 1 relative relocation, data has 1 absolute one.
+        for counts in [[1, 1], [0, 0], [1, 0]] {
+            push_u32(&mut data, counts[0]);
+            push_u32(&mut data, counts[1]);
+        }
+
+        data.extend_from_slice(&[0xC0; 8]);
+        data.extend_from_slice(&[0xD0; 4]);
+        data.extend_from_slice(&[0xE0; 4]);
+
+        for (skip, patch) in [(0, 1), (1, 1), (0, 1)] {
+            push_u16(&mut data, skip);
+            push_u16(&mut data, patch);
+        }
+
+        if extended {
+            let smdh_offset = data.len() as u32;
+            data.extend_from_slice(&smdh);
+            let romfs_offset = data.len() as u32;
+            data.extend_from_slice(&romfs);
+
+            let extended_header = &mut data[extended_header_offset..][..12];
+            extended_header[0..4].copy_from_slice(&smdh_offset.to_le_bytes());
+            extended_header[4..8].copy_from_slice(&(smdh.len() as u32).to_le_bytes());
+            extended_header[8..12].copy_from_slice(&romfs_offset.to_le_bytes());
+        }
+
+        data
+    }
+
+    #[test]
+    fn segments_and_relocations() {
+        let mut executable = ThreeDsx::new(Cursor::new(sample(false))).unwrap();
+
+        assert_eq!(executable.segment_size(Segment::Code), 8);
+        assert_eq!(executable.segment_size(Segment::Rodata), 4);
+        assert_eq!(executable.segment_size(Segment::Data), 16);
+        assert_eq!(executable.bss_size(), 12);
+        assert_eq!(executable.extended_header(), None);
+
+        assert_eq!(executable.read_segment(Segment::Code).unwrap(), [0xC0; 8]);
+        assert_eq!(executable.read_segment(Segment::Rodata).unwrap(), [0xD0; 4]);
+        assert_eq!(executable.read_segment(Segment::Data).unwrap(), [0xE0; 4]);
+
+        let code = executable.relocations(Segment::Code);
+        assert_eq!(code.absolute, [Relocation { skip: 0, patch: 1 }]);
+        assert_eq!(code.relative, [Relocation { skip: 1, patch: 1 }]);
+        assert_eq!(
+            executable.relocations(Segment::Rodata),
+            &Relocations::default()
+        );
+        assert_eq!(
+            executable.relocations(Segment::Data).absolute,
+            [Relocation { skip: 0, patch: 1 }]
+        );
+
+        assert!(executable.smdh().unwrap().is_none());
+        assert!(executable.into_romfs().unwrap().is_none());
+    }
+
+    #[test]
+    fn extended_header() {
+        let mut executable = ThreeDsx::new(Cursor::new(sample(true))).unwrap();
+
+        assert_eq!(executable.read_segment(Segment::Data).unwrap(), [0xE0; 4]);
+
+        let smdh = executable.smdh().unwrap().unwrap();
+        assert_eq!(smdh.title(Language::English).short_description, "Homebrew");
+
+        let mut romfs = executable.into_romfs().unwrap().unwrap();
+        assert_eq!(romfs.read("/hello.txt").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(ThreeDsx::new(Cursor::new(b"3DSY".repeat(16))).is_err());
+
+        let mut data = sample(false);
+        data.truncate(data.len() - 1);
+        assert!(ThreeDsx::new(Cursor::new(data)).is_err());
+
+        // Sizes larger than the file fail without allocating them.
+        let mut data = sample(false);
+        data[0x20..0x24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            ThreeDsx::new(Cursor::new(data)).err().unwrap().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let mut data = sample(false);
+        data[0x10..0x14].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(ThreeDsx::new(Cursor::new(data)).is_err());
+
+        let mut data = sample(true);
+        data[0x24..0x28].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut executable = ThreeDsx::new(Cursor::new(data)).unwrap();
+        assert_eq!(
+            executable.smdh().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
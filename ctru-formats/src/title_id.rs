@@ -0,0 +1,263 @@
+//! Typed title IDs.
+
+use std::fmt;
+use std::str::FromStr;
+
+// Category of updates (patches).
+const CATEGORY_UPDATE: u16 = 0x000E;
+
+// Category of downloadable contents.
+const CATEGORY_DLC: u16 = 0x008C;
+
+// Category flag set for system titles.
+const CATEGORY_SYSTEM_FLAG: u16 = 0x0010;
+
+// Category flag set for DSiWare (TWL) titles.
+const CATEGORY_TWL_FLAG: u16 = 0x8000;
+
+/// Platform a title was made for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Platform {
+    /// Nintendo 3DS.
+    Ctr,
+    /// Any other platform.
+    Other(u16),
+}
+
+impl Platform {
+    fn from_raw(raw: u16) -> Self {
+        match raw {
+            0x0004 => Self::Ctr,
+            raw => Self::Other(raw),
+        }
+    }
+
+    fn into_raw(self) -> u16 {
+        match self {
+            Self::Ctr => 0x0004,
+            Self::Other(raw) => raw,
+        }
+    }
+}
+
+/// Kind of a title, as encoded in its [`TitleId`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// Regular application.
+    Application,
+    /// Child application sent through Download Play.
+    DownloadPlayChild,
+    /// Demo application.
+    Demo,
+    /// Update (patch) of an application.
+    Update,
+    /// Downloadable content of an application.
+    Dlc,
+    /// System title (application, applet, module or data archive).
+    System,
+    /// DSiWare title.
+    DsiWare,
+    /// Any other kind of title.
+    Other,
+}
+
+/// Identifier of a title.
+///
+/// Title IDs are made of 4 parts, from the most to the least significant bits:
+/// the platform (16 bits), the category (16 bits), the unique ID (24 bits) and the variation (8 bits).
+/// Titles related to the same application, such as its updates and DLC, share the same unique ID.
+///
+/// Title IDs are usually written as 16 hexadecimal digits, which is the format used by [`Display`](fmt::Display) and [`FromStr`].
+///
+/// # Example
+///
+/// ```
+/// use ctru_formats::title_id::{Category, TitleId};
+///
+/// let id: TitleId = "000400000F700E00".parse().unwrap();
+///
+/// assert_eq!(id.category(), Category::Application);
+/// assert_eq!(id.unique_id(), 0x0F700E);
+/// assert_eq!(id.update_id().to_string(), "0004000E0F700E00");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TitleId(u64);
+
+impl TitleId {
+    /// Create a title ID from its raw value.
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Create a title ID from its parts.
+    ///
+    /// Only the lower 24 bits of `unique_id` are used.
+    pub fn from_parts(platform: Platform, category: u16, unique_id: u32, variation: u8) -> Self {
+        Self(
+            (u64::from(platform.into_raw()) << 48)
+                | (u64::from(category) << 32)
+                | (u64::from(unique_id & 0xFF_FFFF) << 8)
+                | u64::from(variation),
+        )
+    }
+
+    /// Returns the raw value of this title ID.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the platform of the title.
+    pub fn platform(self) -> Platform {
+        Platform::from_raw((self.0 >> 48) as u16)
+    }
+
+    /// Returns the raw category of the title, which is a combination of flags.
+    pub fn category_raw(self) -> u16 {
+        (self.0 >> 32) as u16
+    }
+
+    /// Returns the kind of the title.
+    pub fn category(self) -> Category {
+        match self.category_raw() {
+            0x0000 => Category::Application,
+            0x0001 => Category::DownloadPlayChild,
+            0x0002 => Category::Demo,
+            CATEGORY_UPDATE => Category::Update,
+            CATEGORY_DLC => Category::Dlc,
+            raw if raw & CATEGORY_TWL_FLAG != 0 => Category::DsiWare,
+            raw if raw & CATEGORY_SYSTEM_FLAG != 0 => Category::System,
+            _ => Category::Other,
+        }
+    }
+
+    /// Returns the unique ID of the title, shared by the titles related to the same application.
+    pub fn unique_id(self) -> u32 {
+        ((self.0 >> 8) & 0xFF_FFFF) as u32
+    }
+
+    /// Returns the variation of the title.
+    pub fn variation(self) -> u8 {
+        self.0 as u8
+    }
+
+    /// Returns the ID of the application with the same unique ID.
+    pub fn application_id(self) -> Self {
+        self.with_category(0x0000)
+    }
+
+    /// Returns the ID of the update with the same unique ID.
+    pub fn update_id(self) -> Self {
+        self.with_category(CATEGORY_UPDATE)
+    }
+
+    /// Returns the ID of the DLC with the same unique ID.
+    pub fn dlc_id(self) -> Self {
+        self.with_category(CATEGORY_DLC)
+    }
+
+    fn with_category(self, category: u16) -> Self {
+        Self((self.0 & !(0xFFFF << 32)) | (u64::from(category) << 32))
+    }
+}
+
+impl From<u64> for TitleId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<TitleId> for u64 {
+    fn from(id: TitleId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for TitleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016X}", self.0)
+    }
+}
+
+impl fmt::LowerHex for TitleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for TitleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+/// Error returned when parsing a [`TitleId`] which isn't made of 16 hexadecimal digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTitleIdError(());
+
+impl fmt::Display for ParseTitleIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "title IDs must be made of 16 hexadecimal digits")
+    }
+}
+
+impl std::error::Error for ParseTitleIdError {}
+
+impl FromStr for TitleId {
+    type Err = ParseTitleIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 16 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseTitleIdError(()));
+        }
+
+        u64::from_str_radix(s, 16)
+            .map(Self)
+            .map_err(|_| ParseTitleIdError(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts() {
+        let id = TitleId::new(0x0004_0000_0F70_0E01);
+
+        assert_eq!(id.platform(), Platform::Ctr);
+        assert_eq!(id.category(), Category::Application);
+        assert_eq!(id.unique_id(), 0x0F700E);
+        assert_eq!(id.variation(), 0x01);
+        assert_eq!(TitleId::from_parts(Platform::Ctr, 0, 0x0F700E, 0x01), id);
+
+        assert_eq!(id.update_id().category(), Category::Update);
+        assert_eq!(id.dlc_id(), TitleId::new(0x0004_008C_0F70_0E01));
+        assert_eq!(id.dlc_id().application_id(), id);
+
+        assert_eq!(
+            TitleId::new(0x0004_0010_0002_0000).category(),
+            Category::System
+        );
+        assert_eq!(
+            TitleId::new(0x0004_8004_4B44_4A00).category(),
+            Category::DsiWare
+        );
+        assert_eq!(
+            TitleId::new(0x0005_0000_1010_1000).platform(),
+            Platform::Other(5)
+        );
+    }
+
+    #[test]
+    fn formatting() {
+        let id = TitleId::new(0x0004_000E_0F70_0E00);
+
+        assert_eq!(id.to_string(), "0004000E0F700E00");
+        assert_eq!(format!("{id:x}"), "4000e0f700e00");
+        assert_eq!("0004000e0F700E00".parse(), Ok(id));
+
+        assert!("4000E0F700E00".parse::<TitleId>().is_err());
+        assert!("0x04000E0F700E00".parse::<TitleId>().is_err());
+        assert!("+004000E0F700E00".parse::<TitleId>().is_err());
+    }
+}
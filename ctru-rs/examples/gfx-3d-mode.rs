@@ -3,7 +3,17 @@
 //! This example showcases 3D mode rendering (using the CPU).
 //! In a normal application, all rendering should be handled via the GPU.
 //!
-//! See `gfx-bitmap.rs` for details on how the image is generated.
+//! Ferris image taken from <https://rustacean.net> and scaled down to 320x240px.
+//! To regenerate the data, you will need to install `imagemagick` and run this
+//! command from the `examples` directory:
+//!
+//! ```sh
+//! magick assets/ferris.png -channel-fx "red<=>blue" -rotate 90 assets/ferris.rgb
+//! ```
+//!
+//! This creates an image appropriate for the default frame buffer format of
+//! [`Bgr8`](ctru::services::gspgpu::FramebufferFormat::Bgr8)
+//! and rotates the image 90° to account for the portrait mode screen.
 //!
 //! # Warning
 //!
@@ -2,20 +2,11 @@
 ///
 /// This example uses the CPU to render a simple bitmap image to the screen.
 use ctru::prelude::*;
+use ctru::services::gfx::image::Image;
 use ctru::services::gfx::{Flush, Screen, Swap};
 
 /// Ferris image taken from <https://rustacean.net> and scaled down to 320x240px.
-/// To regenerate the data, you will need to install `imagemagick` and run this
-/// command from the `examples` directory:
-///
-/// ```sh
-/// magick assets/ferris.png -channel-fx "red<=>blue" -rotate 90 assets/ferris.rgb
-/// ```
-///
-/// This creates an image appropriate for the default frame buffer format of
-/// [`Bgr8`](ctru::services::gspgpu::FramebufferFormat::Bgr8)
-/// and rotates the image 90° to account for the portrait mode screen.
-static IMAGE: &[u8] = include_bytes!("assets/ferris.rgb");
+static IMAGE: &[u8] = include_bytes!("assets/ferris.png");
 
 fn main() {
     let gfx = Gfx::new().expect("Couldn't obtain GFX controller");
@@ -34,20 +25,22 @@ fn main() {
     // Swapping buffers commits the change from the line above.
     bottom_screen.swap_buffers();
 
-    // 3 bytes per pixel, we just want to reverse the pixels but not individual bytes
-    let flipped_image: Vec<_> = IMAGE.chunks(3).rev().flatten().copied().collect();
+    // The image is decoded in the format of the framebuffer, and rotated 90° to account for the portrait mode screen.
+    let ferris = Image::decode(IMAGE, bottom_screen.framebuffer_format())
+        .expect("Couldn't decode the image");
+    let image = ferris.as_bytes();
 
-    let mut image_bytes = IMAGE;
+    // We just want to reverse the pixels but not individual bytes.
+    let pixel_size = bottom_screen.framebuffer_format().pixel_depth_bytes();
+    let flipped_image: Vec<_> = image.chunks(pixel_size).rev().flatten().copied().collect();
 
-    // We assume the image is the correct size already, so we drop width + height.
-    let frame_buffer = bottom_screen.raw_framebuffer();
+    let mut image_bytes = image;
 
-    // We copy the image to the framebuffer.
-    unsafe {
-        frame_buffer
-            .ptr
-            .copy_from(image_bytes.as_ptr(), image_bytes.len());
-    }
+    // We assume the image is the correct size already, and copy it to the framebuffer.
+    bottom_screen
+        .surface()
+        .as_bytes_mut()
+        .copy_from_slice(image_bytes);
 
     while apt.main_loop() {
         hid.scan_input();
@@ -57,20 +50,17 @@ fn main() {
         }
 
         if hid.keys_down().contains(KeyPad::A) {
-            image_bytes = if std::ptr::eq(image_bytes, IMAGE) {
+            image_bytes = if std::ptr::eq(image_bytes, image) {
                 &flipped_image[..]
             } else {
-                IMAGE
+                image
             };
 
-            let frame_buffer = bottom_screen.raw_framebuffer();
-
             // We render the newly switched image to the framebuffer.
-            unsafe {
-                frame_buffer
-                    .ptr
-                    .copy_from(image_bytes.as_ptr(), image_bytes.len());
-            }
+            bottom_screen
+                .surface()
+                .as_bytes_mut()
+                .copy_from_slice(image_bytes);
         }
 
         // Flush framebuffers. Since we're not using double buffering,
@@ -0,0 +1,100 @@
+//! Embedded Graphics example.
+//!
+//! This example uses the [`embedded-graphics`](https://docs.rs/embedded-graphics) crate to draw shapes and text on the screens.
+//! It requires the `embedded-graphics` feature of `ctru-rs`.
+
+use ctru::prelude::*;
+use ctru::services::gfx::{Flush, Screen, Swap};
+use ctru::services::gspgpu::FramebufferFormat;
+
+use embedded_graphics::mono_font::ascii::FONT_10X20;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, PrimitiveStyle, Rectangle, Triangle};
+use embedded_graphics::text::{Alignment, Text};
+
+fn main() {
+    let apt = Apt::new().expect("Couldn't obtain APT controller");
+    let mut hid = Hid::new().expect("Couldn't obtain HID controller");
+    let gfx = Gfx::new().expect("Couldn't obtain GFX controller");
+
+    let mut top_screen = gfx.top_screen.borrow_mut();
+    let mut bottom_screen = gfx.bottom_screen.borrow_mut();
+
+    // Drawing works with any framebuffer format.
+    bottom_screen.set_framebuffer_format(FramebufferFormat::Rgb565);
+
+    // We don't need double buffering in this example.
+    // In this way we can draw our shapes only once on screen.
+    top_screen.set_double_buffering(false);
+    bottom_screen.set_double_buffering(false);
+    // Swapping buffers commits the changes from the lines above.
+    top_screen.swap_buffers();
+    bottom_screen.swap_buffers();
+
+    draw_shapes(&mut *top_screen).unwrap();
+    draw_text(&mut *bottom_screen).unwrap();
+
+    while apt.main_loop() {
+        hid.scan_input();
+
+        if hid.keys_down().contains(KeyPad::START) {
+            break;
+        }
+
+        // Flush framebuffers. Since we're not using double buffering,
+        // this will render the pixels immediately
+        top_screen.flush_buffers();
+        bottom_screen.flush_buffers();
+
+        gfx.wait_for_vblank();
+    }
+}
+
+fn draw_shapes<D: DrawTarget<Color = Rgb888>>(target: &mut D) -> Result<(), D::Error> {
+    target.clear(Rgb888::BLACK)?;
+
+    Rectangle::new(Point::new(20, 20), Size::new(120, 80))
+        .into_styled(PrimitiveStyle::with_fill(Rgb888::RED))
+        .draw(target)?;
+
+    Circle::new(Point::new(160, 60), 120)
+        .into_styled(PrimitiveStyle::with_stroke(Rgb888::GREEN, 4))
+        .draw(target)?;
+
+    Triangle::new(
+        Point::new(300, 200),
+        Point::new(380, 200),
+        Point::new(340, 130),
+    )
+    .into_styled(PrimitiveStyle::with_fill(Rgb888::BLUE))
+    .draw(target)?;
+
+    Ok(())
+}
+
+fn draw_text<D: DrawTarget<Color = Rgb888>>(target: &mut D) -> Result<(), D::Error> {
+    target.clear(Rgb888::WHITE)?;
+
+    let style = MonoTextStyle::new(&FONT_10X20, Rgb888::BLACK);
+    let center = target.bounding_box().center();
+
+    Text::with_alignment("Hello from", center, style, Alignment::Center).draw(target)?;
+    Text::with_alignment(
+        "embedded-graphics!",
+        center + Point::new(0, 20),
+        style,
+        Alignment::Center,
+    )
+    .draw(target)?;
+    Text::with_alignment(
+        "Press Start to exit",
+        Point::new(160, 230),
+        style,
+        Alignment::Center,
+    )
+    .draw(target)?;
+
+    Ok(())
+}
@@ -6,10 +6,13 @@ use std::borrow::Cow;
 use std::error;
 use std::ffi::CStr;
 use std::fmt;
-use std::ops::{ControlFlow, FromResidual, Try};
+use std::ops::{ControlFlow, FromResidual, RangeInclusive, Try};
 
 use ctru_sys::result::{R_DESCRIPTION, R_LEVEL, R_MODULE, R_SUMMARY};
 
+// Range of the descriptions of `FS` module results reporting that an entry already exists.
+const FS_ALREADY_EXISTS: RangeInclusive<ctru_sys::Result> = 180..=199;
+
 /// Custom type alias for generic [`ctru-rs`](crate) operations.
 ///
 /// This type is compatible with [`ctru_sys::Result`] codes.
@@ -123,6 +126,25 @@ impl Error {
             _ => false,
         }
     }
+
+    /// Check if the error reports that the requested resource (e.g. a file or an archive) doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        match *self {
+            Error::Os(code) => R_SUMMARY(code) == ctru_sys::RS_NOTFOUND as ctru_sys::Result,
+            _ => false,
+        }
+    }
+
+    /// Check if the error reports that a file-system entry (e.g. a file or an archive) already exists.
+    pub fn is_already_exists(&self) -> bool {
+        match *self {
+            Error::Os(code) => {
+                R_MODULE(code) == ctru_sys::RM_FS as ctru_sys::Result
+                    && FS_ALREADY_EXISTS.contains(&R_DESCRIPTION(code))
+            }
+            _ => false,
+        }
+    }
 }
 
 impl From<ctru_sys::Result> for Error {
@@ -137,6 +159,20 @@ impl From<ResultCode> for Error {
     }
 }
 
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        let kind = if err.is_not_found() {
+            std::io::ErrorKind::NotFound
+        } else if err.is_already_exists() {
+            std::io::ErrorKind::AlreadyExists
+        } else {
+            std::io::ErrorKind::Other
+        };
+
+        std::io::Error::new(kind, err)
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -394,3 +430,39 @@ fn result_code_module_str(result: ctru_sys::Result) -> Cow<'static, str> {
         code => return Cow::Owned(format!("(unknown module: {code:#x})")),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ctru_sys::result::MAKERESULT;
+
+    fn fs_error(summary: u32, description: ctru_sys::Result) -> Error {
+        Error::Os(MAKERESULT(
+            ctru_sys::RL_STATUS as ctru_sys::Result,
+            summary as ctru_sys::Result,
+            ctru_sys::RM_FS as ctru_sys::Result,
+            description,
+        ))
+    }
+
+    #[test]
+    fn io_error_kinds() {
+        for (err, kind) in [
+            (
+                fs_error(ctru_sys::RS_NOTFOUND, 120),
+                std::io::ErrorKind::NotFound,
+            ),
+            (
+                fs_error(ctru_sys::RS_INVALIDSTATE, 190),
+                std::io::ErrorKind::AlreadyExists,
+            ),
+            (
+                fs_error(ctru_sys::RS_INVALIDSTATE, 230),
+                std::io::ErrorKind::Other,
+            ),
+            (Error::Other("not found".into()), std::io::ErrorKind::Other),
+        ] {
+            assert_eq!(std::io::Error::from(err).kind(), kind);
+        }
+    }
+}
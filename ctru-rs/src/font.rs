@@ -0,0 +1,91 @@
+//! System font access and text rendering.
+//!
+//! The console provides a shared font (in the CFNT format), covering the characters of the console's region,
+//! which can be used instead of the 8x8 font of the [`Console`](crate::console::Console) to display localized text.
+//! [`SystemFont::system()`] maps it into the process, and [`Font::parse()`] reads a CFNT (`.bcfnt`) file from any other source.
+//!
+//! The font parser and renderer live in the [`ctru-formats`](ctru_formats::cfnt) crate, which can also be used on the host.
+//!
+//! # Example
+//!
+//! ```
+//! # let _runner = test_runner::GdbRunner::default();
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! #
+//! use ctru::font::{Font, SystemFont};
+//! use ctru::services::gfx::pixel::Rgba8;
+//! use ctru::services::gfx::{Gfx, Screen};
+//!
+//! let gfx = Gfx::new()?;
+//! let font = Font::system()?;
+//!
+//! let mut bottom_screen = gfx.bottom_screen.borrow_mut();
+//! let mut surface = bottom_screen.surface();
+//!
+//! font.draw_text(&mut surface, "こんにちは、世界！", 10, 10, Rgba8::opaque(255, 255, 255));
+//! #
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, ResultCode};
+use crate::sealed::Sealed;
+
+pub use ctru_formats::cfnt::{CharWidth, Font, Glyph};
+
+// Size of the CFNT header, which holds the size of the whole font.
+const HEADER_LEN: usize = 0x14;
+
+/// Access to the shared system font.
+///
+/// This trait is sealed, and only implemented by [`Font`].
+pub trait SystemFont: Sealed + Sized {
+    /// Returns the system font, mapping it if needed.
+    ///
+    /// The system font stays mapped until the end of the process.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::font::{Font, SystemFont};
+    ///
+    /// let font = Font::system()?;
+    ///
+    /// println!("Line height: {}", font.line_feed());
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "fontEnsureMapped")]
+    #[doc(alias = "fontGetSystemFont")]
+    fn system() -> crate::Result<Self>;
+}
+
+impl SystemFont for Font {
+    fn system() -> crate::Result<Self> {
+        ResultCode(unsafe { ctru_sys::fontEnsureMapped() })?;
+
+        // `fontGetSystemFont()` is an inline function, so we read the font pointer it returns directly.
+        let font = unsafe { ctru_sys::g_sharedFont }.cast::<u8>();
+        if font.is_null() {
+            return Err(Error::Other("the system font isn't mapped".to_owned()));
+        }
+
+        // SAFETY: the font is mapped (and never unmapped) in shared memory, and holds as many bytes as given by its header.
+        let data: &'static [u8] = unsafe {
+            let header = std::slice::from_raw_parts(font, HEADER_LEN);
+            let len = u32::from_le_bytes(header[0x0C..0x10].try_into().unwrap());
+
+            std::slice::from_raw_parts(font, len as usize)
+        };
+
+        // The offsets within the system font are relocated to absolute addresses.
+        Font::parse_relocated(data, font as usize)
+            .map_err(|err| Error::Other(format!("invalid system font: {err}")))
+    }
+}
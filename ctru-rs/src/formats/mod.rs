@@ -0,0 +1,7 @@
+//! Parsers for the file formats used by the Nintendo 3DS.
+//!
+//! Unlike the [`services`](crate::services), these modules don't interact with the console's operating system.
+//! They are re-exported from the [`ctru-formats`](ctru_formats) crate, which doesn't depend on `libctru`,
+//! so it can also be used to inspect files on the host machine (e.g. to check the output of a build) or in build scripts.
+
+pub use ctru_formats::{cia, exefs, ncch, romfs, smdh, threedsx};
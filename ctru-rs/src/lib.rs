@@ -61,6 +61,8 @@ macro_rules! from_impl {
 pub mod applets;
 pub mod console;
 pub mod error;
+pub mod font;
+pub mod formats;
 pub mod linear;
 pub mod mii;
 pub mod os;
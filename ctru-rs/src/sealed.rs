@@ -3,6 +3,7 @@
 //! done in this crate.
 
 use crate::console::Console;
+use crate::font::Font;
 use crate::services::gfx::{BottomScreen, TopScreen, TopScreen3D, TopScreenLeft, TopScreenRight};
 
 pub trait Sealed {}
@@ -13,3 +14,4 @@ impl Sealed for TopScreenLeft {}
 impl Sealed for TopScreenRight {}
 impl Sealed for BottomScreen {}
 impl Sealed for Console<'_> {}
+impl Sealed for Font {}
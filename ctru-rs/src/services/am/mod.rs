@@ -0,0 +1,744 @@
+//! Application Manager service.
+//!
+//! As the name implies, the AM service manages installed applications. It can:
+//! - Read the installed applications on the console and their information (depending on the install location),
+//!   such as their contents, tickets and DLC.
+//! - List the titles whose installation is pending.
+//! - Install compatible applications (CIA files) to the console, with a [`CiaInstaller`].
+//! - Delete installed titles and tickets.
+#![doc(alias = "app")]
+#![doc(alias = "manager")]
+
+use crate::error::ResultCode;
+use crate::formats::cia::ContentType;
+use crate::formats::smdh::{Smdh, SMDH_LEN};
+use crate::services::fs::path::FsPath;
+use crate::services::fs::{ArchiveID, Fs, MediaType};
+use std::io;
+use std::marker::PhantomData;
+
+pub use ctru_formats::title_id::{Category, ParseTitleIdError, Platform, TitleId};
+
+/// Information about a content of an installed title.
+#[doc(alias = "AM_ContentInfo")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ContentInfo {
+    index: u16,
+    content_type: ContentType,
+    id: u32,
+    size: u64,
+    flags: u8,
+}
+
+impl ContentInfo {
+    /// Returns the index of this content within the title.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Returns the properties of this content.
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+
+    /// Returns the ID of this content.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the size of this content in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns `true` if this content has been downloaded.
+    pub fn is_downloaded(&self) -> bool {
+        self.flags & ctru_sys::AM_CONTENT_DOWNLOADED as u8 != 0
+    }
+
+    /// Returns `true` if the console owns the rights to use this content.
+    pub fn is_owned(&self) -> bool {
+        self.flags & ctru_sys::AM_CONTENT_OWNED as u8 != 0
+    }
+}
+
+impl From<ctru_sys::AM_ContentInfo> for ContentInfo {
+    fn from(info: ctru_sys::AM_ContentInfo) -> Self {
+        Self {
+            index: info.index,
+            content_type: ContentType::from_bits_retain(info.type_),
+            id: info.contentId,
+            size: info.size,
+            flags: info.flags,
+        }
+    }
+}
+
+/// Installation state of a [`PendingTitle`].
+#[doc(alias = "AM_InstallStatus")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstallStatus {
+    /// The installation was aborted.
+    Aborted,
+    /// The installation was saved, to be resumed later.
+    Saved,
+    /// The installation is in progress.
+    InProgress,
+    /// The title was installed, but the installation wasn't finalized.
+    AwaitingFinalization,
+    /// Unknown status.
+    Unknown(u16),
+}
+
+impl From<u16> for InstallStatus {
+    fn from(status: u16) -> Self {
+        match u32::from(status) {
+            ctru_sys::AM_STATUS_ABORTED => Self::Aborted,
+            ctru_sys::AM_STATUS_SAVED => Self::Saved,
+            ctru_sys::AM_STATUS_INSTALL_IN_PROGRESS => Self::InProgress,
+            ctru_sys::AM_STATUS_AWAITING_FINALIZATION => Self::AwaitingFinalization,
+            _ => Self::Unknown(status),
+        }
+    }
+}
+
+/// A title whose installation hasn't been completed.
+#[doc(alias = "AM_PendingTitleEntry")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PendingTitle {
+    id: TitleId,
+    version: u16,
+    status: InstallStatus,
+    title_type: u32,
+}
+
+impl PendingTitle {
+    /// Returns the ID of the title.
+    pub fn id(&self) -> TitleId {
+        self.id
+    }
+
+    /// Returns the version of the title being installed.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns the installation state of the title.
+    pub fn status(&self) -> InstallStatus {
+        self.status
+    }
+
+    /// Returns the type of the title.
+    pub fn title_type(&self) -> u32 {
+        self.title_type
+    }
+}
+
+/// General information about a specific title entry.
+#[doc(alias = "AM_TitleEntry")]
+pub struct Title<'a> {
+    id: TitleId,
+    mediatype: MediaType,
+    size: u64,
+    version: u16,
+    _am: PhantomData<&'a Am>,
+}
+
+impl<'a> Title<'a> {
+    /// Returns this title's ID.
+    pub fn id(&self) -> TitleId {
+        self.id
+    }
+
+    /// Returns this title's unique product code.
+    #[doc(alias = "AM_GetTitleProductCode")]
+    pub fn product_code(&self) -> String {
+        let mut buf: [u8; 16] = [0; 16];
+
+        // This operation is safe as long as the title was correctly obtained via [`Am::title_list()`].
+        unsafe {
+            let _ = ctru_sys::AM_GetTitleProductCode(
+                self.mediatype.into(),
+                self.id.get(),
+                buf.as_mut_ptr(),
+            );
+        }
+
+        String::from_utf8_lossy(&buf).to_string()
+    }
+
+    /// Returns the size of this title in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the installed version of this title.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Returns this title's media type
+    pub fn media_type(&self) -> MediaType {
+        self.mediatype
+    }
+
+    /// Returns information about the installed contents of this title.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let app_manager = Am::new()?;
+    ///
+    /// for title in app_manager.title_list(MediaType::Sd)? {
+    ///     let missing = title
+    ///         .content_infos()?
+    ///         .iter()
+    ///         .filter(|content| !content.is_downloaded())
+    ///         .count();
+    ///
+    ///     println!("{}: {missing} missing contents", title.id());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AMAPP_ListDLCContentInfos")]
+    pub fn content_infos(&self) -> crate::Result<Vec<ContentInfo>> {
+        content_infos(self.mediatype, self.id)
+    }
+
+    /// Returns information about the contents of the DLC of this title, or an empty list if it has no DLC installed.
+    ///
+    /// The DLC title ID is derived from this title's ID (see [`TitleId::dlc_id()`]).
+    pub fn dlc_content_infos(&self) -> crate::Result<Vec<ContentInfo>> {
+        let dlc_id = self.id.dlc_id();
+        let is_installed = title_ids(self.mediatype)?.contains(&dlc_id.get());
+
+        if !is_installed {
+            return Ok(Vec::new());
+        }
+
+        content_infos(self.mediatype, dlc_id)
+    }
+
+    /// Returns `true` if a ticket for this title is installed.
+    ///
+    /// Titles can't be launched without a ticket.
+    #[doc(alias = "AM_GetTicketList")]
+    pub fn has_ticket(&self) -> crate::Result<bool> {
+        Ok(ticket_ids()?.contains(&self.id))
+    }
+
+    /// Returns the SMDH (name, publisher, icons...) of this title, read from its ExeFS.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::cfgu::Language;
+    /// use ctru::services::fs::{Fs, MediaType};
+    ///
+    /// let app_manager = Am::new()?;
+    /// let fs = Fs::new()?;
+    ///
+    /// for title in app_manager.title_list(MediaType::Sd)? {
+    ///     let smdh = title.smdh(&fs)?;
+    ///     println!("{}", smdh.title(Language::English).short_description);
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_OpenFileDirectly")]
+    pub fn smdh(&self, _fs: &Fs) -> crate::Result<Smdh> {
+        let media_type: u32 = self.mediatype.into();
+
+        let mut archive_path = Vec::with_capacity(16);
+        archive_path.extend_from_slice(&(self.id.get() as u32).to_le_bytes());
+        archive_path.extend_from_slice(&((self.id.get() >> 32) as u32).to_le_bytes());
+        archive_path.extend_from_slice(&media_type.to_le_bytes());
+        archive_path.extend_from_slice(&0u32.to_le_bytes());
+        let archive_path = FsPath::binary(archive_path);
+
+        // ExeFS file named "icon", within the title's content.
+        let file_path: Vec<u8> = [0u32, 0, 2, u32::from_le_bytes(*b"icon"), 0]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        let file_path = FsPath::binary(file_path);
+
+        let mut handle = 0;
+        let mut data = vec![0; SMDH_LEN];
+        let mut read = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_OpenFileDirectly(
+                &mut handle,
+                ArchiveID::SaveDataAndContent.into(),
+                *archive_path.as_raw(),
+                *file_path.as_raw(),
+                ctru_sys::FS_OPEN_READ,
+                0,
+            ))?;
+
+            let result = ctru_sys::FSFILE_Read(
+                handle,
+                &mut read,
+                0,
+                data.as_mut_ptr().cast(),
+                SMDH_LEN as u32,
+            );
+            let _ = ctru_sys::FSFILE_Close(handle);
+
+            ResultCode(result)?;
+        }
+
+        data.truncate(read as usize);
+
+        Smdh::parse(&data).map_err(|e| crate::Error::Other(e.to_string()))
+    }
+}
+
+/// Streaming installation of a CIA file, started with [`Am::start_cia_install()`].
+///
+/// The CIA contents are written through the [`io::Write`] implementation, in order.
+/// Once everything has been written, the installation must be completed with [`CiaInstaller::finish()`].
+/// To report the installation progress, the total size of the file must be given with [`CiaInstaller::set_total_size()`].
+/// Dropping the installer before that cancels the installation, leaving the installed titles untouched.
+#[doc(alias = "AM_StartCiaInstall")]
+pub struct CiaInstaller<'a> {
+    handle: ctru_sys::Handle,
+    written: u64,
+    total_size: Option<u64>,
+    _am: PhantomData<&'a Am>,
+}
+
+impl CiaInstaller<'_> {
+    /// Set the total size of the CIA file, used to compute the [`progress()`](CiaInstaller::progress).
+    ///
+    /// The installer has no way to know the size of the CIA file on its own, so this must be called
+    /// before any progress can be reported.
+    pub fn set_total_size(&mut self, total_size: u64) {
+        self.total_size = Some(total_size);
+    }
+
+    /// Returns the amount of bytes written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns the installation progress, from 0.0 to 1.0.
+    ///
+    /// Returns `None` if the total size hasn't been set with [`CiaInstaller::set_total_size()`].
+    pub fn progress(&self) -> Option<f32> {
+        self.total_size.map(|total_size| {
+            if total_size == 0 {
+                1.0
+            } else {
+                (self.written as f64 / total_size as f64).min(1.0) as f32
+            }
+        })
+    }
+
+    /// Complete the installation, after the whole CIA file has been written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the written data isn't a complete and valid CIA file.
+    #[doc(alias = "AM_FinishCiaInstall")]
+    pub fn finish(self) -> crate::Result<()> {
+        let handle = self.handle;
+        std::mem::forget(self);
+
+        ResultCode(unsafe { ctru_sys::AM_FinishCiaInstall(handle) })?;
+
+        Ok(())
+    }
+
+    /// Abort the installation, discarding the data written so far.
+    #[doc(alias = "AM_CancelCIAInstall")]
+    pub fn cancel(self) -> crate::Result<()> {
+        let handle = self.handle;
+        std::mem::forget(self);
+
+        ResultCode(unsafe { ctru_sys::AM_CancelCIAInstall(handle) })?;
+
+        Ok(())
+    }
+
+    // Write (part of) `buf` at the current position, returning the amount of bytes written.
+    fn write_chunk(&mut self, buf: &[u8]) -> crate::Result<u32> {
+        let mut written = 0;
+        let size = buf.len().min(u32::MAX as usize) as u32;
+
+        ResultCode(unsafe {
+            ctru_sys::FSFILE_Write(
+                self.handle,
+                &mut written,
+                self.written,
+                buf.as_ptr().cast(),
+                size,
+                0,
+            )
+        })?;
+
+        Ok(written)
+    }
+}
+
+impl io::Write for CiaInstaller<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.write_chunk(buf).map_err(io::Error::from)?;
+
+        self.written += u64::from(written);
+
+        Ok(written as usize)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for CiaInstaller<'_> {
+    #[doc(alias = "AM_CancelCIAInstall")]
+    fn drop(&mut self) {
+        let _ = unsafe { ctru_sys::AM_CancelCIAInstall(self.handle) };
+    }
+}
+
+/// Handle to the Application Manager service.
+pub struct Am(());
+
+impl Am {
+    /// Initialize a new service handle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    ///
+    /// let app_manager = Am::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "amInit")]
+    pub fn new() -> crate::Result<Am> {
+        unsafe {
+            ResultCode(ctru_sys::amInit())?;
+            Ok(Am(()))
+        }
+    }
+
+    /// Returns the amount of titles currently installed in a specific install location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let app_manager = Am::new()?;
+    ///
+    /// // Number of titles installed on the Nand storage.
+    /// let nand_count = app_manager.title_count(MediaType::Nand);
+    ///
+    /// // Number of apps installed on the SD card storage
+    /// let sd_count = app_manager.title_count(MediaType::Sd);
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_GetTitleCount")]
+    pub fn title_count(&self, mediatype: MediaType) -> crate::Result<u32> {
+        unsafe {
+            let mut count = 0;
+            ResultCode(ctru_sys::AM_GetTitleCount(mediatype.into(), &mut count))?;
+            Ok(count)
+        }
+    }
+
+    /// Returns the list of titles installed in a specific install location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let app_manager = Am::new()?;
+    ///
+    /// // Number of apps installed on the SD card storage
+    /// let sd_titles = app_manager.title_list(MediaType::Sd)?;
+    ///
+    /// // Unique product code identifier of the 5th installed title.
+    /// let product_code = sd_titles[4].product_code();
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_GetTitleList")]
+    pub fn title_list(&self, mediatype: MediaType) -> crate::Result<Vec<Title>> {
+        let count = self.title_count(mediatype)?;
+        let mut buf = vec![0; count as usize];
+        let mut read_amount = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::AM_GetTitleList(
+                &mut read_amount,
+                mediatype.into(),
+                count,
+                buf.as_mut_ptr(),
+            ))?;
+        }
+
+        let mut info: Vec<ctru_sys::AM_TitleEntry> = Vec::with_capacity(count as _);
+
+        unsafe {
+            ResultCode(ctru_sys::AM_GetTitleInfo(
+                mediatype.into(),
+                count,
+                buf.as_mut_ptr(),
+                info.as_mut_ptr() as _,
+            ))?;
+
+            info.set_len(count as _);
+        };
+
+        Ok(info
+            .into_iter()
+            .map(|title| Title {
+                id: TitleId::new(title.titleID),
+                mediatype,
+                size: title.size,
+                version: title.version,
+                _am: PhantomData,
+            })
+            .collect())
+    }
+
+    /// Start installing a CIA file to the given install location.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let app_manager = Am::new()?;
+    ///
+    /// let mut cia = std::fs::File::open("sdmc:/cias/app.cia")?;
+    ///
+    /// let mut installer = app_manager.start_cia_install(MediaType::Sd)?;
+    /// installer.set_total_size(cia.metadata()?.len());
+    ///
+    /// std::io::copy(&mut cia, &mut installer)?;
+    /// installer.finish()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_StartCiaInstall")]
+    pub fn start_cia_install(&self, mediatype: MediaType) -> crate::Result<CiaInstaller<'_>> {
+        let mut handle = 0;
+
+        ResultCode(unsafe { ctru_sys::AM_StartCiaInstall(mediatype.into(), &mut handle) })?;
+
+        Ok(CiaInstaller {
+            handle,
+            written: 0,
+            total_size: None,
+            _am: PhantomData,
+        })
+    }
+
+    /// Delete an installed title, along with its save data.
+    #[doc(alias = "AM_DeleteTitle")]
+    pub fn delete_title(&self, mediatype: MediaType, title_id: TitleId) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::AM_DeleteTitle(mediatype.into(), title_id.get()) })?;
+
+        Ok(())
+    }
+
+    /// Returns the amount of tickets installed on the console.
+    #[doc(alias = "AM_GetTicketCount")]
+    pub fn ticket_count(&self) -> crate::Result<u32> {
+        let mut count = 0;
+
+        ResultCode(unsafe { ctru_sys::AM_GetTicketCount(&mut count) })?;
+
+        Ok(count)
+    }
+
+    /// Returns the title IDs of the tickets installed on the console.
+    #[doc(alias = "AM_GetTicketList")]
+    pub fn ticket_list(&self) -> crate::Result<Vec<TitleId>> {
+        ticket_ids()
+    }
+
+    /// Delete the ticket of a title.
+    #[doc(alias = "AM_DeleteTicket")]
+    pub fn delete_ticket(&self, title_id: TitleId) -> crate::Result<()> {
+        ResultCode(unsafe { ctru_sys::AM_DeleteTicket(title_id.get()) })?;
+
+        Ok(())
+    }
+
+    /// Returns the titles whose installation was started but not finalized.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::Am;
+    /// use ctru::services::fs::MediaType;
+    /// let app_manager = Am::new()?;
+    ///
+    /// for pending in app_manager.pending_title_list(MediaType::Sd)? {
+    ///     println!("{}: {:?}", pending.id(), pending.status());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "AM_GetPendingTitleList")]
+    #[doc(alias = "AM_GetPendingTitleInfo")]
+    pub fn pending_title_list(&self, mediatype: MediaType) -> crate::Result<Vec<PendingTitle>> {
+        let mask =
+            ctru_sys::AM_STATUS_MASK_INSTALLING | ctru_sys::AM_STATUS_MASK_AWAITING_FINALIZATION;
+
+        let mut count = 0;
+        ResultCode(unsafe {
+            ctru_sys::AM_GetPendingTitleCount(&mut count, mediatype.into(), mask)
+        })?;
+
+        let mut ids = vec![0; count as usize];
+        let mut read_amount = 0;
+        ResultCode(unsafe {
+            ctru_sys::AM_GetPendingTitleList(
+                &mut read_amount,
+                count,
+                mediatype.into(),
+                mask,
+                ids.as_mut_ptr(),
+            )
+        })?;
+        ids.truncate(read_amount as usize);
+
+        let mut info: Vec<ctru_sys::AM_PendingTitleEntry> = Vec::with_capacity(ids.len());
+
+        unsafe {
+            ResultCode(ctru_sys::AM_GetPendingTitleInfo(
+                ids.len() as u32,
+                mediatype.into(),
+                ids.as_mut_ptr(),
+                info.as_mut_ptr(),
+            ))?;
+
+            info.set_len(ids.len());
+        }
+
+        Ok(ids
+            .into_iter()
+            .zip(info)
+            .map(|(id, entry)| PendingTitle {
+                id: TitleId::new(id),
+                version: entry.version,
+                status: InstallStatus::from(entry.status),
+                title_type: entry.titleType,
+            })
+            .collect())
+    }
+}
+
+impl Drop for Am {
+    #[doc(alias = "amExit")]
+    fn drop(&mut self) {
+        unsafe { ctru_sys::amExit() };
+    }
+}
+
+// List the IDs of the titles installed on the given media.
+fn title_ids(mediatype: MediaType) -> crate::Result<Vec<u64>> {
+    let mut count = 0;
+    ResultCode(unsafe { ctru_sys::AM_GetTitleCount(mediatype.into(), &mut count) })?;
+
+    let mut ids = vec![0; count as usize];
+    let mut read_amount = 0;
+    ResultCode(unsafe {
+        ctru_sys::AM_GetTitleList(&mut read_amount, mediatype.into(), count, ids.as_mut_ptr())
+    })?;
+    ids.truncate(read_amount as usize);
+
+    Ok(ids)
+}
+
+// List the title IDs of the installed tickets.
+fn ticket_ids() -> crate::Result<Vec<TitleId>> {
+    let mut count = 0;
+    ResultCode(unsafe { ctru_sys::AM_GetTicketCount(&mut count) })?;
+
+    let mut ids = vec![0; count as usize];
+    let mut read_amount = 0;
+    ResultCode(unsafe {
+        ctru_sys::AM_GetTicketList(&mut read_amount, count, 0, ids.as_mut_ptr())
+    })?;
+    ids.truncate(read_amount as usize);
+
+    Ok(ids.into_iter().map(TitleId::new).collect())
+}
+
+// List the content infos of a title.
+//
+// Despite their name, the `AMAPP_*DLCContentInfo*` commands work with the contents of any kind of title.
+fn content_infos(mediatype: MediaType, title_id: TitleId) -> crate::Result<Vec<ContentInfo>> {
+    let mut count = 0;
+    ResultCode(unsafe {
+        ctru_sys::AMAPP_GetDLCContentInfoCount(&mut count, mediatype.into(), title_id.get())
+    })?;
+
+    let mut infos: Vec<ctru_sys::AM_ContentInfo> = Vec::with_capacity(count as usize);
+    let mut read_amount = 0;
+
+    unsafe {
+        ResultCode(ctru_sys::AMAPP_ListDLCContentInfos(
+            &mut read_amount,
+            mediatype.into(),
+            title_id.get(),
+            count,
+            0,
+            infos.as_mut_ptr(),
+        ))?;
+
+        infos.set_len(read_amount.min(count) as usize);
+    }
+
+    Ok(infos.into_iter().map(ContentInfo::from).collect())
+}
@@ -7,6 +7,8 @@
 //! Those are implemented in the [`applets`](crate::applets) module.
 
 use crate::error::ResultCode;
+use crate::services::am::TitleId;
+use crate::services::fs::MediaType;
 
 /// Handle to the Applet service.
 pub struct Apt(());
@@ -162,7 +164,13 @@ impl<'a> Chainloader<'a> {
     /// See also [`Title`](crate::services::am::Title]
     #[doc(alias = "aptSetChainloader")]
     pub fn set(&mut self, title: &super::am::Title<'_>) {
-        unsafe { ctru_sys::aptSetChainloader(title.id(), title.media_type() as u8) }
+        self.set_id(title.id(), title.media_type())
+    }
+
+    /// Configures the chainloader to launch the application with the specified ID.
+    #[doc(alias = "aptSetChainloader")]
+    pub fn set_id(&mut self, title_id: TitleId, media_type: MediaType) {
+        unsafe { ctru_sys::aptSetChainloader(title_id.get(), media_type as u8) }
     }
 
     /// Configures the chainloader to launch the previous application.
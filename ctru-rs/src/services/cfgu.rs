@@ -5,57 +5,7 @@
 
 use crate::error::ResultCode;
 
-/// Console region.
-#[doc(alias = "CFG_Region")]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-#[repr(u32)]
-pub enum Region {
-    /// Japan.
-    Japan = ctru_sys::CFG_REGION_JPN,
-    /// USA.
-    USA = ctru_sys::CFG_REGION_USA,
-    /// Europe.
-    Europe = ctru_sys::CFG_REGION_EUR,
-    /// Australia.
-    Australia = ctru_sys::CFG_REGION_AUS,
-    /// China.
-    China = ctru_sys::CFG_REGION_CHN,
-    /// Korea.
-    Korea = ctru_sys::CFG_REGION_KOR,
-    /// Taiwan.
-    Taiwan = ctru_sys::CFG_REGION_TWN,
-}
-
-/// Language set for the console's OS.
-#[doc(alias = "CFG_Language")]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-#[repr(u32)]
-pub enum Language {
-    /// Japanese.
-    Japanese = ctru_sys::CFG_LANGUAGE_JP,
-    /// English.
-    English = ctru_sys::CFG_LANGUAGE_EN,
-    /// French.
-    French = ctru_sys::CFG_LANGUAGE_FR,
-    /// German.
-    German = ctru_sys::CFG_LANGUAGE_DE,
-    /// Italian.
-    Italian = ctru_sys::CFG_LANGUAGE_IT,
-    /// Spanish.
-    Spanish = ctru_sys::CFG_LANGUAGE_ES,
-    /// Korean.
-    Korean = ctru_sys::CFG_LANGUAGE_KO,
-    /// Dutch.
-    Dutch = ctru_sys::CFG_LANGUAGE_NL,
-    /// Portuguese.
-    Portuguese = ctru_sys::CFG_LANGUAGE_PT,
-    /// Russian.
-    Russian = ctru_sys::CFG_LANGUAGE_RU,
-    /// Simplified Chinese.
-    SimplifiedChinese = ctru_sys::CFG_LANGUAGE_ZH,
-    /// Traditional Chinese.
-    TraditionalChinese = ctru_sys::CFG_LANGUAGE_TW,
-}
+pub use ctru_formats::cfg::{Language, Region};
 
 /// Specific model of the console.
 #[doc(alias = "CFG_SystemModel")]
@@ -243,49 +193,8 @@ impl Drop for Cfgu {
     }
 }
 
-from_impl!(Region, u8);
-from_impl!(Language, u8);
 from_impl!(SystemModel, u8);
 
-impl TryFrom<u8> for Region {
-    type Error = ();
-
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value as u32 {
-            ctru_sys::CFG_REGION_JPN => Ok(Region::Japan),
-            ctru_sys::CFG_REGION_USA => Ok(Region::USA),
-            ctru_sys::CFG_REGION_EUR => Ok(Region::Europe),
-            ctru_sys::CFG_REGION_AUS => Ok(Region::Australia),
-            ctru_sys::CFG_REGION_CHN => Ok(Region::China),
-            ctru_sys::CFG_REGION_KOR => Ok(Region::Korea),
-            ctru_sys::CFG_REGION_TWN => Ok(Region::Taiwan),
-            _ => Err(()),
-        }
-    }
-}
-
-impl TryFrom<u8> for Language {
-    type Error = ();
-
-    fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value as u32 {
-            ctru_sys::CFG_LANGUAGE_JP => Ok(Language::Japanese),
-            ctru_sys::CFG_LANGUAGE_EN => Ok(Language::English),
-            ctru_sys::CFG_LANGUAGE_FR => Ok(Language::French),
-            ctru_sys::CFG_LANGUAGE_DE => Ok(Language::German),
-            ctru_sys::CFG_LANGUAGE_IT => Ok(Language::Italian),
-            ctru_sys::CFG_LANGUAGE_ES => Ok(Language::Spanish),
-            ctru_sys::CFG_LANGUAGE_ZH => Ok(Language::SimplifiedChinese),
-            ctru_sys::CFG_LANGUAGE_KO => Ok(Language::Korean),
-            ctru_sys::CFG_LANGUAGE_NL => Ok(Language::Dutch),
-            ctru_sys::CFG_LANGUAGE_PT => Ok(Language::Portuguese),
-            ctru_sys::CFG_LANGUAGE_RU => Ok(Language::Russian),
-            ctru_sys::CFG_LANGUAGE_TW => Ok(Language::TraditionalChinese),
-            _ => Err(()),
-        }
-    }
-}
-
 impl TryFrom<u8> for SystemModel {
     type Error = ();
 
@@ -301,3 +210,42 @@ impl TryFrom<u8> for SystemModel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_values() {
+        for (region, raw) in [
+            (Region::Japan, ctru_sys::CFG_REGION_JPN),
+            (Region::USA, ctru_sys::CFG_REGION_USA),
+            (Region::Europe, ctru_sys::CFG_REGION_EUR),
+            (Region::Australia, ctru_sys::CFG_REGION_AUS),
+            (Region::China, ctru_sys::CFG_REGION_CHN),
+            (Region::Korea, ctru_sys::CFG_REGION_KOR),
+            (Region::Taiwan, ctru_sys::CFG_REGION_TWN),
+        ] {
+            assert_eq!(region as u32, raw);
+            assert_eq!(Region::try_from(raw as u8), Ok(region));
+        }
+
+        for (language, raw) in [
+            (Language::Japanese, ctru_sys::CFG_LANGUAGE_JP),
+            (Language::English, ctru_sys::CFG_LANGUAGE_EN),
+            (Language::French, ctru_sys::CFG_LANGUAGE_FR),
+            (Language::German, ctru_sys::CFG_LANGUAGE_DE),
+            (Language::Italian, ctru_sys::CFG_LANGUAGE_IT),
+            (Language::Spanish, ctru_sys::CFG_LANGUAGE_ES),
+            (Language::Korean, ctru_sys::CFG_LANGUAGE_KO),
+            (Language::Dutch, ctru_sys::CFG_LANGUAGE_NL),
+            (Language::Portuguese, ctru_sys::CFG_LANGUAGE_PT),
+            (Language::Russian, ctru_sys::CFG_LANGUAGE_RU),
+            (Language::SimplifiedChinese, ctru_sys::CFG_LANGUAGE_ZH),
+            (Language::TraditionalChinese, ctru_sys::CFG_LANGUAGE_TW),
+        ] {
+            assert_eq!(language as u32, raw);
+            assert_eq!(Language::try_from(raw as u8), Ok(language));
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Crash-safe file writes.
+//!
+//! Losing power (or crashing) while a file is being written leaves it half-written, which usually means losing the data it held.
+//! The functions in this module avoid that by never writing over the data they replace:
+//!
+//! 1. The new contents are written to a temporary file, followed by a trailer holding their CRC32 checksum.
+//! 2. The temporary file is read back and its checksum is verified.
+//! 3. The temporary file is renamed over the original one.
+//! 4. If the archive is a save data archive, the changes are committed.
+//!
+//! Files written this way must be read with [`read()`], which checks the trailer and rejects torn or corrupted data.
+//! If the original file is missing or corrupted, [`read()`] falls back to a fully written temporary file, which is
+//! left behind if the write was interrupted while renaming. The next [`write()`] restores it before starting over.
+#![doc(alias = "crc32")]
+
+use std::error;
+use std::fmt;
+
+use super::savedata::commit_archive;
+use super::{Archive, ArchiveID, Attribute, File, Open};
+
+pub use ctru_formats::checksum::crc32;
+
+// Magic bytes at the start of the trailer appended to the data.
+const TRAILER_MAGIC: [u8; 4] = *b"CRC1";
+
+/// Size of the trailer appended to files written by [`write()`].
+pub const TRAILER_LEN: usize = 8;
+
+/// Errors returned by [`atomic`](self) functions.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// The file's contents don't match their checksum, or the file wasn't written by [`write()`].
+    Corrupted,
+    /// Any other error returned by the file-system service.
+    Other(crate::Error),
+}
+
+/// Write `data` to the file at `path`, replacing its previous contents only once they are completely written.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs::{atomic, ArchiveID, Fs};
+///
+/// let fs = Fs::new()?;
+/// let sdmc = fs.open_archive(ArchiveID::Sdmc)?;
+///
+/// atomic::write(&sdmc, "/player.sav", b"level 3")?;
+///
+/// assert_eq!(atomic::read(&sdmc, "/player.sav")?, b"level 3");
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub fn write(archive: &Archive, path: &str, data: &[u8]) -> Result<(), Error> {
+    let temp_path = temp_path(path);
+
+    // A leftover temporary file may hold the only valid copy of the data.
+    recover(archive, path, &temp_path)?;
+
+    let mut contents = Vec::with_capacity(data.len() + TRAILER_LEN);
+    contents.extend_from_slice(data);
+    contents.extend_from_slice(&trailer(data));
+
+    {
+        let mut file = archive.open_file(
+            &temp_path,
+            Open::FS_OPEN_WRITE | Open::FS_OPEN_CREATE,
+            Attribute::empty(),
+        )?;
+        file.set_len(contents.len() as u64)?;
+        write_all(&mut file, &contents)?;
+        file.sync()?;
+    }
+
+    if let Err(e) = read_verified(archive, &temp_path) {
+        let _ = archive.remove_file(&temp_path);
+        return Err(e);
+    }
+
+    // Renaming fails if the destination already exists.
+    let _ = archive.remove_file(path);
+    archive.rename_file(&temp_path, path)?;
+
+    if is_save_data(archive.id()) {
+        commit_archive(archive)?;
+    }
+
+    Ok(())
+}
+
+/// Read the contents of a file written by [`write()`].
+///
+/// # Errors
+///
+/// Returns [`Error::Corrupted`] if the file is torn, corrupted or wasn't written by [`write()`].
+pub fn read(archive: &Archive, path: &str) -> Result<Vec<u8>, Error> {
+    match read_verified(archive, path) {
+        Ok(data) => Ok(data),
+        // The write may have been interrupted after removing the original file, but before renaming the new one.
+        Err(e) => read_verified(archive, &temp_path(path)).map_err(|_| e),
+    }
+}
+
+// Clean up after an interrupted write, so that the temporary file can be reused.
+//
+// The temporary file is only removed once the original file is known to be valid:
+// if the write was interrupted after removing the original file, the temporary file is renamed over it instead.
+fn recover(archive: &Archive, path: &str, temp_path: &str) -> Result<(), Error> {
+    if read_verified(archive, path).is_ok() {
+        let _ = archive.remove_file(temp_path);
+    } else if read_verified(archive, temp_path).is_ok() {
+        let _ = archive.remove_file(path);
+        archive.rename_file(temp_path, path)?;
+    } else {
+        // Neither file is valid, so the temporary one can only hold an older, interrupted write.
+        let _ = archive.remove_file(temp_path);
+    }
+
+    Ok(())
+}
+
+// Read a file and check its trailer, returning the data without the trailer.
+fn read_verified(archive: &Archive, path: &str) -> Result<Vec<u8>, Error> {
+    let file = archive.open_file(path, Open::FS_OPEN_READ, Attribute::empty())?;
+    let contents = read_all(&file)?;
+
+    split_verified(contents)
+}
+
+// Check the trailer of `contents`, returning the data without it.
+fn split_verified(mut contents: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let Some(data_len) = contents.len().checked_sub(TRAILER_LEN) else {
+        return Err(Error::Corrupted);
+    };
+
+    if contents[data_len..] != trailer(&contents[..data_len]) {
+        return Err(Error::Corrupted);
+    }
+
+    contents.truncate(data_len);
+
+    Ok(contents)
+}
+
+fn trailer(data: &[u8]) -> [u8; TRAILER_LEN] {
+    let mut trailer = [0; TRAILER_LEN];
+    trailer[..4].copy_from_slice(&TRAILER_MAGIC);
+    trailer[4..].copy_from_slice(&crc32(data).to_le_bytes());
+    trailer
+}
+
+fn temp_path(path: &str) -> String {
+    format!("{path}.tmp")
+}
+
+fn is_save_data(id: ArchiveID) -> bool {
+    matches!(
+        id,
+        ArchiveID::Savedata
+            | ArchiveID::UserSavedata
+            | ArchiveID::GameCardSavedata
+            | ArchiveID::SystemSavedata
+    )
+}
+
+// The contents are read in chunks instead of trusting the size of the file, which may be corrupted.
+fn read_all(file: &File) -> crate::Result<Vec<u8>> {
+    let mut contents = Vec::new();
+    let mut chunk = [0; 0x1000];
+
+    loop {
+        match file.read_at(contents.len() as u64, &mut chunk)? {
+            0 => break,
+            n => contents.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    Ok(contents)
+}
+
+fn write_all(file: &mut File, data: &[u8]) -> crate::Result<()> {
+    let mut written = 0;
+
+    while written < data.len() {
+        match file.write_at(written as u64, &data[written..])? {
+            0 => return Err(crate::Error::Other("failed to write the whole file".into())),
+            n => written += n,
+        }
+    }
+
+    Ok(())
+}
+
+impl From<crate::Error> for Error {
+    fn from(err: crate::Error) -> Self {
+        Self::Other(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Corrupted => write!(f, "the file is torn or corrupted"),
+            Self::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Other(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::fs::Fs;
+
+    #[test]
+    fn trailer_verification() {
+        let mut contents = b"level 3".to_vec();
+        contents.extend_from_slice(&trailer(b"level 3"));
+
+        assert_eq!(split_verified(contents.clone()).unwrap(), b"level 3");
+
+        // Torn write: the trailer is missing.
+        assert!(matches!(
+            split_verified(contents[..7].to_vec()),
+            Err(Error::Corrupted)
+        ));
+
+        // Corrupted data.
+        contents[0] = b'L';
+        assert!(matches!(split_verified(contents), Err(Error::Corrupted)));
+    }
+
+    #[test]
+    fn interrupted_rename() {
+        let fs = Fs::new().unwrap();
+        let sdmc = fs.open_archive(ArchiveID::Sdmc).unwrap();
+        let path = "/atomic-test.sav";
+
+        write(&sdmc, path, b"level 3").unwrap();
+
+        // Simulate a write interrupted after removing the original file, but before renaming the new one.
+        sdmc.rename_file(path, &temp_path(path)).unwrap();
+        assert_eq!(read(&sdmc, path).unwrap(), b"level 3");
+
+        recover(&sdmc, path, &temp_path(path)).unwrap();
+
+        assert_eq!(read_verified(&sdmc, path).unwrap(), b"level 3");
+        assert!(sdmc.remove_file(&temp_path(path)).is_err());
+
+        write(&sdmc, path, b"level 4").unwrap();
+        assert_eq!(read(&sdmc, path).unwrap(), b"level 4");
+
+        sdmc.remove_file(path).unwrap();
+    }
+}
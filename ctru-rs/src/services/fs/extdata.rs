@@ -0,0 +1,282 @@
+//! Extra data management.
+//!
+//! Extra data ("ExtData") archives hold data that doesn't fit into a title's save data, such as downloadable content or data shared between titles.
+//! Unlike save data, ExtData archives must be explicitly created before they can be opened, and they persist even after the title which created them is deleted.
+#![doc(alias = "extdata")]
+
+use std::error;
+use std::fmt;
+
+use super::path::FsPath;
+use super::{Archive, ArchiveID, Fs, MediaType};
+use crate::error::ResultCode;
+
+// Upper word of the IDs of all shared ExtData archives.
+const SHARED_EXTDATA_ID_HIGH: u64 = 0x0004_8000;
+
+/// Errors returned by [`extdata`](self) functions.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum Error {
+    /// The requested ExtData archive doesn't exist.
+    NotFound,
+    /// An ExtData archive with the same ID already exists.
+    AlreadyExists,
+    /// Any other error returned by the file-system service.
+    Other(crate::Error),
+}
+
+/// Kind of ExtData archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// ExtData owned by a title.
+    Normal,
+    /// ExtData shared between system titles, stored on the NAND.
+    Shared,
+    /// ExtData used by the SpotPass (BOSS) service.
+    Boss,
+}
+
+/// Identifier of an ExtData archive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ExtData {
+    kind: Kind,
+    media_type: MediaType,
+    id: u64,
+}
+
+impl ExtData {
+    /// Identify the ExtData with the specified ID, stored in `media_type`.
+    ///
+    /// Titles stored on the SD card usually use the same media type for their ExtData.
+    pub fn new(media_type: MediaType, id: u64) -> Self {
+        Self {
+            kind: Kind::Normal,
+            media_type,
+            id,
+        }
+    }
+
+    /// Identify the shared ExtData with the specified ID (e.g. `0xF000000B`).
+    pub fn shared(id: u32) -> Self {
+        Self {
+            kind: Kind::Shared,
+            media_type: MediaType::Nand,
+            id: (SHARED_EXTDATA_ID_HIGH << 32) | u64::from(id),
+        }
+    }
+
+    /// Identify the SpotPass ExtData with the specified ID, stored in `media_type`.
+    pub fn boss(media_type: MediaType, id: u64) -> Self {
+        Self {
+            kind: Kind::Boss,
+            media_type,
+            id,
+        }
+    }
+
+    /// Returns the kind of this ExtData.
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// Returns the storage this ExtData is located in.
+    pub fn media_type(&self) -> MediaType {
+        self.media_type
+    }
+
+    /// Returns the full ID of this ExtData.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the [`ArchiveID`] used to open this ExtData.
+    pub fn archive_id(&self) -> ArchiveID {
+        match self.kind {
+            Kind::Normal => ArchiveID::Extdata,
+            Kind::Shared => ArchiveID::SharedExtdata,
+            Kind::Boss => ArchiveID::BossExtdata,
+        }
+    }
+
+    /// Returns the path identifying this ExtData within its archive.
+    pub fn path(&self) -> FsPath {
+        FsPath::ext_data(self.media_type, self.id)
+    }
+
+    fn as_raw(&self) -> ctru_sys::FS_ExtSaveDataInfo {
+        let mut info = ctru_sys::FS_ExtSaveDataInfo {
+            saveId: self.id,
+            ..Default::default()
+        };
+        info.set_mediaType(self.media_type.into());
+        info
+    }
+}
+
+/// Configuration used when creating an ExtData archive.
+///
+/// See [`create()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CreateConfig<'a> {
+    /// Maximum amount of directories in the archive.
+    pub directories: u32,
+    /// Maximum amount of files in the archive.
+    pub files: u32,
+    /// Maximum size of the archive in bytes. A value of `0` means no limit.
+    pub quota: u64,
+    /// SMDH (icon and title information) to store along with the archive.
+    pub smdh: &'a [u8],
+}
+
+/// Size information about an ExtData archive, returned by [`format_info()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatInfo {
+    /// Maximum size of the archive in bytes.
+    pub quota: u64,
+    /// Maximum amount of directories in the archive.
+    pub directories: u32,
+    /// Maximum amount of files in the archive.
+    pub files: u32,
+}
+
+/// Create a new ExtData archive.
+///
+/// # Errors
+///
+/// Returns [`Error::AlreadyExists`] if an ExtData archive with the same ID already exists.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs::extdata::{self, CreateConfig, ExtData};
+/// use ctru::services::fs::{Fs, MediaType};
+///
+/// let fs = Fs::new()?;
+/// let smdh = std::fs::read("romfs:/icon.smdh")?;
+///
+/// let config = CreateConfig {
+///     directories: 8,
+///     files: 64,
+///     quota: 1024 * 1024,
+///     smdh: &smdh,
+/// };
+///
+/// extdata::create(&fs, ExtData::new(MediaType::Sd, 0x1234), &config)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "FSUSER_CreateExtSaveData")]
+pub fn create(_fs: &Fs, extdata: ExtData, config: &CreateConfig) -> Result<(), Error> {
+    create_ext_save_data(extdata, config).map_err(Error::from)
+}
+
+/// Open an existing ExtData archive.
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] if the ExtData archive doesn't exist.
+#[doc(alias = "FSUSER_OpenArchive")]
+pub fn open(_fs: &Fs, extdata: ExtData) -> Result<Archive<'_>, Error> {
+    Archive::open(extdata.archive_id(), &extdata.path()).map_err(Error::from)
+}
+
+/// Returns the size limits an ExtData archive was created with.
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] if the ExtData archive doesn't exist.
+#[doc(alias = "FSUSER_GetFormatInfo")]
+pub fn format_info(_fs: &Fs, extdata: ExtData) -> Result<FormatInfo, Error> {
+    get_format_info(extdata).map_err(Error::from)
+}
+
+/// Delete an ExtData archive along with all of its contents.
+///
+/// # Errors
+///
+/// Returns [`Error::NotFound`] if the ExtData archive doesn't exist.
+#[doc(alias = "FSUSER_DeleteExtSaveData")]
+pub fn delete(_fs: &Fs, extdata: ExtData) -> Result<(), Error> {
+    delete_ext_save_data(extdata).map_err(Error::from)
+}
+
+fn create_ext_save_data(extdata: ExtData, config: &CreateConfig) -> crate::Result<()> {
+    ResultCode(unsafe {
+        ctru_sys::FSUSER_CreateExtSaveData(
+            extdata.as_raw(),
+            config.directories,
+            config.files,
+            config.quota,
+            config.smdh.len() as u32,
+            config.smdh.as_ptr().cast_mut(),
+        )
+    })?;
+
+    Ok(())
+}
+
+fn delete_ext_save_data(extdata: ExtData) -> crate::Result<()> {
+    ResultCode(unsafe { ctru_sys::FSUSER_DeleteExtSaveData(extdata.as_raw()) })?;
+
+    Ok(())
+}
+
+fn get_format_info(extdata: ExtData) -> crate::Result<FormatInfo> {
+    let path = extdata.path();
+    let mut quota = 0;
+    let mut directories = 0;
+    let mut files = 0;
+    let mut duplicate_data = false;
+
+    ResultCode(unsafe {
+        ctru_sys::FSUSER_GetFormatInfo(
+            &mut quota,
+            &mut directories,
+            &mut files,
+            &mut duplicate_data,
+            extdata.archive_id().into(),
+            *path.as_raw(),
+        )
+    })?;
+
+    Ok(FormatInfo {
+        quota: quota.into(),
+        directories,
+        files,
+    })
+}
+
+impl From<crate::Error> for Error {
+    fn from(err: crate::Error) -> Self {
+        match err {
+            err if err.is_not_found() => Self::NotFound,
+            err if err.is_already_exists() => Self::AlreadyExists,
+            err => Self::Other(err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "the requested ExtData archive doesn't exist"),
+            Self::AlreadyExists => write!(f, "an ExtData archive with the same ID already exists"),
+            Self::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Other(err) => Some(err),
+            _ => None,
+        }
+    }
+}
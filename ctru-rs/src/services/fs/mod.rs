@@ -0,0 +1,1002 @@
+//! FileSystem service.
+//!
+//! This service gives access to the archives managed by the console's file-system server, such as the SD card, the save data of a title
+//! or the various system archives. Once opened, an [`Archive`] hands out [`File`]s which implement [`std::io::Read`], [`std::io::Write`] and [`std::io::Seek`].
+//!
+//! This module also contains datatypes to easily operate with unsafe [`ctru_sys`] code regarding the file-system functionality.
+#![doc(alias = "filesystem")]
+
+use std::io;
+use std::marker::PhantomData;
+
+use bitflags::bitflags;
+
+use crate::error::ResultCode;
+
+pub mod atomic;
+pub mod extdata;
+pub mod mount;
+pub mod path;
+pub mod savedata;
+
+use self::path::FsPath;
+
+bitflags! {
+    /// Flags used when opening a [`File`].
+    #[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct Open: u32 {
+        /// Open the file with read access.
+        const FS_OPEN_READ   = ctru_sys::FS_OPEN_READ;
+        /// Open the file with write access.
+        const FS_OPEN_WRITE  = ctru_sys::FS_OPEN_WRITE;
+        /// Create the file if it doesn't exist.
+        const FS_OPEN_CREATE = ctru_sys::FS_OPEN_CREATE;
+    }
+
+    /// Flags used when writing to a [`File`].
+    #[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct Write: u32 {
+        /// Flush the written data to the storage immediately.
+        const FS_WRITE_FLUSH       = ctru_sys::FS_WRITE_FLUSH;
+        /// Update the file's timestamp.
+        const FS_WRITE_UPDATE_TIME = ctru_sys::FS_WRITE_UPDATE_TIME;
+    }
+
+    /// Attributes of a file or directory.
+    #[derive(Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct Attribute: u32 {
+        /// The entry is a directory.
+        const FS_ATTRIBUTE_DIRECTORY = ctru_sys::FS_ATTRIBUTE_DIRECTORY;
+        /// The entry is hidden.
+        const FS_ATTRIBUTE_HIDDEN    = ctru_sys::FS_ATTRIBUTE_HIDDEN;
+        /// The entry is marked as an archive.
+        const FS_ATTRIBUTE_ARCHIVE   = ctru_sys::FS_ATTRIBUTE_ARCHIVE;
+        /// The entry is read-only.
+        const FS_ATTRIBUTE_READ_ONLY = ctru_sys::FS_ATTRIBUTE_READ_ONLY;
+    }
+}
+
+/// Media type used for storage.
+#[doc(alias = "FS_MediaType")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MediaType {
+    /// Internal NAND memory.
+    Nand = ctru_sys::MEDIATYPE_NAND,
+    /// External SD card.
+    Sd = ctru_sys::MEDIATYPE_SD,
+    /// Game Cartridge.
+    GameCard = ctru_sys::MEDIATYPE_GAME_CARD,
+}
+
+/// Kind of file path.
+#[doc(alias = "FS_PathType")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PathType {
+    /// Invalid path.
+    Invalid = ctru_sys::PATH_INVALID,
+    /// Empty path.
+    Empty = ctru_sys::PATH_EMPTY,
+    /// Binary path.
+    ///
+    /// Its meaning differs depending on the Archive it is used on.
+    Binary = ctru_sys::PATH_BINARY,
+    /// ASCII path.
+    ASCII = ctru_sys::PATH_ASCII,
+    /// UTF-16 path.
+    UTF16 = ctru_sys::PATH_UTF16,
+}
+
+/// Storage device of the console, used when querying its available space.
+#[doc(alias = "FS_SystemMediaType")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum SystemMediaType {
+    /// CTR (3DS) partition of the internal NAND memory.
+    CtrNand = ctru_sys::SYSTEM_MEDIATYPE_CTR_NAND,
+    /// TWL (DSi) partition of the internal NAND memory.
+    TwlNand = ctru_sys::SYSTEM_MEDIATYPE_TWL_NAND,
+    /// External SD card.
+    Sd = ctru_sys::SYSTEM_MEDIATYPE_SD,
+    /// TWL (DSi) photo partition of the internal NAND memory.
+    TwlPhoto = ctru_sys::SYSTEM_MEDIATYPE_TWL_PHOTO,
+}
+
+/// Kind of game card inserted in the console.
+#[doc(alias = "FS_CardType")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum CardType {
+    /// Nintendo 3DS game card.
+    Ctr = ctru_sys::CARD_CTR,
+    /// Nintendo DS(i) game card.
+    Twl = ctru_sys::CARD_TWL,
+}
+
+/// Information about the space of a storage device.
+///
+/// See [`Fs::archive_resource()`].
+#[doc(alias = "FS_ArchiveResource")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveResource {
+    /// Size of a sector in bytes.
+    pub sector_size: u32,
+    /// Size of a cluster in bytes.
+    pub cluster_size: u32,
+    /// Total amount of clusters.
+    pub total_clusters: u32,
+    /// Amount of free clusters.
+    pub free_clusters: u32,
+}
+
+impl ArchiveResource {
+    /// Returns the total size of the device in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        u64::from(self.cluster_size) * u64::from(self.total_clusters)
+    }
+
+    /// Returns the free space on the device in bytes.
+    pub fn free_bytes(&self) -> u64 {
+        u64::from(self.cluster_size) * u64::from(self.free_clusters)
+    }
+}
+
+/// Index of the various usable data archives.
+#[doc(alias = "FS_ArchiveID")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ArchiveID {
+    /// Read-Only Memory File System.
+    RomFS = ctru_sys::ARCHIVE_ROMFS,
+    /// Game save data.
+    Savedata = ctru_sys::ARCHIVE_SAVEDATA,
+    /// Game ext data.
+    Extdata = ctru_sys::ARCHIVE_EXTDATA,
+    /// Shared ext data.
+    SharedExtdata = ctru_sys::ARCHIVE_SHARED_EXTDATA,
+    /// System save data.
+    SystemSavedata = ctru_sys::ARCHIVE_SYSTEM_SAVEDATA,
+    /// SD card.
+    Sdmc = ctru_sys::ARCHIVE_SDMC,
+    /// SD card (write-only).
+    SdmcWriteOnly = ctru_sys::ARCHIVE_SDMC_WRITE_ONLY,
+    /// BOSS ext data.
+    BossExtdata = ctru_sys::ARCHIVE_BOSS_EXTDATA,
+    /// Card SPI File System.
+    CardSpiFS = ctru_sys::ARCHIVE_CARD_SPIFS,
+    /// Game ext data and BOSS data.
+    ExtDataAndBossExtdata = ctru_sys::ARCHIVE_EXTDATA_AND_BOSS_EXTDATA,
+    /// System save data.
+    SystemSaveData2 = ctru_sys::ARCHIVE_SYSTEM_SAVEDATA2,
+    /// Internal NAND (read-write).
+    NandRW = ctru_sys::ARCHIVE_NAND_RW,
+    /// Internal NAND (read-only).
+    NandRO = ctru_sys::ARCHIVE_NAND_RO,
+    /// Internal NAND (read-only write access).
+    NandROWriteAccess = ctru_sys::ARCHIVE_NAND_RO_WRITE_ACCESS,
+    /// User save data and ExeFS/RomFS.
+    SaveDataAndContent = ctru_sys::ARCHIVE_SAVEDATA_AND_CONTENT,
+    /// User save data and ExeFS/RomFS (only ExeFS for fs:LDR).
+    SaveDataAndContent2 = ctru_sys::ARCHIVE_SAVEDATA_AND_CONTENT2,
+    /// NAND CTR File System.
+    NandCtrFS = ctru_sys::ARCHIVE_NAND_CTR_FS,
+    /// TWL photo.
+    TwlPhoto = ctru_sys::ARCHIVE_TWL_PHOTO,
+    /// NAND TWL File System.
+    NandTwlFS = ctru_sys::ARCHIVE_NAND_TWL_FS,
+    /// Game card save data.
+    GameCardSavedata = ctru_sys::ARCHIVE_GAMECARD_SAVEDATA,
+    /// User save data.
+    UserSavedata = ctru_sys::ARCHIVE_USER_SAVEDATA,
+    /// Demo save data.
+    DemoSavedata = ctru_sys::ARCHIVE_DEMO_SAVEDATA,
+}
+
+/// Handle to the FileSystem service.
+pub struct Fs(());
+
+impl Fs {
+    /// Initialize a new service handle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::fs::Fs;
+    ///
+    /// let fs = Fs::new()?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "fsInit")]
+    pub fn new() -> crate::Result<Self> {
+        unsafe {
+            ResultCode(ctru_sys::fsInit())?;
+            Ok(Fs(()))
+        }
+    }
+
+    /// Open the archive with the specified ID.
+    ///
+    /// The archive is opened with an empty path, which is what most archives (e.g. [`ArchiveID::Sdmc`] or [`ArchiveID::Savedata`]) expect.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::fs::{ArchiveID, Fs};
+    ///
+    /// let fs = Fs::new()?;
+    ///
+    /// let sdmc = fs.open_archive(ArchiveID::Sdmc)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_OpenArchive")]
+    pub fn open_archive(&self, id: ArchiveID) -> crate::Result<Archive<'_>> {
+        Archive::open(id, &FsPath::empty())
+    }
+
+    /// Open the archive with the specified ID, identified by `path`.
+    ///
+    /// Some archives (e.g. [`ArchiveID::UserSavedata`]) need a binary path to know which data to access.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::am::TitleId;
+    /// use ctru::services::fs::path::FsPath;
+    /// use ctru::services::fs::{ArchiveID, Fs, MediaType};
+    ///
+    /// let fs = Fs::new()?;
+    ///
+    /// let path = FsPath::user_save_data(MediaType::Sd, TitleId::new(0x0004000000123400));
+    /// let save = fs.open_archive_with_path(ArchiveID::UserSavedata, &path)?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_OpenArchive")]
+    pub fn open_archive_with_path(
+        &self,
+        id: ArchiveID,
+        path: &FsPath,
+    ) -> crate::Result<Archive<'_>> {
+        Archive::open(id, path)
+    }
+
+    /// Returns information about the space of the specified storage device.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::fs::{Fs, SystemMediaType};
+    ///
+    /// let fs = Fs::new()?;
+    ///
+    /// let sd = fs.archive_resource(SystemMediaType::Sd)?;
+    ///
+    /// if sd.free_bytes() < 64 * 1024 * 1024 {
+    ///     println!("Not enough space on the SD card!");
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_GetArchiveResource")]
+    pub fn archive_resource(&self, media_type: SystemMediaType) -> crate::Result<ArchiveResource> {
+        let mut resource = ctru_sys::FS_ArchiveResource::default();
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_GetArchiveResource(
+                &mut resource,
+                media_type.into(),
+            ))?;
+        }
+
+        Ok(ArchiveResource {
+            sector_size: resource.sectorSize,
+            cluster_size: resource.clusterSize,
+            total_clusters: resource.totalClusters,
+            free_clusters: resource.freeClusters,
+        })
+    }
+
+    /// Returns `true` if an SD card is inserted in the console.
+    #[doc(alias = "FSUSER_IsSdmcDetected")]
+    pub fn is_sdmc_detected(&self) -> crate::Result<bool> {
+        let mut detected = false;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_IsSdmcDetected(&mut detected))?;
+        }
+
+        Ok(detected)
+    }
+
+    /// Returns `true` if the inserted SD card can be written to.
+    #[doc(alias = "FSUSER_IsSdmcWritable")]
+    pub fn is_sdmc_writable(&self) -> crate::Result<bool> {
+        let mut writable = false;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_IsSdmcWritable(&mut writable))?;
+        }
+
+        Ok(writable)
+    }
+
+    /// Returns `true` if a game card is inserted in the console.
+    #[doc(alias = "FSUSER_CardSlotIsInserted")]
+    pub fn is_card_inserted(&self) -> crate::Result<bool> {
+        let mut inserted = false;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_CardSlotIsInserted(&mut inserted))?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Returns the kind of the inserted game card.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no game card is inserted, or if the kind of the game card is unknown.
+    #[doc(alias = "FSUSER_GetCardType")]
+    pub fn card_type(&self) -> crate::Result<CardType> {
+        let mut card_type = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_GetCardType(&mut card_type))?;
+        }
+
+        match card_type {
+            ctru_sys::CARD_CTR => Ok(CardType::Ctr),
+            ctru_sys::CARD_TWL => Ok(CardType::Twl),
+            _ => Err(crate::Error::Other(format!(
+                "unknown card type {card_type}"
+            ))),
+        }
+    }
+}
+
+impl Drop for Fs {
+    #[doc(alias = "fsExit")]
+    fn drop(&mut self) {
+        unsafe { ctru_sys::fsExit() };
+    }
+}
+
+/// An opened file-system archive.
+///
+/// Archives are obtained via [`Fs::open_archive()`] and are closed once dropped.
+#[doc(alias = "FS_Archive")]
+pub struct Archive<'fs> {
+    handle: ctru_sys::FS_Archive,
+    id: ArchiveID,
+    path: FsPath,
+    _fs: PhantomData<&'fs Fs>,
+}
+
+impl<'fs> Archive<'fs> {
+    pub(crate) fn open(id: ArchiveID, path: &FsPath) -> crate::Result<Self> {
+        let mut handle = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_OpenArchive(
+                &mut handle,
+                id.into(),
+                *path.as_raw(),
+            ))?;
+        }
+
+        Ok(Self {
+            handle,
+            id,
+            path: path.clone(),
+            _fs: PhantomData,
+        })
+    }
+
+    /// Returns the ID this archive was opened with.
+    pub fn id(&self) -> ArchiveID {
+        self.id
+    }
+
+    /// Returns the path this archive was opened with.
+    pub fn path(&self) -> &FsPath {
+        &self.path
+    }
+
+    /// Returns the raw `libctru` handle of this archive.
+    pub fn as_raw(&self) -> ctru_sys::FS_Archive {
+        self.handle
+    }
+
+    /// Returns the free space in this archive in bytes.
+    #[doc(alias = "FSUSER_GetFreeBytes")]
+    pub fn free_bytes(&self) -> crate::Result<u64> {
+        let mut free = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_GetFreeBytes(&mut free, self.handle))?;
+        }
+
+        Ok(free)
+    }
+
+    /// Open the file at `path` within this archive.
+    ///
+    /// `attributes` are only used when the file gets created (see [`Open::FS_OPEN_CREATE`]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use std::io::Write;
+    ///
+    /// use ctru::services::fs::{ArchiveID, Attribute, Fs, Open};
+    ///
+    /// let fs = Fs::new()?;
+    /// let sdmc = fs.open_archive(ArchiveID::Sdmc)?;
+    ///
+    /// let mut file = sdmc.open_file(
+    ///     "/hello.txt",
+    ///     Open::FS_OPEN_WRITE | Open::FS_OPEN_CREATE,
+    ///     Attribute::empty(),
+    /// )?;
+    ///
+    /// file.write_all(b"Hello, World!")?;
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_OpenFile")]
+    pub fn open_file(
+        &self,
+        path: &str,
+        flags: Open,
+        attributes: Attribute,
+    ) -> crate::Result<File<'_>> {
+        let path = FsPath::utf16(path);
+        let mut handle = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_OpenFile(
+                &mut handle,
+                self.handle,
+                *path.as_raw(),
+                flags.bits(),
+                attributes.bits(),
+            ))?;
+        }
+
+        Ok(File {
+            handle,
+            offset: 0,
+            write_flags: Write::empty(),
+            _archive: PhantomData,
+        })
+    }
+
+    /// Returns an iterator over the entries of the directory at `path`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::fs::{ArchiveID, Fs};
+    ///
+    /// let fs = Fs::new()?;
+    /// let sdmc = fs.open_archive(ArchiveID::Sdmc)?;
+    ///
+    /// for entry in sdmc.read_dir("/")? {
+    ///     let entry = entry?;
+    ///
+    ///     println!("{} ({} bytes)", entry.name(), entry.len());
+    /// }
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "FSUSER_OpenDirectory")]
+    pub fn read_dir(&self, path: &str) -> crate::Result<ReadDir<'_>> {
+        let path = FsPath::utf16(path);
+        let mut handle = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_OpenDirectory(
+                &mut handle,
+                self.handle,
+                *path.as_raw(),
+            ))?;
+        }
+
+        Ok(ReadDir {
+            handle,
+            entries: vec![ctru_sys::FS_DirectoryEntry::default(); READ_DIR_BATCH],
+            position: 0,
+            available: 0,
+            finished: false,
+            _archive: PhantomData,
+        })
+    }
+
+    /// Create a new file of `size` bytes at `path`.
+    ///
+    /// The file's contents are zero-filled.
+    #[doc(alias = "FSUSER_CreateFile")]
+    pub fn create_file(&self, path: &str, attributes: Attribute, size: u64) -> crate::Result<()> {
+        let path = FsPath::utf16(path);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_CreateFile(
+                self.handle,
+                *path.as_raw(),
+                attributes.bits(),
+                size,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a new, empty directory at `path`.
+    #[doc(alias = "FSUSER_CreateDirectory")]
+    pub fn create_dir(&self, path: &str, attributes: Attribute) -> crate::Result<()> {
+        let path = FsPath::utf16(path);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_CreateDirectory(
+                self.handle,
+                *path.as_raw(),
+                attributes.bits(),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Rename (or move) the file at `from` to `to`.
+    ///
+    /// Both paths are relative to this archive.
+    #[doc(alias = "FSUSER_RenameFile")]
+    pub fn rename_file(&self, from: &str, to: &str) -> crate::Result<()> {
+        let from = FsPath::utf16(from);
+        let to = FsPath::utf16(to);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_RenameFile(
+                self.handle,
+                *from.as_raw(),
+                self.handle,
+                *to.as_raw(),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Rename (or move) the directory at `from` to `to`.
+    ///
+    /// Both paths are relative to this archive.
+    #[doc(alias = "FSUSER_RenameDirectory")]
+    pub fn rename_dir(&self, from: &str, to: &str) -> crate::Result<()> {
+        let from = FsPath::utf16(from);
+        let to = FsPath::utf16(to);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_RenameDirectory(
+                self.handle,
+                *from.as_raw(),
+                self.handle,
+                *to.as_raw(),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the file at `path`.
+    #[doc(alias = "FSUSER_DeleteFile")]
+    pub fn remove_file(&self, path: &str) -> crate::Result<()> {
+        let path = FsPath::utf16(path);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_DeleteFile(self.handle, *path.as_raw()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the empty directory at `path`.
+    ///
+    /// Use [`Archive::remove_dir_all()`] to delete a directory along with its contents.
+    #[doc(alias = "FSUSER_DeleteDirectory")]
+    pub fn remove_dir(&self, path: &str) -> crate::Result<()> {
+        let path = FsPath::utf16(path);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_DeleteDirectory(
+                self.handle,
+                *path.as_raw(),
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the directory at `path` along with all of its contents.
+    #[doc(alias = "FSUSER_DeleteDirectoryRecursively")]
+    pub fn remove_dir_all(&self, path: &str) -> crate::Result<()> {
+        let path = FsPath::utf16(path);
+
+        unsafe {
+            ResultCode(ctru_sys::FSUSER_DeleteDirectoryRecursively(
+                self.handle,
+                *path.as_raw(),
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Archive<'_> {
+    #[doc(alias = "FSUSER_CloseArchive")]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ctru_sys::FSUSER_CloseArchive(self.handle);
+        }
+    }
+}
+
+// Amount of entries read from the file-system server at once while iterating over a directory.
+const READ_DIR_BATCH: usize = 16;
+
+/// Iterator over the entries of a directory within an [`Archive`].
+///
+/// This struct is returned by [`Archive::read_dir()`]. The directory is closed once dropped.
+pub struct ReadDir<'ar> {
+    handle: ctru_sys::Handle,
+    entries: Vec<ctru_sys::FS_DirectoryEntry>,
+    position: usize,
+    available: usize,
+    finished: bool,
+    _archive: PhantomData<&'ar Archive<'ar>>,
+}
+
+impl Iterator for ReadDir<'_> {
+    type Item = crate::Result<DirEntry>;
+
+    #[doc(alias = "FSDIR_Read")]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.available {
+            if self.finished {
+                return None;
+            }
+
+            let mut read = 0;
+
+            let result = ResultCode(unsafe {
+                ctru_sys::FSDIR_Read(
+                    self.handle,
+                    &mut read,
+                    self.entries.len() as u32,
+                    self.entries.as_mut_ptr(),
+                )
+            });
+
+            if let Err(e) = result {
+                self.finished = true;
+                return Some(Err(e));
+            }
+
+            self.position = 0;
+            self.available = read as usize;
+            self.finished = self.available < self.entries.len();
+
+            if self.available == 0 {
+                return None;
+            }
+        }
+
+        let entry = DirEntry::from_raw(&self.entries[self.position]);
+        self.position += 1;
+
+        Some(Ok(entry))
+    }
+}
+
+impl Drop for ReadDir<'_> {
+    #[doc(alias = "FSDIR_Close")]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ctru_sys::FSDIR_Close(self.handle);
+        }
+    }
+}
+
+/// Entry of a directory, returned by [`ReadDir`].
+#[doc(alias = "FS_DirectoryEntry")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirEntry {
+    name: String,
+    attributes: Attribute,
+    size: u64,
+}
+
+impl DirEntry {
+    fn from_raw(raw: &ctru_sys::FS_DirectoryEntry) -> Self {
+        Self {
+            name: decode_utf16_name(&raw.name),
+            attributes: Attribute::from_bits_retain(raw.attributes),
+            size: raw.fileSize,
+        }
+    }
+
+    /// Returns the name of this entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the attributes of this entry.
+    pub fn attributes(&self) -> Attribute {
+        self.attributes
+    }
+
+    /// Returns the size of this entry in bytes.
+    ///
+    /// Directories always have a size of 0.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns `true` if this entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.attributes.contains(Attribute::FS_ATTRIBUTE_DIRECTORY)
+    }
+
+    /// Returns `true` if this entry is a file.
+    pub fn is_file(&self) -> bool {
+        !self.is_dir()
+    }
+
+    /// Returns `true` if this entry is hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.attributes.contains(Attribute::FS_ATTRIBUTE_HIDDEN)
+    }
+
+    /// Returns `true` if this entry is marked as an archive.
+    pub fn is_archive(&self) -> bool {
+        self.attributes.contains(Attribute::FS_ATTRIBUTE_ARCHIVE)
+    }
+
+    /// Returns `true` if this entry is read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.attributes.contains(Attribute::FS_ATTRIBUTE_READ_ONLY)
+    }
+}
+
+/// A file opened within an [`Archive`].
+///
+/// The file keeps track of a cursor, which is used by the [`io::Read`], [`io::Write`] and [`io::Seek`] implementations.
+/// The file is closed once dropped.
+pub struct File<'ar> {
+    handle: ctru_sys::Handle,
+    offset: u64,
+    write_flags: Write,
+    _archive: PhantomData<&'ar Archive<'ar>>,
+}
+
+impl File<'_> {
+    /// Returns the size of the file in bytes.
+    #[doc(alias = "FSFILE_GetSize")]
+    pub fn len(&self) -> crate::Result<u64> {
+        let mut size = 0;
+
+        unsafe {
+            ResultCode(ctru_sys::FSFILE_GetSize(self.handle, &mut size))?;
+        }
+
+        Ok(size)
+    }
+
+    /// Returns `true` if the file is empty.
+    pub fn is_empty(&self) -> crate::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Truncate or extend the file to the given size.
+    #[doc(alias = "FSFILE_SetSize")]
+    pub fn set_len(&mut self, size: u64) -> crate::Result<()> {
+        unsafe {
+            ResultCode(ctru_sys::FSFILE_SetSize(self.handle, size))?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the flags used by subsequent writes to this file.
+    ///
+    /// No flags are set by default.
+    pub fn set_write_flags(&mut self, flags: Write) {
+        self.write_flags = flags;
+    }
+
+    /// Read data starting at `offset` into `buf`, without moving the file's cursor.
+    ///
+    /// Returns the amount of bytes read.
+    #[doc(alias = "FSFILE_Read")]
+    pub fn read_at(&self, offset: u64, buf: &mut [u8]) -> crate::Result<usize> {
+        let mut read = 0;
+        let size = buf.len().min(u32::MAX as usize) as u32;
+
+        unsafe {
+            ResultCode(ctru_sys::FSFILE_Read(
+                self.handle,
+                &mut read,
+                offset,
+                buf.as_mut_ptr().cast(),
+                size,
+            ))?;
+        }
+
+        Ok(read as usize)
+    }
+
+    /// Write the data in `buf` starting at `offset`, without moving the file's cursor.
+    ///
+    /// Returns the amount of bytes written.
+    #[doc(alias = "FSFILE_Write")]
+    pub fn write_at(&mut self, offset: u64, buf: &[u8]) -> crate::Result<usize> {
+        let mut written = 0;
+        let size = buf.len().min(u32::MAX as usize) as u32;
+
+        unsafe {
+            ResultCode(ctru_sys::FSFILE_Write(
+                self.handle,
+                &mut written,
+                offset,
+                buf.as_ptr().cast(),
+                size,
+                self.write_flags.bits(),
+            ))?;
+        }
+
+        Ok(written as usize)
+    }
+
+    /// Flush the file's data to the storage.
+    #[doc(alias = "FSFILE_Flush")]
+    pub fn sync(&mut self) -> crate::Result<()> {
+        unsafe {
+            ResultCode(ctru_sys::FSFILE_Flush(self.handle))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the raw `libctru` handle of this file.
+    pub fn as_raw(&self) -> ctru_sys::Handle {
+        self.handle
+    }
+
+    /// Consume the file, returning its raw `libctru` handle.
+    ///
+    /// The handle won't be closed, so the caller becomes responsible for closing it.
+    pub fn into_raw(self) -> ctru_sys::Handle {
+        let handle = self.handle;
+        std::mem::forget(self);
+        handle
+    }
+}
+
+impl io::Read for File<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.read_at(self.offset, buf)?;
+        self.offset += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl io::Write for File<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.write_at(self.offset, buf)?;
+        self.offset += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(self.sync()?)
+    }
+}
+
+impl io::Seek for File<'_> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, delta) = match pos {
+            io::SeekFrom::Start(offset) => {
+                self.offset = offset;
+                return Ok(offset);
+            }
+            io::SeekFrom::End(delta) => (self.len()?, delta),
+            io::SeekFrom::Current(delta) => (self.offset, delta),
+        };
+
+        match base.checked_add_signed(delta) {
+            Some(offset) => {
+                self.offset = offset;
+                Ok(offset)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl Drop for File<'_> {
+    #[doc(alias = "FSFILE_Close")]
+    fn drop(&mut self) {
+        unsafe {
+            let _ = ctru_sys::FSFILE_Close(self.handle);
+        }
+    }
+}
+
+// Decode a null-terminated UTF-16 name, as found in `FS_DirectoryEntry`.
+fn decode_utf16_name(name: &[u16]) -> String {
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+    String::from_utf16_lossy(&name[..len])
+}
+
+from_impl!(MediaType, ctru_sys::FS_MediaType);
+from_impl!(PathType, ctru_sys::FS_PathType);
+from_impl!(ArchiveID, ctru_sys::FS_ArchiveID);
+from_impl!(SystemMediaType, ctru_sys::FS_SystemMediaType);
+from_impl!(CardType, ctru_sys::FS_CardType);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_name_decoding() {
+        let mut name = [0u16; 8];
+        for (slot, c) in name.iter_mut().zip("ファイル".encode_utf16()) {
+            *slot = c;
+        }
+
+        assert_eq!(decode_utf16_name(&name), "ファイル");
+        assert_eq!(decode_utf16_name(&[0; 4]), "");
+    }
+}
@@ -0,0 +1,163 @@
+//! Mounting archives as devices.
+//!
+//! Once mounted under a device name (e.g. `save`), the contents of an archive can be accessed through the standard library's
+//! file-system APIs by prefixing paths with the device name, like `std::fs::read("save:/data.bin")`.
+//! This lets code which only knows about [`std::fs`] work with any archive.
+//!
+//! # Notes
+//!
+//! Only one archive can be mounted under a given device name at a time, and the `romfs` name is shared with the bundled RomFS.
+//! Like with `romfs:`, `std::path` has problems when parsing paths which include a device prefix,
+//! so it's suggested to use such paths directly or to only do simple append operations on them.
+#![doc(alias = "archiveMount")]
+
+use std::ffi::CString;
+use std::marker::PhantomData;
+
+use super::path::FsPath;
+use super::{Archive, ArchiveID, Fs};
+use crate::error::ResultCode;
+use crate::services::ServiceReference;
+use crate::Error;
+
+/// Guard for an archive mounted as a device.
+///
+/// The archive is unmounted once the guard is dropped.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs::mount::MountGuard;
+/// use ctru::services::fs::path::FsPath;
+/// use ctru::services::fs::{ArchiveID, Fs};
+///
+/// let fs = Fs::new()?;
+/// let save = MountGuard::new(&fs, ArchiveID::Savedata, &FsPath::empty(), "save")?;
+///
+/// std::fs::write("save:/settings.toml", "volume = 10")?;
+/// save.commit()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct MountGuard<'fs> {
+    device: String,
+    _service_handler: ServiceReference,
+    _fs: PhantomData<&'fs Fs>,
+}
+
+impl<'fs> MountGuard<'fs> {
+    /// Mount the archive with the specified ID and path under the `device` name.
+    ///
+    /// `device` must not contain the trailing colon (e.g. use `"save"` to access files via `save:/<file-path>`).
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ServiceAlreadyActive`] if another archive is already mounted under the same device name,
+    /// or an error if the device name isn't valid.
+    #[doc(alias = "archiveMount")]
+    pub fn new(_fs: &'fs Fs, id: ArchiveID, path: &FsPath, device: &str) -> crate::Result<Self> {
+        Self::mount(id, path, device)
+    }
+
+    /// Mount an already opened archive under the `device` name.
+    ///
+    /// The archive handle is closed and the same archive is re-opened by the device, so it's not available anymore
+    /// if mounting fails.
+    ///
+    /// # Errors
+    ///
+    /// See [`MountGuard::new()`].
+    #[doc(alias = "archiveMount")]
+    pub fn from_archive(archive: Archive<'fs>, device: &str) -> crate::Result<Self> {
+        let id = archive.id();
+        let path = archive.path().clone();
+        drop(archive);
+
+        Self::mount(id, &path, device)
+    }
+
+    fn mount(id: ArchiveID, path: &FsPath, device: &str) -> crate::Result<Self> {
+        let device_name = device_name(device)?;
+        let unmount_name = device_name.clone();
+
+        let _service_handler = ServiceReference::new_device(
+            device,
+            || {
+                ResultCode(unsafe {
+                    ctru_sys::archiveMount(id.into(), *path.as_raw(), device_name.as_ptr())
+                })?;
+
+                Ok(())
+            },
+            move || {
+                let _ = unsafe { ctru_sys::archiveUnmount(unmount_name.as_ptr()) };
+            },
+        )?;
+
+        Ok(Self {
+            device: device.to_owned(),
+            _service_handler,
+            _fs: PhantomData,
+        })
+    }
+
+    /// Returns the device name the archive is mounted under.
+    pub fn device_name(&self) -> &str {
+        &self.device
+    }
+
+    /// Commit the changes made to the mounted archive.
+    ///
+    /// This is only needed (and only works) for save data archives.
+    /// All files opened within the device must be closed before committing.
+    #[doc(alias = "archiveCommitSaveData")]
+    pub fn commit(&self) -> crate::Result<()> {
+        let device_name = device_name(&self.device)?;
+
+        ResultCode(unsafe { ctru_sys::archiveCommitSaveData(device_name.as_ptr()) })?;
+
+        Ok(())
+    }
+}
+
+// Validate the name of a device and convert it to a C string.
+pub(crate) fn device_name(device: &str) -> crate::Result<CString> {
+    if device.is_empty() || device.contains([':', '/']) {
+        return Err(Error::Other(format!("invalid device name {device:?}")));
+    }
+
+    CString::new(device).map_err(|_| Error::Other(format!("invalid device name {device:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mount_duplicate() {
+        let fs = Fs::new().unwrap();
+        let sdmc = MountGuard::new(&fs, ArchiveID::Sdmc, &FsPath::empty(), "sdtest").unwrap();
+
+        assert!(matches!(
+            MountGuard::new(&fs, ArchiveID::Sdmc, &FsPath::empty(), "sdtest"),
+            Err(Error::ServiceAlreadyActive)
+        ));
+
+        // The name is available again once the archive is unmounted.
+        drop(sdmc);
+        assert!(MountGuard::new(&fs, ArchiveID::Sdmc, &FsPath::empty(), "sdtest").is_ok());
+    }
+
+    #[test]
+    fn invalid_device_names() {
+        assert!(device_name("save").is_ok());
+        assert!(device_name("").is_err());
+        assert!(device_name("save:").is_err());
+        assert!(device_name("sa\0ve").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Owned file-system paths.
+//!
+//! The file-system service identifies archives and the entries within them using paths of different encodings (see [`PathType`]).
+//! [`FsPath`] owns the encoded data of such a path, and can lend it to `libctru` functions as a [`ctru_sys::FS_Path`].
+
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use ctru_formats::fs_path::{encode_ascii, encode_media_id, encode_utf16};
+
+use super::{MediaType, PathType};
+use crate::services::am::TitleId;
+use crate::Error;
+
+/// An owned, encoded file-system path.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// use ctru::services::fs::path::FsPath;
+/// use ctru::services::fs::PathType;
+///
+/// let path = FsPath::utf16("/saves/slot1.bin");
+///
+/// assert_eq!(path.path_type(), PathType::UTF16);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FsPath {
+    path_type: PathType,
+    data: Vec<u8>,
+}
+
+impl FsPath {
+    /// Create an empty path.
+    ///
+    /// Empty paths are used to open archives which don't need any further identification, such as [`ArchiveID::Sdmc`](super::ArchiveID::Sdmc).
+    pub fn empty() -> Self {
+        Self {
+            path_type: PathType::Empty,
+            data: vec![0],
+        }
+    }
+
+    /// Create a UTF-16 path.
+    ///
+    /// This is the encoding used for paths within most archives.
+    pub fn utf16(path: &str) -> Self {
+        Self {
+            path_type: PathType::UTF16,
+            data: encode_utf16(path),
+        }
+    }
+
+    /// Create an ASCII path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `path` contains non-ASCII or NUL characters.
+    pub fn ascii(path: &str) -> crate::Result<Self> {
+        Ok(Self {
+            path_type: PathType::ASCII,
+            data: encode_ascii(path)
+                .ok_or_else(|| Error::Other(format!("path {path:?} can't be encoded as ASCII")))?,
+        })
+    }
+
+    /// Create a binary path from raw bytes.
+    ///
+    /// The meaning of binary paths depends on the archive they are used with.
+    pub fn binary(data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            path_type: PathType::Binary,
+            data: data.into(),
+        }
+    }
+
+    /// Create the binary path identifying the save data of a title, as used by [`ArchiveID::UserSavedata`](super::ArchiveID::UserSavedata).
+    pub fn user_save_data(media_type: MediaType, title_id: TitleId) -> Self {
+        Self::binary(encode_media_id(media_type as u32, title_id.get()))
+    }
+
+    /// Create the binary path identifying an ExtData archive, as used by [`ArchiveID::Extdata`](super::ArchiveID::Extdata).
+    pub fn ext_data(media_type: MediaType, extdata_id: u64) -> Self {
+        Self::binary(encode_media_id(media_type as u32, extdata_id))
+    }
+
+    /// Returns the encoding of this path.
+    pub fn path_type(&self) -> PathType {
+        self.path_type
+    }
+
+    /// Returns the encoded data of this path, including the NUL terminator for text paths.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns a [`ctru_sys::FS_Path`] borrowing the data of this path.
+    pub fn as_raw(&self) -> RawFsPath<'_> {
+        RawFsPath {
+            raw: ctru_sys::FS_Path {
+                type_: self.path_type.into(),
+                size: self.data.len() as u32,
+                data: self.data.as_ptr().cast(),
+            },
+            _path: PhantomData,
+        }
+    }
+}
+
+impl From<&str> for FsPath {
+    fn from(path: &str) -> Self {
+        Self::utf16(path)
+    }
+}
+
+/// A [`ctru_sys::FS_Path`] borrowed from a [`FsPath`].
+///
+/// The raw path is only valid as long as the [`FsPath`] it was obtained from, which this struct ensures at compile time.
+#[derive(Copy, Clone)]
+pub struct RawFsPath<'a> {
+    raw: ctru_sys::FS_Path,
+    _path: PhantomData<&'a FsPath>,
+}
+
+impl Deref for RawFsPath<'_> {
+    type Target = ctru_sys::FS_Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_paths() {
+        assert_eq!(FsPath::utf16("/a").as_bytes(), [b'/', 0, b'a', 0, 0, 0]);
+        assert_eq!(FsPath::ascii("/a").unwrap().as_bytes(), b"/a\0");
+        assert!(FsPath::ascii("/ファイル.txt").is_err());
+    }
+
+    #[test]
+    fn save_data_lowpath() {
+        let path = FsPath::user_save_data(MediaType::Sd, TitleId::new(0x0004_0000_0012_3400));
+
+        assert_eq!(path.path_type(), PathType::Binary);
+        assert_eq!(
+            path.as_bytes(),
+            [1, 0, 0, 0, 0x00, 0x34, 0x12, 0x00, 0x00, 0x00, 0x04, 0x00]
+        );
+    }
+
+    #[test]
+    fn raw_path_borrows_data() {
+        let path = FsPath::utf16("/");
+        let raw = path.as_raw();
+
+        assert_eq!(raw.size, 4);
+        assert_eq!(raw.data, path.as_bytes().as_ptr().cast());
+    }
+}
@@ -0,0 +1,302 @@
+//! Save data management.
+//!
+//! Save data archives hold the persistent data of a title. Unlike most other archives, changes made to a save data archive
+//! are only stored permanently once they get committed, so every modification must be followed by a call to [`SaveData::commit()`].
+//!
+//! This module also contains functions to format save data archives and to handle the secure value of a title,
+//! which is used to prevent save data rollbacks.
+#![doc(alias = "save")]
+
+use ctru_formats::romfs::hash_table_len;
+
+use super::atomic;
+use super::path::FsPath;
+use super::{Archive, ArchiveID, Fs, MediaType};
+use crate::error::ResultCode;
+use crate::services::am::TitleId;
+
+/// Save data archive to operate on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Save data of the running title.
+    Current,
+    /// Save data of the inserted game card.
+    GameCard,
+    /// Save data of a specific installed title.
+    User {
+        /// Install location of the title.
+        media_type: MediaType,
+        /// ID of the title.
+        title_id: TitleId,
+    },
+}
+
+impl Source {
+    /// Returns the [`ArchiveID`] used to access this save data.
+    pub fn archive_id(&self) -> ArchiveID {
+        match self {
+            Self::Current => ArchiveID::Savedata,
+            Self::GameCard => ArchiveID::GameCardSavedata,
+            Self::User { .. } => ArchiveID::UserSavedata,
+        }
+    }
+
+    /// Returns the path identifying this save data within its archive.
+    pub fn path(&self) -> FsPath {
+        match *self {
+            Self::Current | Self::GameCard => FsPath::empty(),
+            Self::User {
+                media_type,
+                title_id,
+            } => FsPath::user_save_data(media_type, title_id),
+        }
+    }
+}
+
+/// Configuration used when formatting a save data archive.
+///
+/// See [`format()`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatConfig {
+    /// Size of the save data, in blocks of 512 bytes.
+    pub blocks: u32,
+    /// Maximum amount of directories in the archive.
+    pub directories: u32,
+    /// Maximum amount of files in the archive.
+    pub files: u32,
+    /// Whether the archive's data should be duplicated to protect it from corruption.
+    pub duplicate_data: bool,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 0x200,
+            directories: 10,
+            files: 10,
+            duplicate_data: true,
+        }
+    }
+}
+
+/// Guard for an opened save data archive.
+///
+/// Changes made to the save data are only kept once [`SaveData::commit()`] is called.
+/// If the guard is dropped after the archive was accessed without committing, a warning is printed to `stderr`.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use std::io::Write;
+///
+/// use ctru::services::fs::savedata::{SaveData, Source};
+/// use ctru::services::fs::{Attribute, Fs, Open};
+///
+/// let fs = Fs::new()?;
+/// let mut save = SaveData::open(&fs, Source::Current)?;
+///
+/// {
+///     let mut file = save.archive().open_file(
+///         "/progress.bin",
+///         Open::FS_OPEN_WRITE | Open::FS_OPEN_CREATE,
+///         Attribute::empty(),
+///     )?;
+///     file.write_all(&[1, 2, 3])?;
+/// }
+///
+/// // Without this, the written data would be lost.
+/// save.commit()?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+pub struct SaveData<'fs> {
+    archive: Archive<'fs>,
+    source: Source,
+    dirty: bool,
+}
+
+impl<'fs> SaveData<'fs> {
+    /// Open the specified save data archive.
+    #[doc(alias = "FSUSER_OpenArchive")]
+    pub fn open(_fs: &'fs Fs, source: Source) -> crate::Result<Self> {
+        let archive = Archive::open(source.archive_id(), &source.path())?;
+
+        Ok(Self {
+            archive,
+            source,
+            dirty: false,
+        })
+    }
+
+    /// Returns the save data archive this guard was opened for.
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Access the underlying [`Archive`].
+    ///
+    /// Since the archive may be modified through the returned reference, the save data is considered
+    /// uncommitted until the next call to [`SaveData::commit()`], even if it was only read from.
+    /// Use [`SaveData::read_atomic()`] to read files without having to commit afterwards.
+    pub fn archive(&mut self) -> &Archive<'fs> {
+        self.dirty = true;
+        &self.archive
+    }
+
+    /// Write `data` to the file at `path` without risking to lose its previous contents, then commit the save data.
+    ///
+    /// See the [`atomic`] module for more information.
+    pub fn write_atomic(&mut self, path: &str, data: &[u8]) -> Result<(), atomic::Error> {
+        atomic::write(&self.archive, path, data)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Read the contents of a file written by [`SaveData::write_atomic()`].
+    ///
+    /// See the [`atomic`] module for more information.
+    pub fn read_atomic(&self, path: &str) -> Result<Vec<u8>, atomic::Error> {
+        atomic::read(&self.archive, path)
+    }
+
+    /// Returns `true` if the archive was accessed through [`SaveData::archive()`] since the last commit.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Commit all changes made to the save data.
+    ///
+    /// # Notes
+    ///
+    /// All files opened within the archive must be closed before committing.
+    #[doc(alias = "FSUSER_ControlArchive")]
+    pub fn commit(&mut self) -> crate::Result<()> {
+        commit_archive(&self.archive)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+impl Drop for SaveData<'_> {
+    fn drop(&mut self) {
+        if self.dirty {
+            eprintln!(
+                "warning: save data ({:?}) was dropped without committing its changes",
+                self.source
+            );
+        }
+    }
+}
+
+/// Format a save data archive, erasing all of its contents.
+///
+/// The save data must not be opened while formatting.
+///
+/// # Example
+///
+/// ```
+/// # let _runner = test_runner::GdbRunner::default();
+/// # use std::error::Error;
+/// # fn main() -> Result<(), Box<dyn Error>> {
+/// #
+/// use ctru::services::fs::savedata::{self, FormatConfig, Source};
+/// use ctru::services::fs::Fs;
+///
+/// let fs = Fs::new()?;
+///
+/// let config = FormatConfig {
+///     directories: 4,
+///     files: 32,
+///     ..Default::default()
+/// };
+///
+/// savedata::format(&fs, Source::Current, &config)?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[doc(alias = "FSUSER_FormatSaveData")]
+pub fn format(_fs: &Fs, source: Source, config: &FormatConfig) -> crate::Result<()> {
+    let path = source.path();
+
+    unsafe {
+        ResultCode(ctru_sys::FSUSER_FormatSaveData(
+            source.archive_id().into(),
+            *path.as_raw(),
+            config.blocks,
+            config.directories,
+            config.files,
+            hash_table_len(config.directories),
+            hash_table_len(config.files),
+            config.duplicate_data,
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the secure value of the title with the specified ID, if one was set.
+///
+/// # Notes
+///
+/// Only the secure values of titles installed on the SD card can be accessed,
+/// since `SECUREVALUE_SLOT_SD` is the only slot exposed by `libctru`.
+#[doc(alias = "FSUSER_GetSaveDataSecureValue")]
+pub fn secure_value(_fs: &Fs, title_id: TitleId) -> crate::Result<Option<u64>> {
+    let mut exists = false;
+    let mut value = 0;
+
+    unsafe {
+        ResultCode(ctru_sys::FSUSER_GetSaveDataSecureValue(
+            &mut exists,
+            &mut value,
+            ctru_sys::SECUREVALUE_SLOT_SD,
+            title_id.unique_id(),
+            title_id.variation(),
+        ))?;
+    }
+
+    Ok(exists.then_some(value))
+}
+
+/// Set the secure value of the title with the specified ID.
+///
+/// # Notes
+///
+/// Like [`secure_value()`], this only works for titles installed on the SD card.
+#[doc(alias = "FSUSER_SetSaveDataSecureValue")]
+pub fn set_secure_value(_fs: &Fs, title_id: TitleId, value: u64) -> crate::Result<()> {
+    unsafe {
+        ResultCode(ctru_sys::FSUSER_SetSaveDataSecureValue(
+            value,
+            ctru_sys::SECUREVALUE_SLOT_SD,
+            title_id.unique_id(),
+            title_id.variation(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+// Commit the changes made to a save data archive.
+pub(super) fn commit_archive(archive: &Archive) -> crate::Result<()> {
+    unsafe {
+        ResultCode(ctru_sys::FSUSER_ControlArchive(
+            archive.as_raw(),
+            ctru_sys::ARCHIVE_ACTION_COMMIT_SAVE_DATA,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            0,
+        ))?;
+    }
+
+    Ok(())
+}
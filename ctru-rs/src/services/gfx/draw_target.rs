@@ -0,0 +1,74 @@
+//! [`embedded-graphics`](https://docs.rs/embedded-graphics) support.
+//!
+//! With the `embedded-graphics` feature enabled, every screen (except [`TopScreen3D`](super::TopScreen3D), whose sides can be drawn separately)
+//! implements [`DrawTarget`] and [`OriginDimensions`]. Drawing goes through the screen's [`Surface`](super::surface::Surface),
+//! so it works with every [`FramebufferFormat`](crate::services::gspgpu::FramebufferFormat) and with the wide mode of the top screen.
+
+use std::convert::Infallible;
+
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{OriginDimensions, Size};
+use embedded_graphics_core::pixelcolor::Rgb888;
+use embedded_graphics_core::Pixel;
+
+use super::pixel::Rgba8;
+use super::{BottomScreen, Screen, TopScreen, TopScreenLeft, TopScreenRight};
+
+// Size of the screen's current framebuffer, in logical coordinates.
+fn framebuffer_size(screen: &impl Screen) -> Size {
+    let mut width: u16 = 0;
+    let mut height: u16 = 0;
+
+    unsafe {
+        ctru_sys::gfxGetFramebuffer(
+            screen.as_raw(),
+            screen.side().into(),
+            &mut width,
+            &mut height,
+        );
+    }
+
+    // The framebuffer is rotated, so its "width" is the height of the screen.
+    Size::new(height.into(), width.into())
+}
+
+macro_rules! impl_draw_target {
+    ($($screen:ty),+ $(,)?) => {
+        $(
+            impl DrawTarget for $screen {
+                type Color = Rgb888;
+                type Error = Infallible;
+
+                fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+                where
+                    I: IntoIterator<Item = Pixel<Self::Color>>,
+                {
+                    let mut surface = self.surface();
+
+                    for Pixel(point, color) in pixels {
+                        // Pixels outside of the screen are ignored.
+                        if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y)) {
+                            surface.set_pixel(x, y, Rgba8::from(color));
+                        }
+                    }
+
+                    Ok(())
+                }
+
+                fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+                    self.surface().fill(Rgba8::from(color));
+
+                    Ok(())
+                }
+            }
+
+            impl OriginDimensions for $screen {
+                fn size(&self) -> Size {
+                    framebuffer_size(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_draw_target!(TopScreen, TopScreenLeft, TopScreenRight, BottomScreen);
@@ -13,6 +13,16 @@ use crate::sealed::Sealed;
 use crate::services::gspgpu::{self, FramebufferFormat};
 use crate::services::ServiceReference;
 
+#[cfg(feature = "embedded-graphics")]
+mod draw_target;
+
+pub use ctru_formats::gfx::{draw, pixel, surface, tiling};
+
+#[cfg(feature = "image")]
+pub use ctru_formats::gfx::image;
+
+use self::surface::Surface;
+
 /// Trait to handle common functionality for all screens.
 ///
 /// This trait is implemented by the screen structs for working with frame buffers and
@@ -51,10 +61,31 @@ pub trait Screen: Sealed {
         }
     }
 
+    /// Returns a [`Surface`] to safely read and write the pixels of the screen's current framebuffer.
+    ///
+    /// The surface uses the current [`FramebufferFormat`] of the screen, and its size follows the wide mode of the top screen
+    /// (see [`TopScreen::set_wide_mode()`]). Unlike with [`Screen::raw_framebuffer()`], the coordinates of the pixels
+    /// don't depend on the physical rotation of the screen: (0, 0) is the top-left corner.
+    ///
+    /// # Panics
+    ///
+    /// If the [`Gfx`] service was initialised via [`Gfx::with_formats_vram()`] this function will crash the program with an ARM exception.
+    fn surface(&mut self) -> Surface<'_> {
+        let format = self.framebuffer_format();
+        let framebuffer = self.raw_framebuffer();
+
+        let len = Surface::buffer_len(framebuffer.width, framebuffer.height, format);
+        // SAFETY: the framebuffer holds `len` bytes, and stays borrowed (through `self`) as long as the surface lives.
+        let data = unsafe { std::slice::from_raw_parts_mut(framebuffer.ptr, len) };
+
+        // The framebuffer is rotated, so its "width" is the height of the screen.
+        Surface::new(data, framebuffer.height, framebuffer.width, format)
+    }
+
     /// Gets the framebuffer format.
     #[doc(alias = "gfxGetScreenFormat")]
     fn framebuffer_format(&self) -> FramebufferFormat {
-        unsafe { ctru_sys::gfxGetScreenFormat(self.as_raw()) }.into()
+        FramebufferFormat::try_from(unsafe { ctru_sys::gfxGetScreenFormat(self.as_raw()) }).unwrap()
     }
 
     /// Change the framebuffer format.
@@ -1,11 +1,26 @@
 use crate::Error;
 use std::sync::{Mutex, MutexGuard, TryLockError};
 
+// Lock of the bundled RomFS, which is always mounted under the "romfs" device name.
+pub(crate) static ROMFS_ACTIVE: Mutex<()> = Mutex::new(());
+
+// Names of the devices currently mounted by a `ServiceReference`, other than "romfs".
+static ACTIVE_DEVICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
 pub(crate) struct ServiceReference {
-    _guard: MutexGuard<'static, ()>,
+    _guard: Guard,
     close: Box<dyn Fn() + Send + Sync>,
 }
 
+// What keeps other instances of the same service from being started.
+enum Guard {
+    Lock { _guard: MutexGuard<'static, ()> },
+    Device { _name: DeviceName },
+}
+
+// Reservation of a device name, released once dropped.
+struct DeviceName(String);
+
 impl ServiceReference {
     pub fn new<S, E>(counter: &'static Mutex<()>, start: S, close: E) -> crate::Result<Self>
     where
@@ -31,7 +46,30 @@ impl ServiceReference {
         start()?;
 
         Ok(Self {
-            _guard,
+            _guard: Guard::Lock { _guard },
+            close: Box::new(close),
+        })
+    }
+
+    /// Start a service which can have multiple instances told apart by their device name (e.g. mounted archives).
+    ///
+    /// Only one instance can use a given device name at a time. The "romfs" name shares the lock of the bundled RomFS.
+    pub fn new_device<S, E>(name: &str, start: S, close: E) -> crate::Result<Self>
+    where
+        S: FnOnce() -> crate::Result<()>,
+        E: Fn() + Send + Sync + 'static,
+    {
+        if name == "romfs" {
+            return Self::new(&ROMFS_ACTIVE, start, close);
+        }
+
+        // The name is released if starting the service fails.
+        let name = DeviceName::reserve(name)?;
+
+        start()?;
+
+        Ok(Self {
+            _guard: Guard::Device { _name: name },
             close: Box::new(close),
         })
     }
@@ -42,3 +80,25 @@ impl Drop for ServiceReference {
         (self.close)();
     }
 }
+
+impl DeviceName {
+    fn reserve(name: &str) -> crate::Result<Self> {
+        let mut devices = ACTIVE_DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+
+        if devices.iter().any(|device| device == name) {
+            return Err(Error::ServiceAlreadyActive);
+        }
+
+        devices.push(name.to_owned());
+
+        Ok(Self(name.to_owned()))
+    }
+}
+
+impl Drop for DeviceName {
+    fn drop(&mut self) {
+        let mut devices = ACTIVE_DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+
+        devices.retain(|device| *device != self.0);
+    }
+}
@@ -19,6 +19,13 @@
 //!
 //! Alternatively, you can include the RomFS archive manually when building with `3dsxtool`.
 //!
+//! To catch misspelled asset paths at compile time, call [`manifest::generate()`](crate::formats::romfs::manifest)
+//! from your own build script: it lists every file of the RomFS directory along with its size and CRC32 checksum,
+//! and generates a `romfs_path!` macro which fails to compile for paths that don't exist.
+//!
+//! Additional RomFS images (for example, asset packs shipped as separate files on the SD card)
+//! can be mounted alongside the bundled one under custom device names using [`RomFSMount`].
+//!
 //! # Notes
 //!
 //! `std::path` has problems when parsing file paths that include the `romfs:` prefix.
@@ -29,8 +36,10 @@
 
 use crate::error::ResultCode;
 use std::ffi::CStr;
-use std::sync::Mutex;
 
+use crate::services::fs::mount::device_name;
+use crate::services::fs::File;
+use crate::services::reference::ROMFS_ACTIVE;
 use crate::services::ServiceReference;
 
 /// Handle to the RomFS service.
@@ -38,8 +47,6 @@ pub struct RomFS {
     _service_handler: ServiceReference,
 }
 
-static ROMFS_ACTIVE: Mutex<()> = Mutex::new(());
-
 impl RomFS {
     /// Mount the bundled RomFS archive as a virtual drive.
     ///
@@ -79,9 +86,114 @@ impl RomFS {
     }
 }
 
+/// Handle to a RomFS image mounted from a file under a custom device name.
+///
+/// Any number of images can be mounted at the same time, as long as each of them uses a different device name.
+/// The image is unmounted when the handle is dropped.
+pub struct RomFSMount {
+    device: String,
+    _service_handler: ServiceReference,
+}
+
+impl RomFSMount {
+    /// Mount the RomFS image found at `offset` bytes into the file at `path`, making it accessible as `<device>:/`.
+    ///
+    /// `path` is a regular `std` path, such as `"sdmc:/3ds/assets/pack.romfs"`.
+    /// Use an `offset` of 0 for standalone RomFS images.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ServiceAlreadyActive`](crate::Error::ServiceAlreadyActive) if the device name is already in use,
+    /// or an error if the device name is invalid or the file doesn't contain a valid RomFS image.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let _runner = test_runner::GdbRunner::default();
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// #
+    /// use ctru::services::romfs::RomFSMount;
+    ///
+    /// let music = RomFSMount::from_path("sdmc:/3ds/game/music.romfs", 0, "music")?;
+    /// let levels = RomFSMount::from_path("sdmc:/3ds/game/levels.romfs", 0, "levels")?;
+    ///
+    /// let contents = std::fs::read("levels:/level1.bin");
+    /// #
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[doc(alias = "romfsMountFromFsdev")]
+    pub fn from_path(path: &str, offset: u32, device: &str) -> crate::Result<Self> {
+        let path = std::ffi::CString::new(path)
+            .map_err(|_| crate::Error::Other(format!("invalid path {path:?}")))?;
+
+        Self::mount(device, |name| {
+            ResultCode(unsafe {
+                ctru_sys::romfsMountFromFsdev(path.as_ptr(), offset, name.as_ptr())
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Mount the RomFS image found at `offset` bytes into an open [`File`], making it accessible as `<device>:/`.
+    ///
+    /// The file is consumed, and gets closed once the image is unmounted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`Error::ServiceAlreadyActive`](crate::Error::ServiceAlreadyActive) if the device name is already in use,
+    /// or an error if the device name is invalid or the file doesn't contain a valid RomFS image.
+    #[doc(alias = "romfsMountFromFile")]
+    pub fn from_file(file: File<'_>, offset: u32, device: &str) -> crate::Result<Self> {
+        // Only hand over the handle once the device name is known to be valid and free.
+        let mut file = Some(file);
+
+        Self::mount(device, |name| {
+            let handle = file.take().unwrap().into_raw();
+
+            // `libctru` takes ownership of the handle, even if mounting fails.
+            ResultCode(unsafe { ctru_sys::romfsMountFromFile(handle, offset, name.as_ptr()) })?;
+            Ok(())
+        })
+    }
+
+    /// Returns the name of the device the image is mounted as.
+    pub fn device_name(&self) -> &str {
+        &self.device
+    }
+
+    fn mount(
+        device: &str,
+        mut start: impl FnMut(&CStr) -> crate::Result<()>,
+    ) -> crate::Result<Self> {
+        let name = device_name(device)?;
+        let close_name = name.clone();
+
+        let _service_handler = ServiceReference::new_device(
+            device,
+            || start(&name),
+            move || {
+                let _ = unsafe { ctru_sys::romfsUnmount(close_name.as_ptr()) };
+            },
+        )?;
+
+        Ok(Self {
+            device: device.to_owned(),
+            _service_handler,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::formats::romfs::manifest::{self, ManifestEntry};
+    use crate::services::fs::mount::MountGuard;
+    use crate::services::fs::path::FsPath;
+    use crate::services::fs::{ArchiveID, Fs};
+
+    include!(concat!(env!("OUT_DIR"), "/romfs_manifest.rs"));
 
     // NOTE: this test only passes when run with a .3dsx, which for now requires separate build
     // and run steps so the 3dsx is built before the runner looks for the executable
@@ -94,4 +206,39 @@ mod tests {
 
         drop(romfs);
     }
+
+    #[test]
+    fn manifest_matches_contents() {
+        let _romfs = RomFS::new().unwrap();
+
+        for entry in ROMFS_MANIFEST {
+            let contents = std::fs::read(format!("romfs:/{}", entry.path)).unwrap();
+
+            assert_eq!(contents.len() as u64, entry.size);
+            assert_eq!(ctru_formats::checksum::crc32(&contents), entry.crc32);
+        }
+
+        assert_eq!(
+            manifest::find(ROMFS_MANIFEST, romfs_path!("test-file.txt")).map(|e| e.path),
+            Some("test-file.txt")
+        );
+        assert!(manifest::find(ROMFS_MANIFEST, "missing-file").is_none());
+    }
+
+    #[test]
+    fn mount_bundled_name() {
+        let _romfs = RomFS::new().unwrap();
+
+        assert!(matches!(
+            RomFSMount::from_path("sdmc:/test.romfs", 0, "romfs"),
+            Err(crate::Error::ServiceAlreadyActive)
+        ));
+        assert!(RomFSMount::from_path("sdmc:/test.romfs", 0, "bad:name").is_err());
+
+        let fs = Fs::new().unwrap();
+        assert!(matches!(
+            MountGuard::new(&fs, ArchiveID::Sdmc, &FsPath::empty(), "romfs"),
+            Err(crate::Error::ServiceAlreadyActive)
+        ));
+    }
 }